@@ -2,6 +2,7 @@ pub mod consts {
     include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
 }
 
+use std::collections::HashSet;
 use std::rc::Rc;
 
 use crate::context::{DataType, ShaderType};
@@ -55,6 +56,7 @@ impl ActiveInfo {
 #[derive(Clone)]
 pub struct GLContext {
     inner: Rc<InnerGl>,
+    extensions: Rc<HashSet<String>>,
 }
 
 impl GLContext {
@@ -62,8 +64,12 @@ impl GLContext {
     where
         for<'r> F: FnMut(&'r str) -> *const consts::types::GLvoid,
     {
+        let inner = InnerGl::load_with(loadfn);
+        // The supported extensions do not change during the lifetime of the context, so they are only queried once
+        let extensions = query_extensions(&inner);
         let gl = Self {
-            inner: Rc::new(InnerGl::load_with(loadfn)),
+            inner: Rc::new(inner),
+            extensions: Rc::new(extensions),
         };
         gl.bind_vertex_array(&gl.create_vertex_array().unwrap());
         gl.enable(consts::TEXTURE_CUBE_MAP_SEAMLESS);
@@ -76,6 +82,22 @@ impl GLContext {
         }
     }
 
+    pub fn get_supported_extensions(&self) -> Vec<String> {
+        self.extensions.iter().cloned().collect()
+    }
+
+    pub fn get_extension(&self, name: &str) -> bool {
+        self.extensions.contains(name)
+    }
+
+    pub fn get_parameter_i32(&self, pname: u32) -> i32 {
+        let mut value = 0;
+        unsafe {
+            self.inner.GetIntegerv(pname, &mut value);
+        }
+        value
+    }
+
     pub fn create_shader(&self, type_: ShaderType) -> Option<Shader> {
         let id = unsafe { self.inner.CreateShader(type_.to_const()) };
         Some(Shader(id))
@@ -960,6 +982,32 @@ impl GLContext {
         }
     }
 
+    pub fn compressed_tex_sub_image_2d(
+        &self,
+        target: u32,
+        level: u32,
+        x_offset: u32,
+        y_offset: u32,
+        width: u32,
+        height: u32,
+        format: u32,
+        data: &[u8],
+    ) {
+        unsafe {
+            self.inner.CompressedTexSubImage2D(
+                target,
+                level as i32,
+                x_offset as i32,
+                y_offset as i32,
+                width as i32,
+                height as i32,
+                format,
+                data.len() as i32,
+                data.as_ptr() as *const consts::types::GLvoid,
+            );
+        }
+    }
+
     pub fn tex_image_3d(
         &self,
         target: u32,
@@ -1225,6 +1273,27 @@ impl GLContext {
     }
 }
 
+fn query_extensions(gl: &InnerGl) -> HashSet<String> {
+    unsafe {
+        let mut count = 0;
+        gl.GetIntegerv(consts::NUM_EXTENSIONS, &mut count);
+        (0..count.max(0) as u32)
+            .filter_map(|index| {
+                let name = gl.GetStringi(consts::EXTENSIONS, index);
+                if name.is_null() {
+                    None
+                } else {
+                    Some(
+                        std::ffi::CStr::from_ptr(name as *const std::os::raw::c_char)
+                            .to_string_lossy()
+                            .to_string(),
+                    )
+                }
+            })
+            .collect()
+    }
+}
+
 fn create_whitespace_cstring_with_len(len: usize) -> std::ffi::CString {
     // allocate buffer of correct size
     let mut buffer: Vec<u8> = Vec::with_capacity(len + 1);
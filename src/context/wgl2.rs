@@ -31,6 +31,30 @@ impl GLContext {
         self.inner.finish();
     }
 
+    pub fn get_supported_extensions(&self) -> Vec<String> {
+        self.inner
+            .get_supported_extensions()
+            .map(|extensions| {
+                extensions
+                    .iter()
+                    .filter_map(|extension| extension.as_string())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn get_extension(&self, name: &str) -> bool {
+        self.inner.get_extension(name).ok().flatten().is_some()
+    }
+
+    pub fn get_parameter_i32(&self, pname: u32) -> i32 {
+        self.inner
+            .get_parameter(pname)
+            .ok()
+            .and_then(|value| value.as_f64())
+            .unwrap_or(0.0) as i32
+    }
+
     pub fn bind_buffer_base(&self, target: u32, index: u32, buffer: &Buffer) {
         self.inner.bind_buffer_base(target, index, Some(buffer));
     }
@@ -369,6 +393,30 @@ impl GLContext {
             .unwrap();
     }
 
+    pub fn compressed_tex_sub_image_2d(
+        &self,
+        target: u32,
+        level: u32,
+        x_offset: u32,
+        y_offset: u32,
+        width: u32,
+        height: u32,
+        format: u32,
+        data: &[u8],
+    ) {
+        self.inner
+            .compressed_tex_sub_image_2d_with_array_buffer_view(
+                target,
+                level as i32,
+                x_offset as i32,
+                y_offset as i32,
+                width as i32,
+                height as i32,
+                format,
+                &js_sys::Uint8Array::from(data),
+            );
+    }
+
     pub fn tex_image_2d_with_f32_data(
         &self,
         target: u32,
@@ -291,6 +291,25 @@ impl Context {
         )?;
         callback(camera2d.as_ref().unwrap())
     }
+
+    ///
+    /// Returns the number of uniform vectors with four components which are available in a vertex shader.
+    ///
+    pub(crate) fn max_vertex_uniform_vectors(&self) -> usize {
+        self.get_parameter_i32(crate::context::consts::MAX_VERTEX_UNIFORM_VECTORS)
+            .max(0) as usize
+    }
+
+    ///
+    /// Returns whether or not textures with the given compressed format can be uploaded to the GPU without decompressing them,
+    /// see [Texture2D::new_compressed] and [TextureCubeMap::new_compressed].
+    ///
+    pub fn supports_compressed_format(&self, format: CompressedFormat) -> bool {
+        format
+            .extension()
+            .map(|extension| self.get_extension(extension))
+            .unwrap_or(true)
+    }
 }
 
 impl std::ops::Deref for Context {
@@ -327,6 +346,22 @@ mod cpu_material;
 #[doc(inline)]
 pub use cpu_material::*;
 
+mod cpu_scene;
+#[doc(inline)]
+pub use cpu_scene::*;
+
+mod cpu_animation;
+#[doc(inline)]
+pub use cpu_animation::*;
+
+mod cpu_light;
+#[doc(inline)]
+pub use cpu_light::*;
+
+mod cpu_camera;
+#[doc(inline)]
+pub use cpu_camera::*;
+
 mod camera;
 #[doc(inline)]
 pub use camera::*;
@@ -383,6 +418,14 @@ pub enum CoreError {
     TextureCreation,
     #[error("invalid size of texture data (got {0} pixels but expected {1} pixels)")]
     InvalidTextureLength(usize, usize),
+    #[error("invalid size of compressed texture data for mip level {0} (got {1} bytes but expected {2} bytes)")]
+    InvalidCompressedTextureLength(u32, usize, usize),
+    #[error("textures with the {0:?} compression format cannot be decompressed on the CPU")]
+    UnsupportedCompressedFormat(CompressedFormat),
+    #[error("{0}x{1} is not a valid ASTC block size")]
+    InvalidASTCBlockSize(u8, u8),
+    #[error("the size {0}x{1} does not match the {2:?} cube map layout")]
+    InvalidCubeMapLayout(u32, u32, CubeMapLayout),
     #[error("the render call requires the {0} vertex buffer which is missing on the given mesh")]
     MissingMeshBuffer(String),
     #[error(
@@ -407,4 +450,16 @@ pub enum CoreError {
     NegativeDistance,
     #[error("a minimum must be smaller than a maximum")]
     MinimumLargerThanMaximum,
+    #[error("got {0} joint transformations but the model has {1} joints")]
+    InvalidJointCount(usize, usize),
+    #[error("a skinned model must have at least one joint")]
+    NoJoints,
+    #[error("a skinned model with {0} joints is not supported, the graphics context supports at most {1} joints")]
+    TooManyJoints(usize, usize),
+    #[error("morph target contains {0} position values but the mesh contains {1} position values")]
+    InvalidMorphTarget(usize, usize),
+    #[error("the {0} buffer contains {1} values but {2} values, one per vertex, were expected")]
+    InvalidVertexBufferLength(String, usize, usize),
+    #[error("got {0} morph weights but the model has {1} morph targets")]
+    InvalidMorphWeightCount(usize, usize),
 }
@@ -75,6 +75,46 @@ impl Camera {
         Ok(camera)
     }
 
+    ///
+    /// New camera constructed from a [CPUCamera], for example loaded from a glTF file.
+    /// The aspect ratio of the viewport is used instead of the aspect ratio of the [CPUCamera] and
+    /// an infinite far plane is replaced by a far plane at a distance of 10000 times the distance to the near plane.
+    ///
+    pub fn from_cpu_camera(
+        context: &Context,
+        viewport: Viewport,
+        cpu_camera: &CPUCamera,
+    ) -> ThreeDResult<Camera> {
+        let position = cpu_camera.position();
+        let target = position + cpu_camera.view_direction();
+        let up = cpu_camera.up();
+        match cpu_camera.projection {
+            CPUProjection::Orthographic {
+                height,
+                z_near,
+                z_far,
+                ..
+            } => Self::new_orthographic(
+                context, viewport, position, target, up, height, z_near, z_far,
+            ),
+            CPUProjection::Perspective {
+                field_of_view_y,
+                z_near,
+                z_far,
+                ..
+            } => Self::new_perspective(
+                context,
+                viewport,
+                position,
+                target,
+                up,
+                field_of_view_y,
+                z_near,
+                z_far.unwrap_or(z_near * 10000.0),
+            ),
+        }
+    }
+
     ///
     /// Specify the camera to use perspective projection with the given field of view in the y-direction and near and far plane.
     ///
@@ -0,0 +1,522 @@
+use crate::core::*;
+
+///
+/// Possible types of interpolation between the key frames in a [KeyFrames] sequence.
+///
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum KeyFrameInterpolation {
+    /// The value of a key frame is kept constant until the next key frame.
+    Step,
+    /// The values are linearly interpolated between key frames.
+    /// Rotations are interpolated using spherical linear interpolation.
+    Linear,
+    /// The values are interpolated using a cubic spline with the tangents specified in the key frames.
+    CubicSpline,
+}
+
+///
+/// A sequence of key frames, ie. values at specific points in time, which can be interpolated to get the value at any point in time.
+///
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeyFrames<T> {
+    /// The type of interpolation between the key frames.
+    pub interpolation: KeyFrameInterpolation,
+    /// The time in seconds of each key frame in increasing order.
+    pub times: Vec<f32>,
+    /// The value of each key frame.
+    /// If the interpolation is [KeyFrameInterpolation::CubicSpline], there are three contiguous values for each key frame,
+    /// the in-tangent, the value and the out-tangent, otherwise there is one value for each key frame.
+    pub values: Vec<T>,
+}
+
+impl KeyFrames<Vec3> {
+    ///
+    /// Returns the value at the given time in seconds or `None` if there are no key frames or if the key frames are malformed,
+    /// ie. if the number of values does not match the number of key frames, see [KeyFrames::values].
+    /// The time is clamped to the range spanned by the key frames.
+    ///
+    pub fn interpolate(&self, time: f32) -> Option<Vec3> {
+        interpolate(self, time)
+    }
+}
+
+impl KeyFrames<Quat> {
+    ///
+    /// Returns the rotation at the given time in seconds or `None` if there are no key frames or if the key frames are malformed,
+    /// ie. if the number of values does not match the number of key frames, see [KeyFrames::values].
+    /// The time is clamped to the range spanned by the key frames.
+    ///
+    pub fn interpolate(&self, time: f32) -> Option<Quat> {
+        interpolate(self, time).map(|rotation| rotation.normalize())
+    }
+}
+
+impl KeyFrames<Vec<f32>> {
+    ///
+    /// Returns the values at the given time in seconds or `None` if there are no key frames or if the key frames are malformed,
+    /// ie. if the number of values does not match the number of key frames, see [KeyFrames::values].
+    /// The time is clamped to the range spanned by the key frames.
+    ///
+    pub fn interpolate(&self, time: f32) -> Option<Vec<f32>> {
+        interpolate(self, time)
+    }
+}
+
+impl<T> KeyFrames<T> {
+    ///
+    /// Returns the time in seconds of the last key frame.
+    ///
+    pub fn duration(&self) -> f32 {
+        self.times.last().copied().unwrap_or(0.0)
+    }
+}
+
+trait KeyFrameValue: Clone {
+    fn lerp(&self, other: &Self, t: f32) -> Self;
+    fn weighted_sum(values: &[(&Self, f32)]) -> Self;
+}
+
+impl KeyFrameValue for Vec3 {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        VectorSpace::lerp(*self, *other, t)
+    }
+    fn weighted_sum(values: &[(&Self, f32)]) -> Self {
+        values
+            .iter()
+            .fold(Vec3::zero(), |sum, (value, weight)| sum + *value * *weight)
+    }
+}
+
+impl KeyFrameValue for Quat {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        self.slerp(*other, t)
+    }
+    fn weighted_sum(values: &[(&Self, f32)]) -> Self {
+        values
+            .iter()
+            .fold(Quat::zero(), |sum, (value, weight)| sum + *value * *weight)
+    }
+}
+
+impl KeyFrameValue for Vec<f32> {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        self.iter()
+            .zip(other.iter())
+            .map(|(a, b)| a + (b - a) * t)
+            .collect()
+    }
+    fn weighted_sum(values: &[(&Self, f32)]) -> Self {
+        let mut sum = vec![0.0; values[0].0.len()];
+        for (value, weight) in values {
+            for (s, v) in sum.iter_mut().zip(value.iter()) {
+                *s += v * weight;
+            }
+        }
+        sum
+    }
+}
+
+fn interpolate<T: KeyFrameValue>(key_frames: &KeyFrames<T>, time: f32) -> Option<T> {
+    let cubic_spline = key_frames.interpolation == KeyFrameInterpolation::CubicSpline;
+    let values_per_key_frame = if cubic_spline { 3 } else { 1 };
+    let count = key_frames.times.len();
+    if count == 0 || key_frames.values.len() != count * values_per_key_frame {
+        return None;
+    }
+    let times = &key_frames.times;
+    let value =
+        |index: usize| &key_frames.values[index * values_per_key_frame + values_per_key_frame / 2];
+    if count == 1 || time.is_nan() || time <= times[0] {
+        return Some(value(0).clone());
+    }
+    let last = count - 1;
+    if time >= times[last] {
+        return Some(value(last).clone());
+    }
+    let index = times.iter().position(|t| *t > time).unwrap_or(last).max(1) - 1;
+    let dt = times[index + 1] - times[index];
+    let t = if dt > 0.0 {
+        (time - times[index]) / dt
+    } else {
+        0.0
+    };
+    Some(match key_frames.interpolation {
+        KeyFrameInterpolation::Step => value(index).clone(),
+        KeyFrameInterpolation::Linear => value(index).lerp(value(index + 1), t),
+        KeyFrameInterpolation::CubicSpline => {
+            let t2 = t * t;
+            let t3 = t2 * t;
+            T::weighted_sum(&[
+                (value(index), 2.0 * t3 - 3.0 * t2 + 1.0),
+                (&key_frames.values[index * 3 + 2], (t3 - 2.0 * t2 + t) * dt),
+                (value(index + 1), -2.0 * t3 + 3.0 * t2),
+                (&key_frames.values[(index + 1) * 3], (t3 - t2) * dt),
+            ])
+        }
+    })
+}
+
+///
+/// The key frames animating the transformation of a single node in a [CPUScene].
+/// The components which are not animated are taken from the transformation of the node.
+///
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CPUAnimationTrack {
+    /// The index of the animated node in [CPUScene::nodes].
+    pub node: usize,
+    /// Key frames for the translation of the node relative to its parent.
+    pub translation: Option<KeyFrames<Vec3>>,
+    /// Key frames for the rotation of the node relative to its parent.
+    pub rotation: Option<KeyFrames<Quat>>,
+    /// Key frames for the scale of the node relative to its parent.
+    pub scale: Option<KeyFrames<Vec3>>,
+    /// Key frames for the weights of the morph targets of the meshes attached to the node, see [Model::set_morph_weights](crate::Model::set_morph_weights).
+    /// Each key frame value contains one weight for each morph target.
+    pub morph_weights: Option<KeyFrames<Vec<f32>>>,
+}
+
+impl CPUAnimationTrack {
+    ///
+    /// Returns the transformation of the node relative to its parent at the given time in seconds.
+    /// The translation, rotation or scale which are not animated by this track are taken from the given rest transformation.
+    ///
+    pub fn transformation(&self, time: f32, rest_transformation: &Mat4) -> Mat4 {
+        let (mut translation, mut rotation, mut scale) = decompose(rest_transformation);
+        if let Some(value) = self.translation.as_ref().and_then(|k| k.interpolate(time)) {
+            translation = value;
+        }
+        if let Some(value) = self.rotation.as_ref().and_then(|k| k.interpolate(time)) {
+            rotation = value;
+        }
+        if let Some(value) = self.scale.as_ref().and_then(|k| k.interpolate(time)) {
+            scale = value;
+        }
+        Mat4::from_translation(translation)
+            * Mat4::from(rotation)
+            * Mat4::from_nonuniform_scale(scale.x, scale.y, scale.z)
+    }
+
+    ///
+    /// Returns the time in seconds of the last key frame in this track.
+    ///
+    pub fn duration(&self) -> f32 {
+        self.translation
+            .as_ref()
+            .map(|k| k.duration())
+            .unwrap_or(0.0)
+            .max(self.rotation.as_ref().map(|k| k.duration()).unwrap_or(0.0))
+            .max(self.scale.as_ref().map(|k| k.duration()).unwrap_or(0.0))
+            .max(
+                self.morph_weights
+                    .as_ref()
+                    .map(|k| k.duration())
+                    .unwrap_or(0.0),
+            )
+    }
+
+    ///
+    /// Returns the weights of the morph targets of the meshes attached to the node at the given time in seconds
+    /// or `None` if this track does not animate the morph weights or has no morph weight key frames.
+    ///
+    pub fn morph_weights(&self, time: f32) -> Option<Vec<f32>> {
+        self.morph_weights
+            .as_ref()
+            .and_then(|key_frames| key_frames.interpolate(time))
+    }
+}
+
+fn decompose(transformation: &Mat4) -> (Vec3, Quat, Vec3) {
+    let translation = transformation.w.truncate();
+    let mut scale = vec3(
+        transformation.x.truncate().magnitude(),
+        transformation.y.truncate().magnitude(),
+        transformation.z.truncate().magnitude(),
+    );
+    let mut rotation = Mat3::from_cols(
+        transformation.x.truncate(),
+        transformation.y.truncate(),
+        transformation.z.truncate(),
+    );
+    if rotation.determinant() < 0.0 {
+        scale.x = -scale.x;
+    }
+    if scale.x != 0.0 && scale.y != 0.0 && scale.z != 0.0 {
+        rotation.x /= scale.x;
+        rotation.y /= scale.y;
+        rotation.z /= scale.z;
+    }
+    (translation, Quat::from(rotation).normalize(), scale)
+}
+
+///
+/// A CPU-side version of a key frame animation of the nodes in a [CPUScene].
+/// Can be constructed manually or loaded via [io](crate::io).
+///
+/// Use [world_transformations](Self::world_transformations) to evaluate the animation at a given time, for example
+/// `frame_input.accumulated_time as f32 * 0.001` since the accumulated time is in milliseconds, and apply the result to
+/// the [Model](crate::Model)s and [InstancedModel](crate::InstancedModel)s of the scene using [GeometryMut::set_transformation](crate::GeometryMut::set_transformation).
+///
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CPUAnimation {
+    /// Name.
+    pub name: String,
+    /// The animation tracks, at most one for each node.
+    pub tracks: Vec<CPUAnimationTrack>,
+}
+
+impl CPUAnimation {
+    ///
+    /// Returns the time in seconds of the last key frame in this animation.
+    /// Use this to loop the animation, for example by evaluating it at `time % duration`.
+    ///
+    pub fn duration(&self) -> f32 {
+        self.tracks
+            .iter()
+            .map(|track| track.duration())
+            .fold(0.0, f32::max)
+    }
+
+    ///
+    /// Returns the transformation of each node in the given scene relative to its parent at the given time in seconds.
+    /// Nodes which are not animated keep the transformation specified in the scene.
+    /// Tracks which animate a node that is not in the scene are ignored.
+    ///
+    pub fn local_transformations(&self, scene: &CPUScene, time: f32) -> Vec<Mat4> {
+        let mut transformations = scene
+            .nodes
+            .iter()
+            .map(|node| node.transformation)
+            .collect::<Vec<_>>();
+        for track in self.tracks.iter() {
+            if let Some(transformation) = transformations.get_mut(track.node) {
+                *transformation = track.transformation(time, transformation);
+            }
+        }
+        transformations
+    }
+
+    ///
+    /// Returns the weights of the morph targets of the meshes attached to the node with the given index at the given time in seconds
+    /// or `None` if this animation does not animate the morph weights of the node.
+    /// Apply the weights to the models of the node using [Model::set_morph_weights](crate::Model::set_morph_weights).
+    ///
+    pub fn morph_weights(&self, node_index: usize, time: f32) -> Option<Vec<f32>> {
+        self.tracks
+            .iter()
+            .find(|track| track.node == node_index)
+            .and_then(|track| track.morph_weights(time))
+    }
+
+    ///
+    /// Returns the transformation of each node in the given scene from the local space of the node to world space at the given time in seconds.
+    /// The transformations are listed in the same order as [CPUScene::nodes].
+    /// Each node is visited at most once starting from the roots, so if the hierarchy contains a cycle, the traversal stops at the node which closes the cycle.
+    /// Nodes which cannot be reached from the roots get the identity transformation.
+    ///
+    pub fn world_transformations(&self, scene: &CPUScene, time: f32) -> Vec<Mat4> {
+        let local_transformations = self.local_transformations(scene, time);
+        let mut transformations = vec![Mat4::identity(); scene.nodes.len()];
+        let mut visited = vec![false; scene.nodes.len()];
+        let mut stack = scene
+            .roots
+            .iter()
+            .map(|root| (*root, Mat4::identity()))
+            .collect::<Vec<_>>();
+        while let Some((node_index, parent_transformation)) = stack.pop() {
+            if node_index >= visited.len() || visited[node_index] {
+                continue;
+            }
+            visited[node_index] = true;
+            let transformation = parent_transformation * local_transformations[node_index];
+            transformations[node_index] = transformation;
+            for child in scene.nodes[node_index].children.iter() {
+                stack.push((*child, transformation));
+            }
+        }
+        transformations
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn key_frames(interpolation: KeyFrameInterpolation, values: Vec<Vec3>) -> KeyFrames<Vec3> {
+        KeyFrames {
+            interpolation,
+            times: vec![1.0, 2.0, 4.0],
+            values,
+        }
+    }
+
+    #[test]
+    pub fn step_sampling() {
+        let key_frames = key_frames(
+            KeyFrameInterpolation::Step,
+            vec![
+                vec3(1.0, 0.0, 0.0),
+                vec3(2.0, 0.0, 0.0),
+                vec3(3.0, 0.0, 0.0),
+            ],
+        );
+        assert_eq!(key_frames.interpolate(0.0), Some(vec3(1.0, 0.0, 0.0)));
+        assert_eq!(key_frames.interpolate(1.5), Some(vec3(1.0, 0.0, 0.0)));
+        assert_eq!(key_frames.interpolate(2.0), Some(vec3(2.0, 0.0, 0.0)));
+        assert_eq!(key_frames.interpolate(3.9), Some(vec3(2.0, 0.0, 0.0)));
+        assert_eq!(key_frames.interpolate(5.0), Some(vec3(3.0, 0.0, 0.0)));
+    }
+
+    #[test]
+    pub fn linear_sampling() {
+        let key_frames = key_frames(
+            KeyFrameInterpolation::Linear,
+            vec![
+                vec3(1.0, 0.0, 0.0),
+                vec3(2.0, 0.0, 0.0),
+                vec3(4.0, 2.0, 0.0),
+            ],
+        );
+        assert_eq!(key_frames.interpolate(-1.0), Some(vec3(1.0, 0.0, 0.0)));
+        assert_eq!(key_frames.interpolate(1.5), Some(vec3(1.5, 0.0, 0.0)));
+        assert_eq!(key_frames.interpolate(3.0), Some(vec3(3.0, 1.0, 0.0)));
+        assert_eq!(key_frames.interpolate(4.0), Some(vec3(4.0, 2.0, 0.0)));
+
+        let rotations = KeyFrames {
+            interpolation: KeyFrameInterpolation::Linear,
+            times: vec![0.0, 1.0],
+            values: vec![
+                Quat::from_angle_z(degrees(0.0)),
+                Quat::from_angle_z(degrees(90.0)),
+            ],
+        };
+        let rotation = rotations.interpolate(0.5).unwrap();
+        let expected = Quat::from_angle_z(degrees(45.0));
+        assert!((rotation - expected).magnitude() < 0.0001);
+    }
+
+    #[test]
+    pub fn cubic_spline_sampling() {
+        // In-tangent, value and out-tangent for each key frame
+        let key_frames = key_frames(
+            KeyFrameInterpolation::CubicSpline,
+            vec![
+                vec3(0.0, 0.0, 0.0),
+                vec3(1.0, 0.0, 0.0),
+                vec3(1.0, 0.0, 0.0),
+                vec3(1.0, 0.0, 0.0),
+                vec3(2.0, 0.0, 0.0),
+                vec3(0.0, 0.0, 0.0),
+                vec3(0.0, 0.0, 0.0),
+                vec3(3.0, 0.0, 0.0),
+                vec3(0.0, 0.0, 0.0),
+            ],
+        );
+        assert_eq!(key_frames.interpolate(1.0), Some(vec3(1.0, 0.0, 0.0)));
+        assert_eq!(key_frames.interpolate(2.0), Some(vec3(2.0, 0.0, 0.0)));
+        assert_eq!(key_frames.interpolate(4.0), Some(vec3(3.0, 0.0, 0.0)));
+        // A cubic with the same tangent as the straight line between the values is the straight line
+        let value = key_frames.interpolate(1.25).unwrap();
+        assert!((value - vec3(1.25, 0.0, 0.0)).magnitude() < 0.0001);
+        // Zero tangents give a smooth step
+        let value = key_frames.interpolate(3.0).unwrap();
+        assert!((value - vec3(2.5, 0.0, 0.0)).magnitude() < 0.0001);
+    }
+
+    #[test]
+    pub fn sampling_invalid_key_frames() {
+        let empty: KeyFrames<Vec3> = KeyFrames {
+            interpolation: KeyFrameInterpolation::Linear,
+            times: vec![],
+            values: vec![],
+        };
+        assert_eq!(empty.interpolate(1.0), None);
+
+        let missing_tangents = key_frames(
+            KeyFrameInterpolation::CubicSpline,
+            vec![
+                vec3(1.0, 0.0, 0.0),
+                vec3(2.0, 0.0, 0.0),
+                vec3(3.0, 0.0, 0.0),
+            ],
+        );
+        assert_eq!(missing_tangents.interpolate(1.5), None);
+
+        let missing_values = key_frames(
+            KeyFrameInterpolation::Linear,
+            vec![vec3(1.0, 0.0, 0.0), vec3(2.0, 0.0, 0.0)],
+        );
+        assert_eq!(missing_values.interpolate(1.5), None);
+        assert_eq!(missing_values.interpolate(5.0), None);
+
+        let key_frames = key_frames(
+            KeyFrameInterpolation::Linear,
+            vec![
+                vec3(1.0, 0.0, 0.0),
+                vec3(2.0, 0.0, 0.0),
+                vec3(3.0, 0.0, 0.0),
+            ],
+        );
+        assert_eq!(key_frames.interpolate(f32::NAN), Some(vec3(1.0, 0.0, 0.0)));
+        assert_eq!(
+            key_frames.interpolate(f32::INFINITY),
+            Some(vec3(3.0, 0.0, 0.0))
+        );
+        assert_eq!(
+            key_frames.interpolate(f32::NEG_INFINITY),
+            Some(vec3(1.0, 0.0, 0.0))
+        );
+    }
+
+    #[test]
+    pub fn invalid_scene() {
+        let translation = |x| Mat4::from_translation(vec3(x, 0.0, 0.0));
+        // The nodes form a cycle and the scene refers to nodes which do not exist
+        let scene = CPUScene {
+            nodes: vec![
+                CPUNode {
+                    transformation: translation(1.0),
+                    children: vec![1, 7],
+                    ..Default::default()
+                },
+                CPUNode {
+                    transformation: translation(2.0),
+                    parent: Some(0),
+                    children: vec![0],
+                    ..Default::default()
+                },
+                CPUNode {
+                    transformation: translation(4.0),
+                    ..Default::default()
+                },
+            ],
+            roots: vec![0, 3],
+            ..Default::default()
+        };
+        let animation = CPUAnimation {
+            name: String::new(),
+            tracks: vec![CPUAnimationTrack {
+                node: 5,
+                translation: Some(KeyFrames {
+                    interpolation: KeyFrameInterpolation::Step,
+                    times: vec![0.0],
+                    values: vec![vec3(1.0, 1.0, 1.0)],
+                }),
+                rotation: None,
+                scale: None,
+                morph_weights: None,
+            }],
+        };
+        assert_eq!(
+            animation.local_transformations(&scene, 0.0),
+            vec![translation(1.0), translation(2.0), translation(4.0)]
+        );
+        assert_eq!(
+            animation.world_transformations(&scene, 0.0),
+            vec![translation(1.0), translation(3.0), Mat4::identity()]
+        );
+    }
+}
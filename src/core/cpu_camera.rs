@@ -0,0 +1,76 @@
+use crate::core::*;
+
+///
+/// The type of projection used by a [CPUCamera] including parameters.
+///
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CPUProjection {
+    /// Orthographic projection.
+    Orthographic {
+        /// Height of the camera film/sensor.
+        height: f32,
+        /// Width of the camera film/sensor.
+        width: f32,
+        /// The distance to the near plane.
+        z_near: f32,
+        /// The distance to the far plane.
+        z_far: f32,
+    },
+    /// Perspective projection.
+    Perspective {
+        /// The field of view angle in the vertical direction.
+        field_of_view_y: Radians,
+        /// The aspect ratio (width divided by height) of the camera or `None` if the aspect ratio of the viewport should be used.
+        aspect_ratio: Option<f32>,
+        /// The distance to the near plane.
+        z_near: f32,
+        /// The distance to the far plane or `None` if the far plane is infinitely far away.
+        z_far: Option<f32>,
+    },
+}
+
+///
+/// A CPU-side version of a camera.
+/// Can be constructed manually or loaded via [io](crate::io) and instantiated using [Camera::from_cpu_camera].
+///
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CPUCamera {
+    /// Name.
+    pub name: String,
+    /// The projection type and parameters.
+    pub projection: CPUProjection,
+    /// The index of the node in [CPUScene::nodes] which the camera is attached to or `None` if the camera is not part of a scene.
+    pub node: Option<usize>,
+    /// The transformation from the local space of the camera to world space.
+    /// The camera is positioned at the origin, looks in the direction of the negative z-axis and the positive y-axis is up in its local space.
+    pub transformation: Mat4,
+}
+
+impl CPUCamera {
+    ///
+    /// Returns the position of the camera in world space.
+    ///
+    pub fn position(&self) -> Vec3 {
+        self.transformation.w.truncate()
+    }
+
+    ///
+    /// Returns the direction the camera is looking in world space.
+    ///
+    pub fn view_direction(&self) -> Vec3 {
+        (self.transformation * vec4(0.0, 0.0, -1.0, 0.0))
+            .truncate()
+            .normalize()
+    }
+
+    ///
+    /// Returns the up direction of the camera in world space.
+    ///
+    pub fn up(&self) -> Vec3 {
+        (self.transformation * vec4(0.0, 1.0, 0.0, 0.0))
+            .truncate()
+            .normalize()
+    }
+}
@@ -0,0 +1,78 @@
+use crate::core::*;
+
+///
+/// The type of a [CPULight] including the parameters specific to that type.
+///
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CPULightType {
+    /// A light which shines in the direction of the light from infinitely far away, see [DirectionalLight](crate::DirectionalLight).
+    Directional,
+    /// A light which shines in all directions from the position of the light, see [PointLight](crate::PointLight).
+    Point,
+    /// A light which shines in a cone from the position of the light and in the direction of the light, see [SpotLight](crate::SpotLight).
+    Spot {
+        /// The angle from the direction of the light to where the intensity starts to fall off.
+        inner_cone_angle: Radians,
+        /// The angle from the direction of the light to where the intensity is zero.
+        outer_cone_angle: Radians,
+    },
+}
+
+///
+/// A CPU-side version of a light.
+/// Can be constructed manually or loaded via [io](crate::io) and
+/// instantiated using for example [DirectionalLight::from_cpu_light](crate::DirectionalLight::from_cpu_light).
+///
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CPULight {
+    /// Name.
+    pub name: String,
+    /// The type of light.
+    pub light_type: CPULightType,
+    /// The color of the light.
+    pub color: Color,
+    /// The intensity of the light.
+    pub intensity: f32,
+    /// The distance where the light intensity is considered to reach zero or `None` if the range is infinite.
+    /// Not used for directional lights.
+    pub range: Option<f32>,
+    /// The index of the node in [CPUScene::nodes] which the light is attached to or `None` if the light is not part of a scene.
+    pub node: Option<usize>,
+    /// The transformation from the local space of the light to world space.
+    /// The light is positioned at the origin and shines in the direction of the negative z-axis in its local space.
+    pub transformation: Mat4,
+}
+
+impl Default for CPULight {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            light_type: CPULightType::Point,
+            color: Color::WHITE,
+            intensity: 1.0,
+            range: None,
+            node: None,
+            transformation: Mat4::identity(),
+        }
+    }
+}
+
+impl CPULight {
+    ///
+    /// Returns the position of the light in world space.
+    ///
+    pub fn position(&self) -> Vec3 {
+        self.transformation.w.truncate()
+    }
+
+    ///
+    /// Returns the direction the light is shining in world space.
+    ///
+    pub fn direction(&self) -> Vec3 {
+        (self.transformation * vec4(0.0, 0.0, -1.0, 0.0))
+            .truncate()
+            .normalize()
+    }
+}
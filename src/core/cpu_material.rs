@@ -6,6 +6,7 @@ use crate::core::*;
 /// Lighting models which specify how the lighting is computed when rendering a material.
 /// This is a trade-off between how fast the computations are versus how physically correct they look.
 #[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum LightingModel {
     /// Phong lighting model.
     /// The fastest lighting model to calculate.
@@ -21,6 +22,7 @@ pub enum LightingModel {
 /// The geometry function used in a Cook-Torrance lighting model.
 #[derive(Debug, Copy, Clone, PartialEq)]
 #[allow(missing_docs)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GeometryFunction {
     SmithSchlickGGX,
 }
@@ -28,6 +30,7 @@ pub enum GeometryFunction {
 /// The normal distribution function used in a Cook-Torrance lighting model.
 #[derive(Debug, Copy, Clone, PartialEq)]
 #[allow(missing_docs)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum NormalDistributionFunction {
     Blinn,
     Beckmann,
@@ -50,10 +53,42 @@ impl LightingModel {
     }
 }
 
+///
+/// Specifies how the alpha value of the albedo color and albedo texture of a material is interpreted.
+///
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AlphaMode {
+    /// The material is rendered as transparent if the albedo color or the albedo texture contains alpha values below the maximum.
+    #[default]
+    Auto,
+    /// The alpha value is ignored and the material is rendered fully opaque.
+    Opaque,
+    /// Fragments with an alpha value below the alpha cutout value, see [CPUMaterial::alpha_cutout], are discarded while the rest are rendered fully opaque.
+    Mask,
+    /// The material is always rendered as transparent and blended with the background.
+    Blend,
+}
+
+impl AlphaMode {
+    ///
+    /// Returns the alpha value below which fragments are discarded, if any.
+    /// The cutout is used when the alpha mode is [AlphaMode::Mask], with a default value of 0.5, or when it is [AlphaMode::Auto] and a cutout value is given.
+    ///
+    pub(crate) fn cutout(&self, alpha_cutout: Option<f32>) -> Option<f32> {
+        match self {
+            AlphaMode::Mask => Some(alpha_cutout.unwrap_or(0.5)),
+            AlphaMode::Auto => alpha_cutout,
+            AlphaMode::Opaque | AlphaMode::Blend => None,
+        }
+    }
+}
+
 ///
 /// A CPU-side version of a material.
 /// Can be constructed manually or loaded via [io](crate::io).
 ///
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CPUMaterial {
     /// Name. Used for matching geometry and material.
     pub name: String,
@@ -87,8 +122,21 @@ pub struct CPUMaterial {
     pub emissive: Color,
     /// Texture with color of light shining from an object.
     pub emissive_texture: Option<CPUTexture<u8>>,
-    /// Alpha cutout value for transparency in deferred rendering pipeline.
+    /// A scalar multiplier applied to the [Self::emissive] color and [Self::emissive_texture], which makes it possible to specify light shining from an object brighter than the maximum color value.
+    pub emissive_strength: f32,
+    /// Fragments with an alpha value below this value are discarded if the alpha mode is [AlphaMode::Auto] or [AlphaMode::Mask].
+    /// If the alpha mode is [AlphaMode::Mask] and no value is specified, 0.5 is used.
     pub alpha_cutout: Option<f32>,
+    /// Specifies how the alpha value of the albedo color and albedo texture is interpreted.
+    pub alpha_mode: AlphaMode,
+    /// Whether or not both sides of the triangles are rendered. If false, the back-facing triangles are culled.
+    pub double_sided: bool,
+    /// Whether or not the material is unlit, ie. the albedo color is rendered as it is without being affected by lights.
+    pub unlit: bool,
+    /// The transformation of the uv coordinates before sampling the textures of this material.
+    /// It is not applied when constructing a material from this CPU material since the transformation is a property of the model,
+    /// so apply it using for example [Model::set_texture_transform](crate::Model::set_texture_transform).
+    pub texture_transform: Mat3,
     /// The lighting model used when rendering this material
     pub lighting_model: LightingModel,
 }
@@ -109,7 +157,12 @@ impl Default for CPUMaterial {
             normal_scale: 1.0,
             emissive: Color::BLACK,
             emissive_texture: None,
+            emissive_strength: 1.0,
             alpha_cutout: None,
+            alpha_mode: AlphaMode::Auto,
+            double_sided: true,
+            unlit: false,
+            texture_transform: Mat3::identity(),
             lighting_model: LightingModel::Blinn,
         }
     }
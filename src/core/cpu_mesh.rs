@@ -1,8 +1,18 @@
 use crate::core::*;
 
+mod simplification;
+#[doc(inline)]
+pub use simplification::*;
+
+mod optimization;
+#[doc(inline)]
+pub use optimization::*;
+
 ///
 /// An array of indices. Supports different data types.
 ///
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Indices {
     /// Uses unsigned 8 bit integer for each index.
     U8(Vec<u8>),
@@ -42,7 +52,8 @@ impl std::fmt::Debug for Indices {
 /// Can be constructed manually or loaded via [io](crate::io)
 /// or via the utility functions for generating simple triangle meshes.
 ///
-#[derive(Default)]
+#[derive(Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CPUMesh {
     /// Name.
     pub name: String,
@@ -63,6 +74,41 @@ pub struct CPUMesh {
     /// The colors of the vertices. Four contiguous bytes defines a color `(r, g, b, a)`, therefore the length must be divisable by 4.
     /// The colors are assumed to be in linear space.
     pub colors: Option<Vec<u8>>,
+    /// The indices of the joints which influence the vertices, used for skinning. Four contiguous values defines the indices of up to four joints `(j0, j1, j2, j3)`, therefore the length must be divisable by 4.
+    /// The indices refer to the joints of the [CPUSkin] applied to this mesh.
+    pub joints: Option<Vec<u16>>,
+    /// The weights of the joints which influence the vertices, used for skinning. Four contiguous floats defines the weights of the four joints specified in [Self::joints], therefore the length must be divisable by 4.
+    /// The weights of a vertex should sum to one.
+    pub weights: Option<Vec<f32>>,
+    /// Morph targets, also called blend shapes, which deform the mesh when they are blended together with a set of weights,
+    /// see [Model::set_morph_weights](crate::Model::set_morph_weights).
+    pub morph_targets: Vec<MorphTarget>,
+}
+
+///
+/// A morph target, also called a blend shape, which contains displacements of the vertices of a [CPUMesh].
+/// The displacements are multiplied by a weight and added to the vertex attributes of the mesh.
+///
+#[derive(Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MorphTarget {
+    /// The displacements of the positions. Three contiguous floats defines a displacement `(x, y, z)` for each vertex in the mesh.
+    pub positions: Vec<f32>,
+    /// The displacements of the normals. Three contiguous floats defines a displacement `(x, y, z)` for each vertex in the mesh.
+    pub normals: Option<Vec<f32>>,
+    /// The displacements of the tangents. Three contiguous floats defines a displacement `(x, y, z)` for each vertex in the mesh,
+    /// the handedness of the tangent is not displaced.
+    pub tangents: Option<Vec<f32>>,
+}
+
+impl std::fmt::Debug for MorphTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut d = f.debug_struct("MorphTarget");
+        d.field("positions", &self.positions.len());
+        d.field("normals", &self.normals.as_ref().map(|v| v.len()));
+        d.field("tangents", &self.tangents.as_ref().map(|v| v.len()));
+        d.finish()
+    }
 }
 
 impl std::fmt::Debug for CPUMesh {
@@ -76,6 +122,9 @@ impl std::fmt::Debug for CPUMesh {
         d.field("tangents", &self.tangents.as_ref().map(|v| v.len()));
         d.field("uvs", &self.uvs.as_ref().map(|v| v.len()));
         d.field("colors", &self.colors.as_ref().map(|v| v.len()));
+        d.field("joints", &self.joints.as_ref().map(|v| v.len()));
+        d.field("weights", &self.weights.as_ref().map(|v| v.len()));
+        d.field("morph targets", &self.morph_targets);
         d.finish()
     }
 }
@@ -95,6 +144,7 @@ impl CPUMesh {
 
     ///
     /// Transforms the mesh by the given transformation.
+    /// If the transformation is not invertible, the normals and tangents are not changed.
     ///
     pub fn transform(&mut self, transform: &Mat4) {
         for i in 0..self.positions.len() / 3 {
@@ -110,7 +160,10 @@ impl CPUMesh {
             self.positions[i * 3 + 1] = p.y;
             self.positions[i * 3 + 2] = p.z;
         }
-        let normal_transform = transform.invert().unwrap().transpose();
+        let normal_transform = transform
+            .invert()
+            .map(|m| m.transpose())
+            .unwrap_or_else(Mat4::identity);
 
         if let Some(ref mut normals) = self.normals {
             for i in 0..normals.len() / 3 {
@@ -122,6 +175,35 @@ impl CPUMesh {
             }
         }
 
+        for morph_target in self.morph_targets.iter_mut() {
+            for i in 0..morph_target.positions.len() / 3 {
+                let p = (transform
+                    * vec4(
+                        morph_target.positions[i * 3],
+                        morph_target.positions[i * 3 + 1],
+                        morph_target.positions[i * 3 + 2],
+                        0.0,
+                    ))
+                .truncate();
+                morph_target.positions[i * 3] = p.x;
+                morph_target.positions[i * 3 + 1] = p.y;
+                morph_target.positions[i * 3 + 2] = p.z;
+            }
+            for data in morph_target
+                .normals
+                .iter_mut()
+                .chain(morph_target.tangents.iter_mut())
+            {
+                for i in 0..data.len() / 3 {
+                    let n =
+                        normal_transform * vec4(data[i * 3], data[i * 3 + 1], data[i * 3 + 2], 0.0);
+                    data[i * 3] = n.x;
+                    data[i * 3 + 1] = n.y;
+                    data[i * 3 + 2] = n.z;
+                }
+            }
+        }
+
         if let Some(ref mut tangents) = self.tangents {
             for i in 0..tangents.len() / 4 {
                 let t = normal_transform
@@ -414,8 +496,363 @@ impl CPUMesh {
         arrow
     }
 
+    ///
+    /// Returns a torus mesh lying in the xy-plane with center in `(0, 0, 0)`, the center of the tube at distance 1 from the center
+    /// and the given tube radius, which should be in the range `]0..1[`.
+    /// The angle subdivisions are the number of segments around the z-axis and the tube subdivisions are the number of segments around the tube.
+    ///
+    pub fn torus(tube_radius: f32, angle_subdivisions: u32, tube_subdivisions: u32) -> Self {
+        let profile = (0..=tube_subdivisions)
+            .map(|i| {
+                let angle = 2.0 * std::f32::consts::PI * (i % tube_subdivisions) as f32
+                    / tube_subdivisions as f32;
+                ProfilePoint {
+                    axial: tube_radius * angle.sin(),
+                    radial: 1.0 + tube_radius * angle.cos(),
+                    normal: vec2(angle.sin(), angle.cos()),
+                }
+            })
+            .collect::<Vec<_>>();
+        revolve(
+            "torus",
+            &profile,
+            [
+                vec3(0.0, 0.0, 1.0),
+                vec3(1.0, 0.0, 0.0),
+                vec3(0.0, 1.0, 0.0),
+            ],
+            angle_subdivisions,
+        )
+    }
+
+    ///
+    /// Returns a capsule mesh around the x-axis with center in `(0, 0, 0)`, that is a cylinder in the range `[-length/2..length/2]`
+    /// with the given radius and a hemisphere with the same radius at each end.
+    /// The angle subdivisions are the number of segments around the x-axis and each hemisphere has a quarter of that many rings.
+    ///
+    pub fn capsule(length: f32, radius: f32, angle_subdivisions: u32) -> Self {
+        let ring_subdivisions = (angle_subdivisions / 4).max(1);
+        let mut profile = Vec::new();
+        for (center, start_angle) in [(-0.5 * length, 0.0), (0.5 * length, 0.5)].iter() {
+            for i in 0..=ring_subdivisions {
+                let angle = std::f32::consts::PI
+                    * (start_angle + 0.5 * i as f32 / ring_subdivisions as f32);
+                profile.push(ProfilePoint {
+                    axial: center - radius * angle.cos(),
+                    radial: radius * angle.sin().max(0.0),
+                    normal: vec2(-angle.cos(), angle.sin()),
+                });
+            }
+        }
+        revolve(
+            "capsule",
+            &profile,
+            [
+                vec3(1.0, 0.0, 0.0),
+                vec3(0.0, 1.0, 0.0),
+                vec3(0.0, 0.0, 1.0),
+            ],
+            angle_subdivisions,
+        )
+    }
+
+    ///
+    /// Returns a sphere mesh with radius 1 and center in `(0, 0, 0)` made by subdividing each triangle of an icosahedron into four triangles the given number of times.
+    /// Compared to [CPUMesh::sphere], the triangles are almost equally sized and evenly distributed.
+    /// The uv coordinates are spherical coordinates, so the vertices along the seam at the negative x-axis are duplicated.
+    ///
+    pub fn icosphere(subdivisions: u32) -> Self {
+        let t = (1.0 + 5.0f32.sqrt()) / 2.0;
+        let mut points = vec![
+            vec3(-1.0, t, 0.0),
+            vec3(1.0, t, 0.0),
+            vec3(-1.0, -t, 0.0),
+            vec3(1.0, -t, 0.0),
+            vec3(0.0, -1.0, t),
+            vec3(0.0, 1.0, t),
+            vec3(0.0, -1.0, -t),
+            vec3(0.0, 1.0, -t),
+            vec3(t, 0.0, -1.0),
+            vec3(t, 0.0, 1.0),
+            vec3(-t, 0.0, -1.0),
+            vec3(-t, 0.0, 1.0),
+        ]
+        .into_iter()
+        .map(|p| p.normalize())
+        .collect::<Vec<_>>();
+        let mut triangles: Vec<[u32; 3]> = vec![
+            [0, 11, 5],
+            [0, 5, 1],
+            [0, 1, 7],
+            [0, 7, 10],
+            [0, 10, 11],
+            [1, 5, 9],
+            [5, 11, 4],
+            [11, 10, 2],
+            [10, 7, 6],
+            [7, 1, 8],
+            [3, 9, 4],
+            [3, 4, 2],
+            [3, 2, 6],
+            [3, 6, 8],
+            [3, 8, 9],
+            [4, 9, 5],
+            [2, 4, 11],
+            [6, 2, 10],
+            [8, 6, 7],
+            [9, 8, 1],
+        ];
+
+        for _ in 0..subdivisions {
+            let mut midpoints = std::collections::HashMap::new();
+            let mut midpoint = |a: u32, b: u32| {
+                *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                    points.push((points[a as usize] + points[b as usize]).normalize());
+                    points.len() as u32 - 1
+                })
+            };
+            triangles = triangles
+                .iter()
+                .flat_map(|&[a, b, c]| {
+                    let ab = midpoint(a, b);
+                    let bc = midpoint(b, c);
+                    let ca = midpoint(c, a);
+                    vec![[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+                })
+                .collect();
+        }
+
+        // Each corner gets the uv coordinates which fit the triangle, so vertices on the seam and at the poles are duplicated
+        let mut vertices = std::collections::HashMap::new();
+        let mut positions = Vec::new();
+        let mut uvs = Vec::new();
+        let mut indices = Vec::new();
+        for triangle in triangles.iter() {
+            let mut corners = Vec::new();
+            for &i in triangle.iter() {
+                let p = points[i as usize];
+                let is_pole = p.x.abs() < 0.0001 && p.y.abs() < 0.0001;
+                let u = 0.5 + p.y.atan2(p.x) / (2.0 * std::f32::consts::PI);
+                corners.push((i, p, is_pole, u));
+            }
+            let u_values = corners.iter().filter(|c| !c.2).map(|c| c.3);
+            let u_max = u_values.clone().fold(0.0, f32::max);
+            let u_min = u_values.clone().fold(1.0, f32::min);
+            if u_max - u_min > 0.5 {
+                for corner in corners.iter_mut() {
+                    if corner.3 < 0.5 {
+                        corner.3 += 1.0;
+                    }
+                }
+            }
+            let u_values = corners.iter().filter(|c| !c.2).map(|c| c.3);
+            let u_pole = u_values.clone().sum::<f32>() / u_values.count() as f32;
+            for &(i, p, is_pole, u) in corners.iter() {
+                let uv = vec2(
+                    if is_pole { u_pole } else { u },
+                    p.z.acos() / std::f32::consts::PI,
+                );
+                let index = *vertices.entry((i, uv.x.to_bits())).or_insert_with(|| {
+                    positions.extend_from_slice(&[p.x, p.y, p.z]);
+                    uvs.extend_from_slice(&[uv.x, uv.y]);
+                    positions.len() as u32 / 3 - 1
+                });
+                indices.push(index);
+            }
+        }
+
+        let mut mesh = Self {
+            name: "icosphere".to_string(),
+            indices: Some(indices_with_smallest_type(indices, positions.len() / 3)),
+            normals: Some(positions.clone()),
+            positions,
+            uvs: Some(uvs),
+            ..Default::default()
+        };
+        mesh.compute_tangents().unwrap();
+        mesh
+    }
+
+    ///
+    /// Returns a square mesh spanning the xy-plane with positions in the range `[-1..1]` in the x and y axes,
+    /// which is subdivided into the given number of segments along the x and y axes.
+    ///
+    pub fn grid(x_subdivisions: u32, y_subdivisions: u32) -> Self {
+        let mut positions = Vec::new();
+        let mut normals = Vec::new();
+        let mut uvs = Vec::new();
+        let mut indices = Vec::new();
+        for j in 0..=y_subdivisions {
+            let v = j as f32 / y_subdivisions as f32;
+            for i in 0..=x_subdivisions {
+                let u = i as f32 / x_subdivisions as f32;
+                positions.extend_from_slice(&[2.0 * u - 1.0, 2.0 * v - 1.0, 0.0]);
+                normals.extend_from_slice(&[0.0, 0.0, 1.0]);
+                uvs.extend_from_slice(&[u, v]);
+            }
+        }
+        for j in 0..y_subdivisions {
+            for i in 0..x_subdivisions {
+                let i0 = j * (x_subdivisions + 1) + i;
+                let i1 = i0 + x_subdivisions + 1;
+                indices.extend_from_slice(&[i0, i0 + 1, i1 + 1, i1 + 1, i1, i0]);
+            }
+        }
+        let mut mesh = Self {
+            name: "grid".to_string(),
+            indices: Some(indices_with_smallest_type(indices, positions.len() / 3)),
+            positions,
+            normals: Some(normals),
+            uvs: Some(uvs),
+            ..Default::default()
+        };
+        mesh.compute_tangents().unwrap();
+        mesh
+    }
+
+    ///
+    /// Returns an annulus mesh, that is a disk with a hole, spanning the xy-plane with center in `(0, 0, 0)`, outer radius 1
+    /// and the given inner radius, which should be in the range `[0..1[`.
+    ///
+    pub fn annulus(inner_radius: f32, angle_subdivisions: u32) -> Self {
+        let profile = [inner_radius, 1.0]
+            .iter()
+            .map(|&radial| ProfilePoint {
+                axial: 0.0,
+                radial,
+                normal: vec2(1.0, 0.0),
+            })
+            .collect::<Vec<_>>();
+        revolve(
+            "annulus",
+            &profile,
+            [
+                vec3(0.0, 0.0, 1.0),
+                vec3(1.0, 0.0, 0.0),
+                vec3(0.0, 1.0, 0.0),
+            ],
+            angle_subdivisions,
+        )
+    }
+
+    ///
+    /// Returns a closed frustum mesh, that is a truncated cone, around the x-axis in the range `[0..1]` with radius 1 at 0.0
+    /// and the given end radius at 1.0, which should be in the range `[0..1]`.
+    /// The ends are closed with flat disks.
+    ///
+    pub fn frustum(end_radius: f32, angle_subdivisions: u32) -> Self {
+        let side_normal = vec2(1.0 - end_radius, 1.0).normalize();
+        let point = |axial, radial, normal| ProfilePoint {
+            axial,
+            radial,
+            normal,
+        };
+        let mut profile = vec![
+            point(0.0, 0.0, vec2(-1.0, 0.0)),
+            point(0.0, 1.0, vec2(-1.0, 0.0)),
+            point(0.0, 1.0, side_normal),
+            point(1.0, end_radius, side_normal),
+        ];
+        if end_radius > 0.0 {
+            profile.push(point(1.0, end_radius, vec2(1.0, 0.0)));
+            profile.push(point(1.0, 0.0, vec2(1.0, 0.0)));
+        }
+        revolve(
+            "frustum",
+            &profile,
+            [
+                vec3(1.0, 0.0, 0.0),
+                vec3(0.0, 1.0, 0.0),
+                vec3(0.0, 0.0, 1.0),
+            ],
+            angle_subdivisions,
+        )
+    }
+
+    ///
+    /// Returns an axis aligned box mesh with positions in the range `[-1..1]` in all axes, where the edges and corners are rounded
+    /// with the given radius, which should be in the range `]0..1]`.
+    /// Each rounded edge is subdivided into `2 * corner_subdivisions` segments.
+    ///
+    pub fn rounded_box(corner_radius: f32, corner_subdivisions: u32) -> Self {
+        let radius = corner_radius.clamp(0.0001, 1.0);
+        let corner_subdivisions = corner_subdivisions.max(1);
+        // The coordinates along each side of a face, chosen so that the rounded parts are evenly subdivided by angle
+        let mut coordinates = Vec::new();
+        for i in 0..=corner_subdivisions {
+            let angle = 0.25 * std::f32::consts::PI * (1.0 - i as f32 / corner_subdivisions as f32);
+            coordinates.push(radius - 1.0 - radius * angle.tan());
+        }
+        coordinates[0] = -1.0;
+        if radius < 1.0 {
+            let count = coordinates.len();
+            for i in (0..count).rev() {
+                coordinates.push(-coordinates[i]);
+            }
+        } else {
+            for i in (0..corner_subdivisions as usize).rev() {
+                coordinates.push(-coordinates[i]);
+            }
+        }
+
+        let mut positions = Vec::new();
+        let mut normals = Vec::new();
+        let mut uvs = Vec::new();
+        let mut indices = Vec::new();
+        let n = coordinates.len() as u32;
+        for axis in 0..3 {
+            for &sign in [1.0f32, -1.0].iter() {
+                let (u_axis, v_axis) = if sign > 0.0 {
+                    ((axis + 1) % 3, (axis + 2) % 3)
+                } else {
+                    ((axis + 2) % 3, (axis + 1) % 3)
+                };
+                let offset = positions.len() as u32 / 3;
+                for j in 0..n {
+                    for i in 0..n {
+                        let mut p = vec3(0.0, 0.0, 0.0);
+                        p[axis] = sign;
+                        p[u_axis] = coordinates[i as usize];
+                        p[v_axis] = coordinates[j as usize];
+                        let inner = vec3(
+                            p.x.max(radius - 1.0).min(1.0 - radius),
+                            p.y.max(radius - 1.0).min(1.0 - radius),
+                            p.z.max(radius - 1.0).min(1.0 - radius),
+                        );
+                        let normal = (p - inner).normalize();
+                        let position = inner + normal * radius;
+                        positions.extend_from_slice(&[position.x, position.y, position.z]);
+                        normals.extend_from_slice(&[normal.x, normal.y, normal.z]);
+                        uvs.extend_from_slice(&[
+                            0.5 + 0.5 * coordinates[i as usize],
+                            0.5 + 0.5 * coordinates[j as usize],
+                        ]);
+                    }
+                }
+                for j in 0..n - 1 {
+                    for i in 0..n - 1 {
+                        let i0 = offset + j * n + i;
+                        let i1 = i0 + n;
+                        indices.extend_from_slice(&[i0, i0 + 1, i1 + 1, i1 + 1, i1, i0]);
+                    }
+                }
+            }
+        }
+        let mut mesh = Self {
+            name: "rounded box".to_string(),
+            indices: Some(indices_with_smallest_type(indices, positions.len() / 3)),
+            positions,
+            normals: Some(normals),
+            uvs: Some(uvs),
+            ..Default::default()
+        };
+        mesh.compute_tangents().unwrap();
+        mesh
+    }
+
     ///
     /// Computes the per vertex normals and updates the normals of the mesh.
+    /// A vertex where all of the triangles are degenerate gets the normal `(0, 0, 1)`.
     /// It will override the current normals if they already exist.
     ///
     pub fn compute_normals(&mut self) {
@@ -437,7 +874,8 @@ impl CPUMesh {
         });
 
         self.for_each_vertex(|i| {
-            let normal = vec3(normals[3 * i], normals[3 * i + 1], normals[3 * i + 2]).normalize();
+            let normal =
+                normalize_or_default(vec3(normals[3 * i], normals[3 * i + 1], normals[3 * i + 2]));
             normals[3 * i] = normal.x;
             normals[3 * i + 1] = normal.y;
             normals[3 * i + 2] = normal.z;
@@ -445,6 +883,110 @@ impl CPUMesh {
         self.normals = Some(normals);
     }
 
+    ///
+    /// Computes the per vertex normals so that the mesh is smooth shaded except along the edges where the angle between the two triangles is larger than the given crease angle,
+    /// which is useful for hard-edged models, for example CAD models.
+    /// The normal of a corner of a triangle is the area weighted average of the normals of the triangles with a vertex at the same position
+    /// and where the angle to the triangle is at most the crease angle.
+    /// The vertices of an indexed mesh are duplicated where the corners sharing the vertex get different normals.
+    /// A corner where all of the triangles are degenerate gets the normal `(0, 0, 1)`.
+    /// It will override the current normals if they already exist.
+    ///
+    pub fn compute_normals_with_crease_angle(&mut self, crease_angle: impl Into<Radians>) {
+        let cos_crease_angle = crease_angle.into().0.cos();
+        let mut triangles = Vec::new();
+        self.for_each_triangle(|i0, i1, i2| triangles.push([i0, i1, i2]));
+        let face_normals = triangles
+            .iter()
+            .map(|&[i0, i1, i2]| {
+                let p0 = self.position(i0);
+                (self.position(i1) - p0).cross(self.position(i2) - p0)
+            })
+            .collect::<Vec<_>>();
+
+        let key = |i: usize| position_key(self.position(i));
+        let mut triangles_at_position: std::collections::HashMap<_, Vec<usize>> =
+            std::collections::HashMap::new();
+        for (triangle_index, triangle) in triangles.iter().enumerate() {
+            for &i in triangle.iter() {
+                let neighbours = triangles_at_position.entry(key(i)).or_default();
+                if neighbours.last() != Some(&triangle_index) {
+                    neighbours.push(triangle_index);
+                }
+            }
+        }
+
+        let mut corner_normals = Vec::with_capacity(triangles.len() * 3);
+        for (triangle_index, triangle) in triangles.iter().enumerate() {
+            let normal = face_normals[triangle_index];
+            for &i in triangle.iter() {
+                let mut sum = vec3(0.0, 0.0, 0.0);
+                for &neighbour in triangles_at_position[&key(i)].iter() {
+                    let neighbour_normal = face_normals[neighbour];
+                    // A degenerate triangle has no direction, so it is smooth shaded
+                    if normal.magnitude2() == 0.0
+                        || normal.dot(neighbour_normal)
+                            >= cos_crease_angle * normal.magnitude() * neighbour_normal.magnitude()
+                    {
+                        sum += neighbour_normal;
+                    }
+                }
+                corner_normals.push(normalize_or_default(sum));
+            }
+        }
+
+        if self.indices.is_none() {
+            self.normals = Some(
+                corner_normals
+                    .iter()
+                    .flat_map(|n| vec![n.x, n.y, n.z])
+                    .collect(),
+            );
+        } else {
+            let mut vertices = Vec::new();
+            let mut normals = Vec::new();
+            let mut new_indices = std::collections::HashMap::new();
+            let indices = triangles
+                .iter()
+                .flat_map(|triangle| triangle.iter())
+                .zip(corner_normals.iter())
+                .map(|(&i, n)| {
+                    *new_indices
+                        .entry((i, [n.x.to_bits(), n.y.to_bits(), n.z.to_bits()]))
+                        .or_insert_with(|| {
+                            vertices.push(i as u32);
+                            normals.extend_from_slice(&[n.x, n.y, n.z]);
+                            vertices.len() as u32 - 1
+                        })
+                })
+                .collect();
+            *self = self.vertex_subset(&vertices);
+            self.normals = Some(normals);
+            self.indices = Some(Indices::U32(indices));
+            self.shrink_indices();
+        }
+    }
+
+    ///
+    /// Computes the normals so that each triangle is flat shaded, that is all three vertices of a triangle have the normal of the triangle.
+    /// Degenerate triangles, which have no area and therefore no normal, get the normal `(0, 0, 1)`.
+    /// The indices are removed, see [CPUMesh::unindex], since the vertices cannot be shared between triangles.
+    /// It will override the current normals if they already exist.
+    ///
+    pub fn compute_flat_normals(&mut self) {
+        self.unindex();
+        let mut normals = Vec::with_capacity(self.positions.len());
+        self.for_each_triangle(|i0, i1, i2| {
+            let p0 = self.position(i0);
+            let normal =
+                normalize_or_default((self.position(i1) - p0).cross(self.position(i2) - p0));
+            for _ in 0..3 {
+                normals.extend_from_slice(&[normal.x, normal.y, normal.z]);
+            }
+        });
+        self.normals = Some(normals);
+    }
+
     ///
     /// Computes the per vertex tangents and updates the tangents of the mesh.
     /// It will override the current tangents if they already exist.
@@ -504,6 +1046,372 @@ impl CPUMesh {
         Ok(())
     }
 
+    ///
+    /// Returns a single mesh containing the triangles of all of the given meshes.
+    /// A vertex attribute, for example uv coordinates, is only kept if all of the meshes have it
+    /// and the morph targets are only kept if all of the meshes have the same number of morph targets.
+    /// The name and material name are copied from the first mesh.
+    ///
+    pub fn merge(meshes: &[CPUMesh]) -> Self {
+        let first = match meshes.first() {
+            Some(first) => first,
+            None => return Self::default(),
+        };
+        fn merge_attribute<T: Copy>(
+            meshes: &[CPUMesh],
+            attribute: impl Fn(&CPUMesh) -> Option<&Vec<T>>,
+        ) -> Option<Vec<T>> {
+            let mut result = Vec::new();
+            for mesh in meshes {
+                result.extend_from_slice(attribute(mesh)?);
+            }
+            Some(result)
+        }
+
+        let mut indices = Vec::new();
+        let mut offset = 0;
+        for mesh in meshes {
+            mesh.for_each_triangle(|i0, i1, i2| {
+                indices.extend_from_slice(&[
+                    i0 as u32 + offset,
+                    i1 as u32 + offset,
+                    i2 as u32 + offset,
+                ]);
+            });
+            offset += mesh.positions.len() as u32 / 3;
+        }
+        let morph_target_count = first.morph_targets.len();
+        let morph_targets = if meshes
+            .iter()
+            .all(|mesh| mesh.morph_targets.len() == morph_target_count)
+        {
+            (0..morph_target_count)
+                .map(|i| MorphTarget {
+                    positions: merge_attribute(meshes, |mesh| {
+                        Some(&mesh.morph_targets[i].positions)
+                    })
+                    .unwrap(),
+                    normals: merge_attribute(meshes, |mesh| mesh.morph_targets[i].normals.as_ref()),
+                    tangents: merge_attribute(meshes, |mesh| {
+                        mesh.morph_targets[i].tangents.as_ref()
+                    }),
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let mut mesh = Self {
+            name: first.name.clone(),
+            material_name: first.material_name.clone(),
+            positions: merge_attribute(meshes, |mesh| Some(&mesh.positions)).unwrap(),
+            indices: Some(Indices::U32(indices)),
+            normals: merge_attribute(meshes, |mesh| mesh.normals.as_ref()),
+            tangents: merge_attribute(meshes, |mesh| mesh.tangents.as_ref()),
+            uvs: merge_attribute(meshes, |mesh| mesh.uvs.as_ref()),
+            colors: merge_attribute(meshes, |mesh| mesh.colors.as_ref()),
+            joints: merge_attribute(meshes, |mesh| mesh.joints.as_ref()),
+            weights: merge_attribute(meshes, |mesh| mesh.weights.as_ref()),
+            morph_targets,
+        };
+        mesh.shrink_indices();
+        mesh
+    }
+
+    ///
+    /// Merges the vertices where the distance between the positions is at most the given tolerance
+    /// and all other vertex attributes, including the morph target displacements, differ by at most the tolerance in each component.
+    /// Colors and joints must be equal. So vertices along uv, normal or color seams are not merged.
+    /// A merged vertex gets the attributes of the first of the vertices and the triangles which become degenerate are removed.
+    /// The mesh is indexed afterwards, also if it was not indexed before.
+    ///
+    pub fn weld(&mut self, tolerance: f32) {
+        self.merge_vertices(tolerance, true);
+    }
+
+    ///
+    /// Adds indices to a mesh without indices by merging the vertices with exactly the same attributes.
+    /// Does nothing if the mesh already has indices, use [CPUMesh::weld] to merge the vertices of an indexed mesh.
+    ///
+    pub fn compute_indices(&mut self) {
+        if self.indices.is_none() {
+            self.merge_vertices(0.0, false);
+        }
+    }
+
+    ///
+    /// Removes the indices by duplicating the vertices which are shared between triangles,
+    /// so three contiguous vertices defines a triangle afterwards.
+    /// Does nothing if the mesh has no indices.
+    ///
+    pub fn unindex(&mut self) {
+        if let Some(ref indices) = self.indices {
+            *self = self.vertex_subset(&indices.into_u32());
+        }
+    }
+
+    ///
+    /// Reverses the order of the vertices of each triangle, which makes the back side of the triangles the front side and vice versa.
+    /// The normals and tangents are not changed.
+    ///
+    pub fn flip_winding(&mut self) {
+        fn flip<T>(indices: &mut [T]) {
+            for triangle in indices.chunks_exact_mut(3) {
+                triangle.swap(1, 2);
+            }
+        }
+        match self.indices {
+            Some(Indices::U8(ref mut indices)) => flip(indices),
+            Some(Indices::U16(ref mut indices)) => flip(indices),
+            Some(Indices::U32(ref mut indices)) => flip(indices),
+            None => {
+                let mut order = (0..self.positions.len() as u32 / 3).collect::<Vec<_>>();
+                flip(&mut order);
+                *self = self.vertex_subset(&order);
+            }
+        }
+    }
+
+    ///
+    /// Splits the mesh into its connected components, where two triangles are connected if they share a vertex position,
+    /// so triangles which are only connected along uv, normal or color seams are still in the same component.
+    /// Each returned mesh only contains the vertices used by its triangles and the meshes are ordered by their first triangle.
+    ///
+    pub fn split_components(&self) -> Vec<CPUMesh> {
+        let vertex_count = self.positions.len() / 3;
+        let mut parents = (0..vertex_count).collect::<Vec<_>>();
+        fn root(parents: &mut [usize], mut i: usize) -> usize {
+            while parents[i] != i {
+                parents[i] = parents[parents[i]];
+                i = parents[i];
+            }
+            i
+        }
+        fn union(parents: &mut [usize], a: usize, b: usize) {
+            let (a, b) = (root(parents, a), root(parents, b));
+            parents[a.max(b)] = a.min(b);
+        }
+
+        let mut first_with_position = std::collections::HashMap::new();
+        for i in 0..vertex_count {
+            let first = *first_with_position
+                .entry(position_key(self.position(i)))
+                .or_insert(i);
+            union(&mut parents, first, i);
+        }
+        self.for_each_triangle(|i0, i1, i2| {
+            union(&mut parents, i0, i1);
+            union(&mut parents, i0, i2);
+        });
+
+        let mut components: Vec<(usize, Vec<u32>)> = Vec::new();
+        let mut component_indices = std::collections::HashMap::new();
+        self.for_each_triangle(|i0, i1, i2| {
+            let component = root(&mut parents, i0);
+            let index = *component_indices.entry(component).or_insert_with(|| {
+                components.push((component, Vec::new()));
+                components.len() - 1
+            });
+            components[index]
+                .1
+                .extend_from_slice(&[i0 as u32, i1 as u32, i2 as u32]);
+        });
+
+        components
+            .into_iter()
+            .map(|(_, triangles)| {
+                let mut vertices = Vec::new();
+                let mut new_indices = std::collections::HashMap::new();
+                let indices = triangles
+                    .iter()
+                    .map(|&i| {
+                        *new_indices.entry(i).or_insert_with(|| {
+                            vertices.push(i);
+                            vertices.len() as u32 - 1
+                        })
+                    })
+                    .collect();
+                let mut mesh = self.vertex_subset(&vertices);
+                mesh.indices = Some(Indices::U32(indices));
+                mesh.shrink_indices();
+                mesh
+            })
+            .collect()
+    }
+
+    ///
+    /// Changes the indices to use the smallest data type which can represent all of them, for example from `u32` to `u16` or `u8`.
+    ///
+    pub fn shrink_indices(&mut self) {
+        if let Some(ref indices) = self.indices {
+            let indices = indices.into_u32();
+            let max = indices.iter().max().cloned().unwrap_or(0);
+            self.indices = Some(if max <= u8::MAX as u32 {
+                Indices::U8(indices.into_iter().map(|i| i as u8).collect())
+            } else if max <= u16::MAX as u32 {
+                Indices::U16(indices.into_iter().map(|i| i as u16).collect())
+            } else {
+                Indices::U32(indices)
+            });
+        }
+    }
+
+    ///
+    /// Merges similar vertices, see [CPUMesh::weld], and optionally removes the degenerate triangles.
+    ///
+    fn merge_vertices(&mut self, tolerance: f32, remove_degenerate_triangles: bool) {
+        // The vertices are sorted into cells of the size of the tolerance, or by their exact position if the tolerance is zero,
+        // so only the vertices in the same or neighbouring cells need to be compared
+        let range = if tolerance > 0.0 { 1 } else { 0 };
+        let mut cells: std::collections::HashMap<[i64; 3], Vec<u32>> =
+            std::collections::HashMap::new();
+        let mut vertices = Vec::new();
+        let mut new_indices = Vec::with_capacity(self.positions.len() / 3);
+        for i in 0..self.positions.len() / 3 {
+            let cell = if tolerance > 0.0 {
+                let p = self.position(i) / tolerance;
+                [p.x.floor() as i64, p.y.floor() as i64, p.z.floor() as i64]
+            } else {
+                let key = position_key(self.position(i));
+                [key[0] as i64, key[1] as i64, key[2] as i64]
+            };
+            let mut similar = None;
+            'search: for x in cell[0] - range..=cell[0] + range {
+                for y in cell[1] - range..=cell[1] + range {
+                    for z in cell[2] - range..=cell[2] + range {
+                        if let Some(candidates) = cells.get(&[x, y, z]) {
+                            for &candidate in candidates {
+                                if self.is_similar_vertex(
+                                    vertices[candidate as usize] as usize,
+                                    i,
+                                    tolerance,
+                                ) {
+                                    similar = Some(candidate);
+                                    break 'search;
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            new_indices.push(similar.unwrap_or_else(|| {
+                vertices.push(i as u32);
+                let index = vertices.len() as u32 - 1;
+                cells.entry(cell).or_default().push(index);
+                index
+            }));
+        }
+
+        let mut indices = Vec::new();
+        self.for_each_triangle(|i0, i1, i2| {
+            let triangle = [new_indices[i0], new_indices[i1], new_indices[i2]];
+            if !remove_degenerate_triangles
+                || (triangle[0] != triangle[1]
+                    && triangle[0] != triangle[2]
+                    && triangle[1] != triangle[2])
+            {
+                indices.extend_from_slice(&triangle);
+            }
+        });
+        *self = self.vertex_subset(&vertices);
+        self.indices = Some(Indices::U32(indices));
+        self.shrink_indices();
+    }
+
+    ///
+    /// Returns whether all of the attributes of the two vertices differ by at most the given tolerance, see [CPUMesh::weld].
+    ///
+    fn is_similar_vertex(&self, a: usize, b: usize, tolerance: f32) -> bool {
+        fn similar<T: Copy + Into<f64>>(
+            data: &[T],
+            components: usize,
+            a: usize,
+            b: usize,
+            tolerance: f32,
+        ) -> bool {
+            (0..components).all(|i| {
+                (data[a * components + i].into() - data[b * components + i].into()).abs()
+                    <= tolerance as f64
+            })
+        }
+        self.position(a).distance2(self.position(b)) <= tolerance * tolerance
+            && self
+                .normals
+                .as_ref()
+                .is_none_or(|data| similar(data, 3, a, b, tolerance))
+            && self
+                .tangents
+                .as_ref()
+                .is_none_or(|data| similar(data, 4, a, b, tolerance))
+            && self
+                .uvs
+                .as_ref()
+                .is_none_or(|data| similar(data, 2, a, b, tolerance))
+            && self
+                .colors
+                .as_ref()
+                .is_none_or(|data| similar(data, 4, a, b, 0.0))
+            && self
+                .joints
+                .as_ref()
+                .is_none_or(|data| similar(data, 4, a, b, 0.0))
+            && self
+                .weights
+                .as_ref()
+                .is_none_or(|data| similar(data, 4, a, b, tolerance))
+            && self.morph_targets.iter().all(|morph_target| {
+                similar(&morph_target.positions, 3, a, b, tolerance)
+                    && morph_target
+                        .normals
+                        .as_ref()
+                        .is_none_or(|data| similar(data, 3, a, b, tolerance))
+                    && morph_target
+                        .tangents
+                        .as_ref()
+                        .is_none_or(|data| similar(data, 3, a, b, tolerance))
+            })
+    }
+
+    ///
+    /// Returns a mesh without indices containing copies of the given vertices in the given order.
+    ///
+    fn vertex_subset(&self, vertices: &[u32]) -> Self {
+        fn gather<T: Copy>(data: &[T], components: usize, vertices: &[u32]) -> Vec<T> {
+            let mut result = Vec::with_capacity(vertices.len() * components);
+            for &vertex in vertices {
+                let start = vertex as usize * components;
+                result.extend_from_slice(&data[start..start + components]);
+            }
+            result
+        }
+        Self {
+            name: self.name.clone(),
+            material_name: self.material_name.clone(),
+            positions: gather(&self.positions, 3, vertices),
+            indices: None,
+            normals: self.normals.as_ref().map(|data| gather(data, 3, vertices)),
+            tangents: self.tangents.as_ref().map(|data| gather(data, 4, vertices)),
+            uvs: self.uvs.as_ref().map(|data| gather(data, 2, vertices)),
+            colors: self.colors.as_ref().map(|data| gather(data, 4, vertices)),
+            joints: self.joints.as_ref().map(|data| gather(data, 4, vertices)),
+            weights: self.weights.as_ref().map(|data| gather(data, 4, vertices)),
+            morph_targets: self
+                .morph_targets
+                .iter()
+                .map(|morph_target| MorphTarget {
+                    positions: gather(&morph_target.positions, 3, vertices),
+                    normals: morph_target
+                        .normals
+                        .as_ref()
+                        .map(|data| gather(data, 3, vertices)),
+                    tangents: morph_target
+                        .tangents
+                        .as_ref()
+                        .map(|data| gather(data, 3, vertices)),
+                })
+                .collect(),
+        }
+    }
+
     ///
     ///  Iterates over all vertices in this mesh and calls the callback function with the index for each vertex.
     ///
@@ -594,72 +1502,745 @@ impl CPUMesh {
     /// Returns an error if the mesh is not valid.
     ///
     pub fn validate(&self) -> ThreeDResult<()> {
+        if !self.positions.len().is_multiple_of(3) {
+            Err(CoreError::InvalidBufferLength(
+                "position".to_string(),
+                self.positions.len(),
+            ))?;
+        }
+        let vertex_count = self.positions.len() / 3;
         if let Some(ref indices) = self.indices {
             let index_count = match indices {
                 Indices::U8(ind) => ind.len(),
                 Indices::U16(ind) => ind.len(),
                 Indices::U32(ind) => ind.len(),
             };
-            if index_count % 3 != 0 {
+            if !index_count.is_multiple_of(3) {
                 Err(CoreError::InvalidBufferLength(
                     "index".to_string(),
                     index_count,
                 ))?;
             }
-            if self.positions.len() % 3 != 0 {
-                Err(CoreError::InvalidBufferLength(
-                    "position".to_string(),
-                    index_count,
+            let indices_valid = match indices {
+                Indices::U8(ind) => ind.iter().all(|&i| (i as usize) < vertex_count),
+                Indices::U16(ind) => ind.iter().all(|&i| (i as usize) < vertex_count),
+                Indices::U32(ind) => ind.iter().all(|&i| (i as usize) < vertex_count),
+            };
+            if !indices_valid {
+                Err(CoreError::InvalidIndexBuffer(vertex_count))?;
+            }
+        } else if !vertex_count.is_multiple_of(3) {
+            Err(CoreError::InvalidPositionBuffer(self.positions.len()))?;
+        }
+
+        let check = |name: &str, length: usize, components: usize| -> ThreeDResult<()> {
+            if length != vertex_count * components {
+                Err(CoreError::InvalidVertexBufferLength(
+                    name.to_string(),
+                    length,
+                    vertex_count * components,
                 ))?;
             }
-            if let Some(ref data) = self.normals {
-                if data.len() % 3 != 0 {
-                    Err(CoreError::InvalidBufferLength(
-                        "normal".to_string(),
-                        index_count,
-                    ))?;
-                }
+            Ok(())
+        };
+        if let Some(ref data) = self.normals {
+            check("normal", data.len(), 3)?;
+        }
+        if let Some(ref data) = self.tangents {
+            check("tangent", data.len(), 4)?;
+        }
+        if let Some(ref data) = self.uvs {
+            check("uv coordinate", data.len(), 2)?;
+        }
+        if let Some(ref data) = self.colors {
+            check("color", data.len(), 4)?;
+        }
+        if let Some(ref data) = self.joints {
+            check("joint", data.len(), 4)?;
+        }
+        if let Some(ref data) = self.weights {
+            check("weight", data.len(), 4)?;
+        }
+        for morph_target in self.morph_targets.iter() {
+            if morph_target.positions.len() != self.positions.len() {
+                Err(CoreError::InvalidMorphTarget(
+                    morph_target.positions.len(),
+                    self.positions.len(),
+                ))?;
             }
-            if let Some(ref data) = self.colors {
-                if data.len() % 4 != 0 {
-                    Err(CoreError::InvalidBufferLength(
-                        "color".to_string(),
-                        index_count,
-                    ))?;
-                }
+            if let Some(ref data) = morph_target.normals {
+                check("morph target normal", data.len(), 3)?;
             }
-            if let Some(ref data) = self.uvs {
-                if data.len() % 2 != 0 {
-                    Err(CoreError::InvalidBufferLength(
-                        "uv coordinate".to_string(),
-                        index_count,
-                    ))?;
-                }
+            if let Some(ref data) = morph_target.tangents {
+                check("morph target tangent", data.len(), 3)?;
             }
-            if cfg!(debug) {
-                let indices_valid = match indices {
-                    Indices::U8(ind) => {
-                        let len = self.positions.len();
-                        ind.iter().all(|&i| (i as usize) < len)
-                    }
-                    Indices::U16(ind) => {
-                        let len = self.positions.len();
-                        ind.iter().all(|&i| (i as usize) < len)
-                    }
-                    Indices::U32(ind) => {
-                        let len = self.positions.len();
-                        ind.iter().all(|&i| (i as usize) < len)
-                    }
-                };
-                if !indices_valid {
-                    Err(CoreError::InvalidIndexBuffer(self.positions.len()))?;
+        }
+        Ok(())
+    }
+}
+
+///
+/// A point on the profile curve of a surface of revolution, given by the position along the axis of revolution,
+/// the distance to the axis and the normal in the same two directions.
+///
+struct ProfilePoint {
+    axial: f32,
+    radial: f32,
+    normal: Vec2,
+}
+
+///
+/// Returns the surface made by revolving the profile curve around the first of the given axes,
+/// starting from the second axis towards the third axis.
+/// The u coordinate follows the angle and the v coordinate follows the length of the profile curve, so the vertices along the seam are duplicated.
+/// Two consecutive profile points at the same position makes a hard edge and a profile point on the axis makes a pole.
+///
+fn revolve(
+    name: &str,
+    profile: &[ProfilePoint],
+    axes: [Vec3; 3],
+    angle_subdivisions: u32,
+) -> CPUMesh {
+    let mut lengths = vec![0.0];
+    for i in 1..profile.len() {
+        let d = vec2(
+            profile[i].axial - profile[i - 1].axial,
+            profile[i].radial - profile[i - 1].radial,
+        );
+        lengths.push(lengths[i - 1] + d.magnitude());
+    }
+    let total_length = lengths[profile.len() - 1];
+
+    // A pole has a vertex in the middle of each segment, other rows have a vertex at each end of each segment
+    let mut row_starts = Vec::new();
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut uvs = Vec::new();
+    for (point, length) in profile.iter().zip(lengths.iter()) {
+        row_starts.push(positions.len() as u32 / 3);
+        let (offset, vertex_count) = if point.radial == 0.0 {
+            (0.5, angle_subdivisions)
+        } else {
+            (0.0, angle_subdivisions + 1)
+        };
+        for j in 0..vertex_count {
+            let u = (j as f32 + offset) / angle_subdivisions as f32;
+            // The last vertex in a row is at the same position as the first
+            let angle = if j == angle_subdivisions {
+                0.0
+            } else {
+                2.0 * std::f32::consts::PI * u
+            };
+            let direction = axes[1] * angle.cos() + axes[2] * angle.sin();
+            let position = axes[0] * point.axial + direction * point.radial;
+            let normal = axes[0] * point.normal.x + direction * point.normal.y;
+            positions.extend_from_slice(&[position.x, position.y, position.z]);
+            normals.extend_from_slice(&[normal.x, normal.y, normal.z]);
+            uvs.extend_from_slice(&[u, length / total_length]);
+        }
+    }
+
+    let mut indices = Vec::new();
+    for i in 0..profile.len() - 1 {
+        let (p0, p1) = (&profile[i], &profile[i + 1]);
+        if p0.axial == p1.axial && p0.radial == p1.radial {
+            continue;
+        }
+        // The quads are counter clockwise when seen from the side the normals point to
+        // if the normals are to the left of the profile curve
+        let flip =
+            vec2(p0.radial - p1.radial, p1.axial - p0.axial).dot(p0.normal + p1.normal) < 0.0;
+        for j in 0..angle_subdivisions {
+            let i0 = row_starts[i] + j;
+            let i1 = row_starts[i + 1] + j;
+            let mut triangles = Vec::new();
+            if p0.radial == 0.0 {
+                triangles.push([i1 + 1, i1, i0]);
+            } else if p1.radial == 0.0 {
+                triangles.push([i0, i0 + 1, i1]);
+            } else {
+                triangles.push([i0, i0 + 1, i1 + 1]);
+                triangles.push([i1 + 1, i1, i0]);
+            }
+            for [a, b, c] in triangles {
+                if flip {
+                    indices.extend_from_slice(&[a, c, b]);
+                } else {
+                    indices.extend_from_slice(&[a, b, c]);
                 }
             }
-        } else {
-            if self.positions.len() % 9 != 0 {
-                Err(CoreError::InvalidPositionBuffer(self.positions.len()))?;
+        }
+    }
+
+    let mut mesh = CPUMesh {
+        name: name.to_string(),
+        indices: Some(indices_with_smallest_type(indices, positions.len() / 3)),
+        positions,
+        normals: Some(normals),
+        uvs: Some(uvs),
+        ..Default::default()
+    };
+    mesh.compute_tangents().unwrap();
+    mesh
+}
+
+///
+/// Returns a key which is equal for two positions if and only if they are exactly equal.
+///
+///
+/// Returns the given normal normalized or `(0, 0, 1)` if it has zero length, for example if it is the normal of a degenerate triangle.
+///
+fn normalize_or_default(normal: Vec3) -> Vec3 {
+    if normal.magnitude2() > 0.0 {
+        normal.normalize()
+    } else {
+        vec3(0.0, 0.0, 1.0)
+    }
+}
+
+fn position_key(position: Vec3) -> [u32; 3] {
+    // Adding zero makes negative zero equal to zero
+    [
+        (position.x + 0.0).to_bits(),
+        (position.y + 0.0).to_bits(),
+        (position.z + 0.0).to_bits(),
+    ]
+}
+
+///
+/// Returns the given indices stored with 16 bit integers if possible, otherwise with 32 bit integers.
+///
+fn indices_with_smallest_type(indices: Vec<u32>, vertex_count: usize) -> Indices {
+    if vertex_count <= u16::MAX as usize + 1 {
+        Indices::U16(indices.into_iter().map(|i| i as u16).collect())
+    } else {
+        Indices::U32(indices)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_valid(mesh: &CPUMesh) {
+        mesh.validate().unwrap();
+        let vertex_count = mesh.positions.len() / 3;
+        assert!(
+            mesh.positions.iter().all(|v| v.is_finite()),
+            "{}",
+            mesh.name
+        );
+        assert_eq!(mesh.normals.as_ref().unwrap().len(), vertex_count * 3);
+        assert_eq!(mesh.uvs.as_ref().unwrap().len(), vertex_count * 2);
+        assert_eq!(mesh.tangents.as_ref().unwrap().len(), vertex_count * 4);
+        for i in 0..vertex_count {
+            let length = mesh.normal(i).unwrap().magnitude();
+            assert!((length - 1.0).abs() < 0.001, "{}", mesh.name);
+        }
+        let mut triangle_count = 0;
+        mesh.for_each_triangle(|i0, i1, i2| {
+            assert!(i0 < vertex_count && i1 < vertex_count && i2 < vertex_count);
+            triangle_count += 1;
+        });
+        assert!(triangle_count > 0, "{}", mesh.name);
+    }
+
+    fn assert_closed(mesh: &CPUMesh) {
+        // Only the positions are used, so the vertices along the uv seams are welded as well
+        let mut welded = CPUMesh {
+            positions: mesh.positions.clone(),
+            indices: mesh.indices.clone(),
+            ..Default::default()
+        };
+        welded.weld(0.0001);
+        let mut edges = std::collections::HashMap::new();
+        welded.for_each_triangle(|i0, i1, i2| {
+            for &(a, b) in [(i0, i1), (i1, i2), (i2, i0)].iter() {
+                *edges.entry((a.min(b), a.max(b))).or_insert(0) += 1;
+            }
+        });
+        assert!(!edges.is_empty(), "{}", mesh.name);
+        for (edge, count) in edges {
+            assert_eq!(
+                count, 2,
+                "the edge {:?} of the {} is shared by {} triangles",
+                edge, mesh.name, count
+            );
+        }
+    }
+
+    #[test]
+    pub fn torus() {
+        for &(angle_subdivisions, tube_subdivisions) in [(3, 3), (16, 8), (32, 5)].iter() {
+            let mesh = CPUMesh::torus(0.25, angle_subdivisions, tube_subdivisions);
+            assert_valid(&mesh);
+            assert_closed(&mesh);
+        }
+    }
+
+    #[test]
+    pub fn capsule() {
+        for &angle_subdivisions in [3, 4, 16, 33].iter() {
+            let mesh = CPUMesh::capsule(2.0, 0.5, angle_subdivisions);
+            assert_valid(&mesh);
+            assert_closed(&mesh);
+        }
+    }
+
+    #[test]
+    pub fn icosphere() {
+        for subdivisions in 0..4 {
+            let mesh = CPUMesh::icosphere(subdivisions);
+            assert_valid(&mesh);
+            assert_closed(&mesh);
+        }
+    }
+
+    #[test]
+    pub fn grid() {
+        for &(x_subdivisions, y_subdivisions) in [(1, 1), (4, 2), (10, 10)].iter() {
+            let mesh = CPUMesh::grid(x_subdivisions, y_subdivisions);
+            assert_valid(&mesh);
+            assert_eq!(
+                mesh.positions.len() / 3,
+                ((x_subdivisions + 1) * (y_subdivisions + 1)) as usize
+            );
+        }
+    }
+
+    #[test]
+    pub fn annulus() {
+        for &inner_radius in [0.0, 0.5].iter() {
+            let mesh = CPUMesh::annulus(inner_radius, 16);
+            assert_valid(&mesh);
+        }
+    }
+
+    #[test]
+    pub fn frustum() {
+        for &end_radius in [0.0, 0.5, 1.0].iter() {
+            let mesh = CPUMesh::frustum(end_radius, 16);
+            assert_valid(&mesh);
+            assert_closed(&mesh);
+        }
+    }
+
+    #[test]
+    pub fn rounded_box() {
+        for &(corner_radius, corner_subdivisions) in [(0.25, 1), (0.5, 4), (1.0, 3)].iter() {
+            let mesh = CPUMesh::rounded_box(corner_radius, corner_subdivisions);
+            assert_valid(&mesh);
+            assert_closed(&mesh);
+        }
+    }
+
+    fn triangle_positions(mesh: &CPUMesh) -> Vec<[Vec3; 3]> {
+        let mut triangles = Vec::new();
+        mesh.for_each_triangle(|i0, i1, i2| {
+            triangles.push([mesh.position(i0), mesh.position(i1), mesh.position(i2)]);
+        });
+        triangles
+    }
+
+    fn triangle(offset: f32) -> CPUMesh {
+        CPUMesh {
+            positions: vec![offset, 0.0, 0.0, offset + 1.0, 0.0, 0.0, offset, 1.0, 0.0],
+            normals: Some(vec![0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    pub fn merge() {
+        let square = CPUMesh::square();
+        let mut large = CPUMesh::grid(20, 20);
+        large.indices = Some(Indices::U32(large.indices.unwrap().into_u32()));
+        let merged = CPUMesh::merge(&[square.clone(), triangle(2.0), large.clone()]);
+        merged.validate().unwrap();
+        assert_eq!(merged.name, "square");
+        assert_eq!(
+            merged.positions.len(),
+            square.positions.len() + 9 + large.positions.len()
+        );
+        let mut expected = triangle_positions(&square);
+        expected.extend(triangle_positions(&triangle(2.0)));
+        expected.extend(triangle_positions(&large));
+        assert_eq!(triangle_positions(&merged), expected);
+        // Only the attributes which all of the meshes have are kept
+        assert_eq!(
+            merged.normals.as_ref().unwrap().len(),
+            merged.positions.len()
+        );
+        assert!(merged.uvs.is_none());
+        assert!(merged.tangents.is_none());
+        // The 441 vertices of the grid need 16 bit indices
+        assert!(matches!(merged.indices, Some(Indices::U16(_))));
+
+        let merged = CPUMesh::merge(&[triangle(0.0), triangle(2.0)]);
+        assert!(matches!(merged.indices, Some(Indices::U8(_))));
+        assert_eq!(merged.indices.unwrap().into_u32(), vec![0, 1, 2, 3, 4, 5]);
+        assert!(CPUMesh::merge(&[]).positions.is_empty());
+    }
+
+    #[test]
+    pub fn weld() {
+        // Two triangles sharing an edge where the shared vertices are slightly apart
+        let mut mesh = CPUMesh::merge(&[triangle(0.0), triangle(0.0)]);
+        mesh.positions[9..].copy_from_slice(&[1.0, 1.0, 0.0, 0.0, 1.0001, 0.0, 1.0001, 0.0, 0.0]);
+        let triangles = triangle_positions(&mesh);
+
+        let mut welded = mesh.clone();
+        welded.weld(0.00001);
+        assert_eq!(welded.positions.len(), 18);
+
+        let mut welded = mesh.clone();
+        welded.weld(0.001);
+        assert_eq!(welded.positions.len(), 12);
+        assert_eq!(welded.indices.as_ref().unwrap().into_u32().len(), 6);
+        // The merged vertices get the position of the first vertex
+        assert_eq!(triangle_positions(&welded)[0], triangles[0]);
+        assert_eq!(
+            triangle_positions(&welded)[1],
+            [
+                vec3(1.0, 1.0, 0.0),
+                vec3(0.0, 1.0, 0.0),
+                vec3(1.0, 0.0, 0.0)
+            ]
+        );
+
+        // A large tolerance collapses triangles which are then removed
+        let mut welded = mesh.clone();
+        welded.weld(2.0);
+        assert_eq!(welded.positions.len(), 3);
+        assert!(welded.indices.unwrap().into_u32().is_empty());
+    }
+
+    #[test]
+    pub fn weld_keeps_seams() {
+        // The faces of the cube have different normals and the uv coordinates have seams
+        let mut cube = CPUMesh::cube();
+        cube.weld(0.0001);
+        assert_eq!(cube.positions.len() / 3, 24);
+        assert_eq!(
+            triangle_positions(&cube),
+            triangle_positions(&CPUMesh::cube())
+        );
+
+        let mut cube = CPUMesh::cube();
+        cube.normals = None;
+        cube.tangents = None;
+        cube.weld(0.0001);
+        assert!(cube.positions.len() / 3 > 8);
+
+        let mut cube = CPUMesh::cube();
+        cube.normals = None;
+        cube.tangents = None;
+        cube.uvs = None;
+        cube.weld(0.0001);
+        assert_eq!(cube.positions.len() / 3, 8);
+        assert_eq!(
+            triangle_positions(&cube),
+            triangle_positions(&CPUMesh::cube())
+        );
+
+        // Vertices with different colors are never merged
+        let mut mesh = CPUMesh::merge(&[triangle(0.0), triangle(0.0)]);
+        mesh.colors = Some(
+            [[255, 0, 0, 255]; 3]
+                .iter()
+                .chain([[255, 0, 0, 254]; 3].iter())
+                .flatten()
+                .cloned()
+                .collect(),
+        );
+        mesh.weld(0.1);
+        assert_eq!(mesh.positions.len(), 18);
+    }
+
+    #[test]
+    pub fn compute_indices_and_unindex() {
+        let mut square = CPUMesh::square();
+        square.unindex();
+        assert!(square.indices.is_none());
+        assert_eq!(square.positions.len(), 18);
+        assert_eq!(square.uvs.as_ref().unwrap().len(), 12);
+        assert_eq!(
+            triangle_positions(&square),
+            triangle_positions(&CPUMesh::square())
+        );
+
+        square.compute_indices();
+        assert_eq!(square.positions.len(), 12);
+        assert_eq!(square.uvs.as_ref().unwrap().len(), 8);
+        assert_eq!(
+            triangle_positions(&square),
+            triangle_positions(&CPUMesh::square())
+        );
+
+        // The vertices of an indexed mesh are not merged
+        let mut cube = CPUMesh::cube();
+        cube.normals = None;
+        cube.tangents = None;
+        cube.uvs = None;
+        cube.weld(0.0);
+        cube.unindex();
+        cube.compute_indices();
+        assert_eq!(cube.positions.len() / 3, 8);
+        let vertex_count = cube.positions.len();
+        cube.positions.extend_from_slice(&[1.0, 1.0, 1.0]);
+        cube.compute_indices();
+        assert_eq!(cube.positions.len(), vertex_count + 3);
+    }
+
+    #[test]
+    pub fn flip_winding() {
+        let mut square = CPUMesh::square();
+        square.flip_winding();
+        assert_eq!(
+            square.indices.as_ref().unwrap().into_u32(),
+            vec![0, 2, 1, 2, 0, 3]
+        );
+        square.flip_winding();
+        assert_eq!(
+            triangle_positions(&square),
+            triangle_positions(&CPUMesh::square())
+        );
+
+        let mut mesh = triangle(0.0);
+        mesh.flip_winding();
+        assert_eq!(
+            triangle_positions(&mesh),
+            vec![[
+                vec3(0.0, 0.0, 0.0),
+                vec3(0.0, 1.0, 0.0),
+                vec3(1.0, 0.0, 0.0)
+            ]]
+        );
+        assert_eq!(mesh.normals, triangle(0.0).normals);
+    }
+
+    #[test]
+    pub fn split_components() {
+        let mut far_cube = CPUMesh::cube();
+        far_cube.transform(&Mat4::from_translation(vec3(10.0, 0.0, 0.0)));
+        let mesh = CPUMesh::merge(&[
+            CPUMesh::cube(),
+            triangle(5.0),
+            far_cube.clone(),
+            triangle(5.5),
+        ]);
+        let components = mesh.split_components();
+        // The two triangles overlap but do not share a vertex position
+        assert_eq!(components.len(), 4);
+        assert_eq!(
+            triangle_positions(&components[0]),
+            triangle_positions(&CPUMesh::cube())
+        );
+        assert_eq!(
+            triangle_positions(&components[1]),
+            triangle_positions(&triangle(5.0))
+        );
+        assert_eq!(
+            triangle_positions(&components[2]),
+            triangle_positions(&far_cube)
+        );
+        // Each component only contains its own vertices
+        assert_eq!(components[1].positions.len(), 9);
+        assert!(components.iter().all(|c| c.validate().is_ok()));
+
+        // Triangles sharing a vertex position are connected
+        let mesh = CPUMesh::merge(&[triangle(0.0), triangle(1.0)]);
+        assert_eq!(mesh.split_components().len(), 1);
+        assert!(CPUMesh::default().split_components().is_empty());
+    }
+
+    #[test]
+    pub fn shrink_indices() {
+        for &(vertex_count, expected_size) in
+            [(3, 1), (256, 1), (257, 2), (65536, 2), (65537, 4)].iter()
+        {
+            let mut mesh = CPUMesh {
+                positions: vec![0.0; vertex_count * 3],
+                indices: Some(Indices::U32(vec![0, 1, vertex_count as u32 - 1])),
+                ..Default::default()
+            };
+            mesh.shrink_indices();
+            let size = match mesh.indices {
+                Some(Indices::U8(_)) => 1,
+                Some(Indices::U16(_)) => 2,
+                Some(Indices::U32(_)) => 4,
+                None => 0,
+            };
+            assert_eq!(size, expected_size);
+            assert_eq!(
+                mesh.indices.unwrap().into_u32(),
+                vec![0, 1, vertex_count as u32 - 1]
+            );
+        }
+    }
+
+    fn welded_cube() -> CPUMesh {
+        let mut cube = CPUMesh::cube();
+        cube.normals = None;
+        cube.tangents = None;
+        cube.uvs = None;
+        cube.weld(0.0001);
+        cube
+    }
+
+    #[test]
+    pub fn compute_normals_with_crease_angle() {
+        // The angle between the faces of a cube is 90 degrees, so all edges are creases
+        let mut cube = welded_cube();
+        cube.compute_normals_with_crease_angle(degrees(89.0));
+        assert_eq!(cube.positions.len() / 3, 24);
+        let mut triangles = Vec::new();
+        cube.for_each_triangle(|i0, i1, i2| triangles.push([i0, i1, i2]));
+        for &[i0, i1, i2] in triangles.iter() {
+            let p0 = cube.position(i0);
+            let face_normal = (cube.position(i1) - p0)
+                .cross(cube.position(i2) - p0)
+                .normalize();
+            for &i in [i0, i1, i2].iter() {
+                assert!((cube.normal(i).unwrap() - face_normal).magnitude() < 0.0001);
             }
+        }
+
+        // Otherwise the cube is smooth shaded, so each corner has a single normal pointing away from the cube
+        let mut cube = welded_cube();
+        cube.compute_normals_with_crease_angle(degrees(91.0));
+        assert_eq!(cube.positions.len() / 3, 8);
+        for i in 0..8 {
+            let position = cube.position(i);
+            let normal = cube.normal(i).unwrap();
+            assert!((normal.magnitude() - 1.0).abs() < 0.0001);
+            for c in 0..3 {
+                assert!(normal[c] * position[c] > 0.1);
+            }
+        }
+        assert_eq!(
+            triangle_positions(&cube),
+            triangle_positions(&welded_cube())
+        );
+
+        let mut cube = CPUMesh::cube();
+        cube.compute_normals_with_crease_angle(degrees(91.0));
+        assert!(cube.indices.is_none());
+        assert_eq!(cube.normals.as_ref().unwrap().len(), cube.positions.len());
+    }
+
+    #[test]
+    pub fn compute_flat_normals() {
+        let mut cube = welded_cube();
+        cube.compute_flat_normals();
+        assert!(cube.indices.is_none());
+        assert_eq!(cube.positions.len() / 3, 36);
+        cube.for_each_triangle(|i0, i1, i2| {
+            let normal = cube.normal(i0).unwrap();
+            assert_eq!(cube.normal(i1).unwrap(), normal);
+            assert_eq!(cube.normal(i2).unwrap(), normal);
+            // The normals point away from the cube and are parallel to one of the axes
+            let center = (cube.position(i0) + cube.position(i1) + cube.position(i2)) / 3.0;
+            assert!(normal.dot(center) > 0.0);
+            assert!((normal.x.abs() + normal.y.abs() + normal.z.abs() - 1.0).abs() < 0.0001);
+        });
+    }
+
+    #[test]
+    pub fn compute_normals_of_degenerate_triangles() {
+        // The first triangle is degenerate and is the only triangle at its third vertex
+        let mesh = CPUMesh {
+            positions: vec![
+                0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 2.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0,
+                1.0, 0.0,
+            ],
+            ..Default::default()
         };
-        Ok(())
+        let mut smooth = mesh.clone();
+        smooth.compute_normals();
+        let mut flat = mesh.clone();
+        flat.compute_flat_normals();
+        let mut creased = mesh.clone();
+        creased.compute_normals_with_crease_angle(degrees(30.0));
+        for mesh in [smooth, flat, creased].iter() {
+            let normals = mesh.normals.as_ref().unwrap();
+            assert!(normals.iter().all(|n| n.is_finite()));
+            assert_eq!(mesh.normal(2), Some(vec3(0.0, 0.0, 1.0)));
+            assert_eq!(mesh.normal(5), Some(vec3(0.0, 0.0, 1.0)));
+        }
+    }
+
+    #[test]
+    pub fn validate() {
+        let square = CPUMesh::square();
+        square.validate().unwrap();
+        let mut unindexed = CPUMesh::square();
+        unindexed.unindex();
+        unindexed.validate().unwrap();
+
+        // The lengths are checked against the number of vertices both with and without indices
+        let invalid_meshes: Vec<fn(&mut CPUMesh)> = vec![
+            |mesh| mesh.positions.push(0.0),
+            |mesh| mesh.indices = Some(Indices::U8(vec![0, 1, 2, 2])),
+            |mesh| mesh.indices = Some(Indices::U16(vec![0, 1, 6])),
+            |mesh| {
+                let normals = mesh.normals.as_mut().unwrap();
+                normals.truncate(normals.len() - 3);
+            },
+            |mesh| mesh.tangents.as_mut().unwrap().extend_from_slice(&[0.0; 4]),
+            |mesh| {
+                let uvs = mesh.uvs.as_mut().unwrap();
+                uvs.truncate(uvs.len() - 2);
+            },
+            |mesh| mesh.colors = Some(vec![255; mesh.positions.len() / 3 * 4 - 4]),
+            |mesh| mesh.joints = Some(vec![0; mesh.positions.len() / 3 * 4 + 4]),
+            |mesh| mesh.weights = Some(vec![0.25; mesh.positions.len()]),
+            |mesh| {
+                mesh.morph_targets.push(MorphTarget {
+                    positions: vec![0.0; mesh.positions.len() - 3],
+                    ..Default::default()
+                })
+            },
+            |mesh| {
+                mesh.morph_targets.push(MorphTarget {
+                    positions: vec![0.0; mesh.positions.len()],
+                    normals: Some(vec![0.0; mesh.positions.len() - 3]),
+                    ..Default::default()
+                })
+            },
+            |mesh| {
+                mesh.morph_targets.push(MorphTarget {
+                    positions: vec![0.0; mesh.positions.len()],
+                    tangents: Some(vec![0.0; mesh.positions.len() / 3 * 4]),
+                    ..Default::default()
+                })
+            },
+        ];
+        for (i, modify) in invalid_meshes.iter().enumerate() {
+            for valid in [square.clone(), unindexed.clone()].iter() {
+                let mut mesh = valid.clone();
+                modify(&mut mesh);
+                assert!(
+                    mesh.validate().is_err(),
+                    "mesh {} with indices {} is valid",
+                    i,
+                    mesh.indices.is_some()
+                );
+            }
+        }
+
+        let mut mesh = CPUMesh::square();
+        mesh.joints = Some(vec![0; 16]);
+        mesh.weights = Some(vec![0.25; 16]);
+        mesh.morph_targets.push(MorphTarget {
+            positions: vec![0.0; 12],
+            normals: Some(vec![0.0; 12]),
+            tangents: Some(vec![0.0; 12]),
+        });
+        mesh.validate().unwrap();
+
+        let mut mesh = CPUMesh::square();
+        mesh.indices = None;
+        assert!(mesh.validate().is_err());
     }
 }
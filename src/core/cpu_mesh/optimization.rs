@@ -0,0 +1,447 @@
+use crate::core::*;
+
+///
+/// Statistics about how many times the vertices of a [CPUMesh] are processed by the vertex shader when it is drawn,
+/// simulated using a first in first out post-transform vertex cache of a given size, see [CPUMesh::vertex_cache_statistics].
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VertexCacheStatistics {
+    /// The average number of processed vertices per triangle, called the average cache miss ratio (ACMR),
+    /// which is 3.0 in the worst case and close to 0.5 for a well optimized closed mesh.
+    pub acmr: f32,
+    /// The average number of times each vertex is processed, called the average transformed vertex ratio (ATVR), which is 1.0 in the best case.
+    pub atvr: f32,
+}
+
+///
+/// The vertex cache statistics before and after an optimization of a [CPUMesh], see for example [CPUMesh::optimize_vertex_cache].
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VertexCacheOptimization {
+    /// The statistics before the optimization.
+    pub before: VertexCacheStatistics,
+    /// The statistics after the optimization.
+    pub after: VertexCacheStatistics,
+}
+
+impl CPUMesh {
+    ///
+    /// Returns statistics about how many times the vertices are processed when the triangles are drawn in the current order
+    /// using a first in first out post-transform vertex cache of the given size, which is typically between 16 and 32 on current GPUs.
+    ///
+    pub fn vertex_cache_statistics(&self, cache_size: u32) -> VertexCacheStatistics {
+        let mut triangles = Vec::new();
+        self.for_each_triangle(|i0, i1, i2| triangles.push([i0 as u32, i1 as u32, i2 as u32]));
+        let misses = cache_misses(&triangles, self.positions.len() / 3, cache_size);
+        let miss_count = misses.iter().map(|&m| m as usize).sum::<usize>();
+        let mut used = vec![false; self.positions.len() / 3];
+        for &i in triangles.iter().flat_map(|t| t.iter()) {
+            used[i as usize] = true;
+        }
+        let used_count = used.iter().filter(|&&used| used).count();
+        VertexCacheStatistics {
+            acmr: miss_count as f32 / triangles.len().max(1) as f32,
+            atvr: miss_count as f32 / used_count.max(1) as f32,
+        }
+    }
+
+    ///
+    /// Reorders the triangles to reduce the number of times the vertices are processed by the vertex shader, using the Tipsify algorithm
+    /// which assumes a first in first out post-transform vertex cache of the given size, see [CPUMesh::vertex_cache_statistics].
+    /// Indices are computed first if the mesh has none, see [CPUMesh::compute_indices].
+    /// Call [CPUMesh::optimize_vertex_fetch] afterwards to also order the vertices in memory.
+    ///
+    pub fn optimize_vertex_cache(&mut self, cache_size: u32) -> VertexCacheOptimization {
+        let before = self.vertex_cache_statistics(cache_size);
+        self.compute_indices();
+        let indices = self.indices.as_ref().unwrap().into_u32();
+        let vertex_count = self.positions.len() / 3;
+        let triangles = indices
+            .chunks_exact(3)
+            .map(|t| [t[0], t[1], t[2]])
+            .collect::<Vec<_>>();
+        let order = tipsify(&triangles, vertex_count, cache_size);
+        self.indices = Some(Indices::U32(
+            order
+                .iter()
+                .flat_map(|&t| triangles[t as usize].iter().cloned())
+                .collect(),
+        ));
+        self.shrink_indices();
+        VertexCacheOptimization {
+            before,
+            after: self.vertex_cache_statistics(cache_size),
+        }
+    }
+
+    ///
+    /// Reorders the triangles to reduce overdraw, that is the number of times the same pixel is shaded, while keeping most of the vertex cache efficiency.
+    /// The triangles are split into clusters at the points where the vertex cache is flushed anyway, and into smaller clusters while the average cache miss ratio
+    /// of a cluster is at most the threshold times the ratio of the unsplit cluster, for example 1.05 allows the ratio to increase by about 5%.
+    /// The clusters are then ordered so that the clusters facing away from the center of the mesh are drawn first, since they are more likely to occlude other clusters.
+    /// The mesh should be optimized using [CPUMesh::optimize_vertex_cache] with the same cache size first.
+    ///
+    pub fn optimize_overdraw(
+        &mut self,
+        cache_size: u32,
+        threshold: f32,
+    ) -> VertexCacheOptimization {
+        let before = self.vertex_cache_statistics(cache_size);
+        let mut triangles = Vec::new();
+        self.for_each_triangle(|i0, i1, i2| triangles.push([i0 as u32, i1 as u32, i2 as u32]));
+        let misses = cache_misses(&triangles, self.positions.len() / 3, cache_size);
+
+        let mut hard_boundaries = (0..triangles.len())
+            .filter(|&i| i == 0 || misses[i] == 3)
+            .collect::<Vec<_>>();
+        hard_boundaries.push(triangles.len());
+        let mut boundaries = Vec::new();
+        let mut time_stamps = vec![0u64; self.positions.len() / 3];
+        let mut time = 0;
+        for cluster in hard_boundaries.windows(2) {
+            let (start, end) = (cluster[0], cluster[1]);
+            let cluster_misses = misses[start..end].iter().map(|&m| m as f32).sum::<f32>();
+            let max_acmr = threshold * cluster_misses / (end - start) as f32;
+            boundaries.push(start);
+
+            // Each new cluster starts with an empty cache since the clusters are drawn in a different order afterwards
+            time += u64::from(cache_size) + 1;
+            let (mut running_misses, mut running_count) = (0.0, 0.0);
+            for (i, triangle) in triangles.iter().enumerate().take(end).skip(start) {
+                for &v in triangle.iter() {
+                    if time - time_stamps[v as usize] > u64::from(cache_size) {
+                        time_stamps[v as usize] = time;
+                        time += 1;
+                        running_misses += 1.0;
+                    }
+                }
+                running_count += 1.0;
+                if i + 1 < end && running_misses / running_count <= max_acmr {
+                    boundaries.push(i + 1);
+                    time += u64::from(cache_size) + 1;
+                    running_misses = 0.0;
+                    running_count = 0.0;
+                }
+            }
+        }
+        boundaries.push(triangles.len());
+
+        let centroid_and_normal = |triangles: &[[u32; 3]]| {
+            let mut centroid = vec3(0.0, 0.0, 0.0);
+            let mut normal = vec3(0.0, 0.0, 0.0);
+            let mut area = 0.0;
+            for t in triangles.iter() {
+                let p = [
+                    self.position(t[0] as usize),
+                    self.position(t[1] as usize),
+                    self.position(t[2] as usize),
+                ];
+                let cross = (p[1] - p[0]).cross(p[2] - p[0]);
+                let a = cross.magnitude();
+                centroid += (p[0] + p[1] + p[2]) * (a / 3.0);
+                normal += cross;
+                area += a;
+            }
+            (centroid / area.max(f32::EPSILON), normal)
+        };
+        let (mesh_centroid, _) = centroid_and_normal(&triangles);
+        let mut clusters = boundaries
+            .windows(2)
+            .map(|cluster| {
+                let (centroid, normal) = centroid_and_normal(&triangles[cluster[0]..cluster[1]]);
+                let sort_key = if normal.magnitude2() > 0.0 {
+                    (centroid - mesh_centroid).dot(normal.normalize())
+                } else {
+                    0.0
+                };
+                (sort_key, cluster[0], cluster[1])
+            })
+            .collect::<Vec<_>>();
+        clusters.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+
+        self.indices = Some(Indices::U32(
+            clusters
+                .iter()
+                .flat_map(|&(_, start, end)| triangles[start..end].iter())
+                .flat_map(|t| t.iter().cloned())
+                .collect(),
+        ));
+        self.shrink_indices();
+        VertexCacheOptimization {
+            before,
+            after: self.vertex_cache_statistics(cache_size),
+        }
+    }
+
+    ///
+    /// Reorders the vertices in the order they are first used by the triangles, which improves the memory access pattern when the vertices are fetched by the GPU,
+    /// and removes the vertices which are not used by any triangle.
+    /// Should be called after the triangles are reordered, for example using [CPUMesh::optimize_vertex_cache].
+    /// Does nothing if the mesh has no indices.
+    ///
+    pub fn optimize_vertex_fetch(&mut self) {
+        if let Some(ref indices) = self.indices {
+            let mut new_indices = vec![u32::MAX; self.positions.len() / 3];
+            let mut vertices = Vec::new();
+            let indices = indices
+                .into_u32()
+                .into_iter()
+                .map(|i| {
+                    if new_indices[i as usize] == u32::MAX {
+                        new_indices[i as usize] = vertices.len() as u32;
+                        vertices.push(i);
+                    }
+                    new_indices[i as usize]
+                })
+                .collect();
+            *self = self.vertex_subset(&vertices);
+            self.indices = Some(Indices::U32(indices));
+            self.shrink_indices();
+        }
+    }
+}
+
+///
+/// Returns the number of vertices of each triangle which are not in a first in first out cache of the given size when the triangles are drawn in order.
+///
+fn cache_misses(triangles: &[[u32; 3]], vertex_count: usize, cache_size: u32) -> Vec<u8> {
+    // A vertex is in the cache if less than cache size vertices have been added to the cache since it was added
+    let mut time_stamps = vec![0u32; vertex_count];
+    let mut time = cache_size + 1;
+    triangles
+        .iter()
+        .map(|triangle| {
+            let mut misses = 0;
+            for &i in triangle.iter() {
+                if time - time_stamps[i as usize] > cache_size {
+                    time_stamps[i as usize] = time;
+                    time += 1;
+                    misses += 1;
+                }
+            }
+            misses
+        })
+        .collect()
+}
+
+///
+/// Returns the order of the triangles given by the Tipsify algorithm from the paper "Fast Triangle Reordering for Vertex Locality and Reduced Overdraw" by Sander, Nehab and Barczak.
+/// It fans around a vertex, emitting all of its remaining triangles, and then continues with the vertex of the emitted triangles
+/// which will stay in the cache the longest without being evicted before its remaining triangles are emitted.
+///
+fn tipsify(triangles: &[[u32; 3]], vertex_count: usize, cache_size: u32) -> Vec<u32> {
+    let mut adjacency_offsets = vec![0usize; vertex_count + 1];
+    for &i in triangles.iter().flat_map(|t| t.iter()) {
+        adjacency_offsets[i as usize + 1] += 1;
+    }
+    for i in 0..vertex_count {
+        adjacency_offsets[i + 1] += adjacency_offsets[i];
+    }
+    let mut adjacency = vec![0u32; adjacency_offsets[vertex_count]];
+    let mut fill = adjacency_offsets.clone();
+    for (triangle_index, triangle) in triangles.iter().enumerate() {
+        for &i in triangle.iter() {
+            adjacency[fill[i as usize]] = triangle_index as u32;
+            fill[i as usize] += 1;
+        }
+    }
+
+    let mut live_triangles = (0..vertex_count)
+        .map(|i| adjacency_offsets[i + 1] - adjacency_offsets[i])
+        .collect::<Vec<_>>();
+    let mut time_stamps = vec![0u32; vertex_count];
+    let mut time = cache_size + 1;
+    let mut emitted = vec![false; triangles.len()];
+    let mut dead_end_stack = Vec::new();
+    let mut order = Vec::with_capacity(triangles.len());
+    let mut cursor = 0;
+
+    let mut fanning_vertex = if vertex_count > 0 { Some(0) } else { None };
+    while let Some(vertex) = fanning_vertex {
+        let mut candidates = Vec::new();
+        for &triangle_index in
+            adjacency[adjacency_offsets[vertex]..adjacency_offsets[vertex + 1]].iter()
+        {
+            if emitted[triangle_index as usize] {
+                continue;
+            }
+            for &i in triangles[triangle_index as usize].iter() {
+                dead_end_stack.push(i);
+                candidates.push(i);
+                live_triangles[i as usize] -= 1;
+                if time - time_stamps[i as usize] > cache_size {
+                    time_stamps[i as usize] = time;
+                    time += 1;
+                }
+            }
+            emitted[triangle_index as usize] = true;
+            order.push(triangle_index);
+        }
+
+        // Prefer the candidate which entered the cache the earliest and will still be in the cache after its remaining triangles are emitted
+        let mut best = None;
+        let mut best_priority = 0;
+        for &i in candidates.iter() {
+            if live_triangles[i as usize] > 0 {
+                let age = time - time_stamps[i as usize];
+                let priority = if age + 2 * live_triangles[i as usize] as u32 <= cache_size {
+                    age
+                } else {
+                    0
+                };
+                if best.is_none() || priority > best_priority {
+                    best = Some(i as usize);
+                    best_priority = priority;
+                }
+            }
+        }
+        fanning_vertex = best.or_else(|| {
+            while let Some(i) = dead_end_stack.pop() {
+                if live_triangles[i as usize] > 0 {
+                    return Some(i as usize);
+                }
+            }
+            while cursor < vertex_count {
+                if live_triangles[cursor] > 0 {
+                    return Some(cursor);
+                }
+                cursor += 1;
+            }
+            None
+        });
+    }
+    order
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    ///
+    /// Returns the triangles as positions, each rotated so that it starts with its smallest position, in sorted order.
+    ///
+    fn triangle_set(mesh: &CPUMesh) -> Vec<[[u32; 3]; 3]> {
+        let mut triangles = Vec::new();
+        mesh.for_each_triangle(|i0, i1, i2| {
+            let mut triangle = [i0, i1, i2].map(|i| {
+                let p = mesh.position(i);
+                [p.x.to_bits(), p.y.to_bits(), p.z.to_bits()]
+            });
+            let first = (0..3).min_by_key(|&k| triangle[k]).unwrap();
+            triangle.rotate_left(first);
+            triangles.push(triangle);
+        });
+        triangles.sort_unstable();
+        triangles
+    }
+
+    fn shuffled_grid() -> CPUMesh {
+        let mut grid = CPUMesh::grid(16, 16);
+        let indices = grid.indices.as_ref().unwrap().into_u32();
+        let triangle_count = indices.len() / 3;
+        // 97 is coprime with the number of triangles, so this is a permutation
+        grid.indices = Some(Indices::U32(
+            (0..triangle_count)
+                .flat_map(|t| {
+                    let t = (t * 97) % triangle_count;
+                    indices[3 * t..3 * t + 3].to_vec()
+                })
+                .collect(),
+        ));
+        grid
+    }
+
+    #[test]
+    pub fn vertex_cache_statistics() {
+        // Each triangle of a strip shares two vertices with the previous triangle
+        let strip = CPUMesh {
+            positions: (0..12)
+                .flat_map(|i| vec![i as f32, (i % 2) as f32, 0.0])
+                .collect(),
+            indices: Some(Indices::U32(
+                (0..10).flat_map(|i| vec![i, i + 1, i + 2]).collect(),
+            )),
+            ..Default::default()
+        };
+        assert_eq!(
+            strip.vertex_cache_statistics(16),
+            VertexCacheStatistics {
+                acmr: 1.2,
+                atvr: 1.0
+            }
+        );
+
+        let mut unindexed = strip.clone();
+        unindexed.unindex();
+        assert_eq!(
+            unindexed.vertex_cache_statistics(16),
+            VertexCacheStatistics {
+                acmr: 3.0,
+                atvr: 1.0
+            }
+        );
+
+        let mut strip = strip;
+        strip.indices = Some(Indices::U32(vec![0, 1, 2, 3, 4, 5, 0, 1, 2]));
+        let statistics = strip.vertex_cache_statistics(3);
+        assert_eq!(statistics.acmr, 3.0);
+        assert_eq!(statistics.atvr, 1.5);
+    }
+
+    #[test]
+    pub fn optimize_vertex_cache() {
+        let mut grid = shuffled_grid();
+        let triangles = triangle_set(&grid);
+        let before = grid.vertex_cache_statistics(16);
+        let optimization = grid.optimize_vertex_cache(16);
+        assert_eq!(optimization.before, before);
+        assert_eq!(optimization.after, grid.vertex_cache_statistics(16));
+        assert!(optimization.after.acmr <= optimization.before.acmr);
+        assert!(optimization.after.acmr < 1.0);
+        assert_eq!(triangle_set(&grid), triangles);
+        grid.validate().unwrap();
+    }
+
+    #[test]
+    pub fn optimize_overdraw() {
+        let mut sphere = CPUMesh::sphere(16);
+        sphere.optimize_vertex_cache(16);
+        let triangles = triangle_set(&sphere);
+        let optimization = sphere.optimize_overdraw(16, 1.05);
+        assert_eq!(optimization.after, sphere.vertex_cache_statistics(16));
+        assert_eq!(triangle_set(&sphere), triangles);
+        sphere.validate().unwrap();
+
+        let mut grid = shuffled_grid();
+        let triangles = triangle_set(&grid);
+        grid.optimize_overdraw(16, 1.05);
+        assert_eq!(triangle_set(&grid), triangles);
+    }
+
+    #[test]
+    pub fn optimize_vertex_fetch() {
+        let mut grid = shuffled_grid();
+        // Add a few vertices which are not used by any triangle
+        let vertex_count = grid.positions.len() / 3;
+        let indices = grid.indices.as_ref().unwrap().into_u32();
+        let mut unused = grid.clone();
+        unused.transform(&Mat4::from_translation(vec3(0.0, 0.0, 1.0)));
+        grid = CPUMesh::merge(&[grid, unused]);
+        grid.indices = Some(Indices::U32(indices));
+        let triangles = triangle_set(&grid);
+
+        grid.optimize_vertex_fetch();
+        assert_eq!(triangle_set(&grid), triangles);
+        assert_eq!(grid.positions.len() / 3, vertex_count);
+        grid.validate().unwrap();
+
+        // The vertices are in the order they are first used
+        let mut next = 0;
+        for i in grid.indices.as_ref().unwrap().into_u32() {
+            assert!(i <= next);
+            if i == next {
+                next += 1;
+            }
+        }
+    }
+}
@@ -0,0 +1,628 @@
+use super::position_key;
+use crate::core::*;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+///
+/// A level in a level of detail chain, see [CPUMesh::lod_chain].
+///
+#[derive(Clone, Debug)]
+pub struct LODLevel {
+    /// The mesh of this level.
+    pub mesh: CPUMesh,
+    /// An estimate of the distance between the surface of this level and the surface of the original mesh, in the same units as the positions.
+    /// The level can for example be used when the error projected onto the screen is smaller than a pixel.
+    pub error: f32,
+}
+
+impl CPUMesh {
+    ///
+    /// Simplifies the mesh by collapsing edges in the order given by the quadric error metric until the number of triangles is at most the target triangle count
+    /// or the next collapse would give an error larger than the given max error, so use `0` as target triangle count to only target the error
+    /// and [f32::INFINITY] as max error to only target the triangle count.
+    /// The error is an estimate of the distance between the simplified and the original surface in the same units as the positions, and the largest error is returned.
+    ///
+    /// The vertices are only moved to the position of another vertex, so all vertex attributes are kept unchanged.
+    /// Vertices along uv, normal or color seams are only moved along the seam, so the seams are preserved,
+    /// and vertices on the borders of the mesh are never moved,
+    /// so meshes which share a border, for example the parts of a model with different materials, stay connected.
+    /// The mesh is indexed afterwards and degenerate triangles are removed.
+    ///
+    pub fn simplify(&mut self, target_triangle_count: usize, max_error: f32) -> f32 {
+        let mut simplifier = Simplifier::new(self);
+        simplifier.run(target_triangle_count, max_error);
+        *self = simplifier.mesh();
+        simplifier.error
+    }
+
+    ///
+    /// Returns a chain of levels of detail, starting with this mesh, where each level is simplified using [CPUMesh::simplify]
+    /// to the given reduction, for example `0.5`, times the number of triangles in the previous level.
+    /// The chain contains at most the given number of levels and ends when the mesh cannot be simplified any further.
+    /// The levels are ordered by increasing error, so a distance based level of detail renderer should use the last level where the error is acceptable at the given distance.
+    ///
+    pub fn lod_chain(&self, level_count: usize, reduction: f32) -> Vec<LODLevel> {
+        let mut levels = vec![LODLevel {
+            mesh: self.clone(),
+            error: 0.0,
+        }];
+        let mut simplifier = Simplifier::new(self);
+        while levels.len() < level_count {
+            let triangle_count = simplifier.triangle_count;
+            simplifier.run(
+                (triangle_count as f32 * reduction.clamp(0.0, 1.0)) as usize,
+                f32::INFINITY,
+            );
+            if simplifier.triangle_count >= triangle_count {
+                break;
+            }
+            levels.push(LODLevel {
+                mesh: simplifier.mesh(),
+                error: simplifier.error,
+            });
+        }
+        levels
+    }
+}
+
+///
+/// The triangles at each edge between two groups together with the two wedges of the edge in the triangle.
+///
+type EdgeTriangles = HashMap<(u32, u32), Vec<(u32, u32, u32)>>;
+
+///
+/// A symmetric 4x4 matrix which gives the sum of the weighted squared distances from a point to a set of planes.
+///
+#[derive(Clone, Copy, Default)]
+struct Quadric {
+    m: [f64; 10],
+    weight: f64,
+}
+
+impl Quadric {
+    fn from_plane(normal: Vec3, point: Vec3, weight: f64) -> Self {
+        let (a, b, c) = (normal.x as f64, normal.y as f64, normal.z as f64);
+        let d = -(a * point.x as f64 + b * point.y as f64 + c * point.z as f64);
+        let m = [
+            a * a,
+            a * b,
+            a * c,
+            a * d,
+            b * b,
+            b * c,
+            b * d,
+            c * c,
+            c * d,
+            d * d,
+        ];
+        Self {
+            m: m.map(|v| v * weight),
+            weight,
+        }
+    }
+
+    fn add(&mut self, other: &Self) {
+        for i in 0..10 {
+            self.m[i] += other.m[i];
+        }
+        self.weight += other.weight;
+    }
+
+    ///
+    /// Returns the weighted average of the squared distances from the point to the planes.
+    ///
+    fn error(&self, point: Vec3) -> f64 {
+        let (x, y, z) = (point.x as f64, point.y as f64, point.z as f64);
+        let m = &self.m;
+        let sum = m[0] * x * x
+            + 2.0 * m[1] * x * y
+            + 2.0 * m[2] * x * z
+            + 2.0 * m[3] * x
+            + m[4] * y * y
+            + 2.0 * m[5] * y * z
+            + 2.0 * m[6] * y
+            + m[7] * z * z
+            + 2.0 * m[8] * z
+            + m[9];
+        if self.weight > 0.0 {
+            sum.max(0.0) / self.weight
+        } else {
+            0.0
+        }
+    }
+}
+
+///
+/// The state of an ongoing simplification.
+/// The vertices of the mesh are called wedges and the wedges at the same position are grouped together,
+/// so an edge collapse moves all the wedges of one group to the position of another group.
+/// The queue contains the cheapest collapse of each group, which is updated when the neighbourhood of the group changes.
+///
+struct Simplifier {
+    mesh: CPUMesh,
+    triangles: Vec<[u32; 3]>,
+    removed: Vec<bool>,
+    triangle_count: usize,
+    groups: Vec<u32>,
+    positions: Vec<Vec3>,
+    quadrics: Vec<Quadric>,
+    group_triangles: Vec<Vec<u32>>,
+    locked: Vec<bool>,
+    alive: Vec<bool>,
+    versions: Vec<u32>,
+    targets: Vec<u32>,
+    queue: BinaryHeap<Reverse<(u64, u32, u32)>>,
+    error: f32,
+}
+
+impl Simplifier {
+    fn new(mesh: &CPUMesh) -> Self {
+        let mut mesh = mesh.clone();
+        mesh.weld(0.0);
+
+        let mut groups = Vec::with_capacity(mesh.positions.len() / 3);
+        let mut positions = Vec::new();
+        let mut group_indices = HashMap::new();
+        for i in 0..mesh.positions.len() / 3 {
+            let p = mesh.position(i);
+            groups.push(*group_indices.entry(position_key(p)).or_insert_with(|| {
+                positions.push(p);
+                positions.len() as u32 - 1
+            }));
+        }
+        let group_count = positions.len();
+
+        let mut triangles = Vec::new();
+        mesh.for_each_triangle(|i0, i1, i2| {
+            let (g0, g1, g2) = (groups[i0], groups[i1], groups[i2]);
+            if g0 != g1 && g0 != g2 && g1 != g2 {
+                triangles.push([i0 as u32, i1 as u32, i2 as u32]);
+            }
+        });
+
+        let mut quadrics = vec![Quadric::default(); group_count];
+        let mut group_triangles = vec![Vec::new(); group_count];
+        let mut edges: EdgeTriangles = HashMap::new();
+        for (triangle_index, triangle) in triangles.iter().enumerate() {
+            let g = triangle.map(|w| groups[w as usize]);
+            let p = g.map(|g| positions[g as usize]);
+            let cross = (p[1] - p[0]).cross(p[2] - p[0]);
+            let area = 0.5 * cross.magnitude() as f64;
+            if area > 0.0 {
+                let quadric = Quadric::from_plane(cross.normalize(), p[0], area);
+                for &g in g.iter() {
+                    quadrics[g as usize].add(&quadric);
+                }
+            }
+            for k in 0..3 {
+                group_triangles[g[k] as usize].push(triangle_index as u32);
+                let (w0, w1) = (triangle[k], triangle[(k + 1) % 3]);
+                let (w0, w1) = if g[k] < g[(k + 1) % 3] {
+                    (w0, w1)
+                } else {
+                    (w1, w0)
+                };
+                edges
+                    .entry((groups[w0 as usize], groups[w1 as usize]))
+                    .or_default()
+                    .push((triangle_index as u32, w0, w1));
+            }
+        }
+
+        // Vertices on borders and non-manifold edges are locked, and the seams are preserved by adding planes perpendicular to the triangles along the seams
+        let mut locked = vec![false; group_count];
+        for (&(g0, g1), edge_triangles) in edges.iter() {
+            if edge_triangles.len() != 2 {
+                locked[g0 as usize] = true;
+                locked[g1 as usize] = true;
+            } else if (edge_triangles[0].1, edge_triangles[0].2)
+                != (edge_triangles[1].1, edge_triangles[1].2)
+            {
+                let (p0, p1) = (positions[g0 as usize], positions[g1 as usize]);
+                for &(triangle_index, _, _) in edge_triangles.iter() {
+                    let p = triangles[triangle_index as usize]
+                        .map(|w| positions[groups[w as usize] as usize]);
+                    let normal = (p[1] - p[0]).cross(p[2] - p[0]);
+                    let seam_normal = (p1 - p0).cross(normal);
+                    if seam_normal.magnitude2() > 0.0 {
+                        let quadric = Quadric::from_plane(
+                            seam_normal.normalize(),
+                            p0,
+                            p0.distance2(p1) as f64,
+                        );
+                        quadrics[g0 as usize].add(&quadric);
+                        quadrics[g1 as usize].add(&quadric);
+                    }
+                }
+            }
+        }
+
+        let mut simplifier = Self {
+            mesh,
+            removed: vec![false; triangles.len()],
+            triangle_count: triangles.len(),
+            triangles,
+            groups,
+            positions,
+            quadrics,
+            group_triangles,
+            locked,
+            alive: vec![true; group_count],
+            versions: vec![0; group_count],
+            targets: vec![0; group_count],
+            queue: BinaryHeap::new(),
+            error: 0.0,
+        };
+        for group in 0..group_count as u32 {
+            simplifier.update(group, false);
+        }
+        simplifier
+    }
+
+    ///
+    /// Returns the squared error of collapsing the first group into the second group.
+    ///
+    fn cost(&self, from: u32, to: u32) -> f64 {
+        let mut quadric = self.quadrics[from as usize];
+        quadric.add(&self.quadrics[to as usize]);
+        quadric.error(self.positions[to as usize])
+    }
+
+    ///
+    /// Adds the cheapest collapse of the given group into one of its neighbours to the queue and invalidates the previous one.
+    /// If checked, only a collapse which is currently valid is added.
+    ///
+    fn update(&mut self, group: u32, checked: bool) {
+        self.versions[group as usize] += 1;
+        if self.locked[group as usize] || !self.alive[group as usize] {
+            return;
+        }
+        let mut candidates = self
+            .neighbours(group)
+            .into_iter()
+            .map(|neighbour| (self.cost(group, neighbour), neighbour))
+            .collect::<Vec<_>>();
+        candidates.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+        let best = candidates
+            .into_iter()
+            .find(|&(_, neighbour)| !checked || self.collapse_wedges(group, neighbour).is_some());
+        if let Some((cost, target)) = best {
+            self.targets[group as usize] = target;
+            self.queue.push(Reverse((
+                cost.to_bits(),
+                group,
+                self.versions[group as usize],
+            )));
+        }
+    }
+
+    ///
+    /// Collapses edges until the number of triangles is at most the target triangle count or the next collapse would exceed the max error.
+    ///
+    fn run(&mut self, target_triangle_count: usize, max_error: f32) {
+        while self.triangle_count > target_triangle_count {
+            let entry = match self.queue.pop() {
+                Some(entry) => entry,
+                None => break,
+            };
+            let Reverse((cost, from, version)) = entry;
+            if self.versions[from as usize] != version {
+                continue;
+            }
+            let to = self.targets[from as usize];
+            let error = f64::from_bits(cost).sqrt() as f32;
+            if error > max_error {
+                self.queue.push(entry);
+                break;
+            }
+            if let Some(wedge_map) = self.collapse_wedges(from, to) {
+                self.collapse(from, to, &wedge_map);
+                self.error = self.error.max(error);
+            } else {
+                self.update(from, true);
+            }
+        }
+    }
+
+    ///
+    /// Returns the groups which share a triangle with the given group.
+    ///
+    fn neighbours(&self, group: u32) -> Vec<u32> {
+        let mut neighbours = Vec::new();
+        for &triangle_index in self.group_triangles[group as usize].iter() {
+            for &wedge in self.triangles[triangle_index as usize].iter() {
+                let g = self.groups[wedge as usize];
+                if g != group && !neighbours.contains(&g) {
+                    neighbours.push(g);
+                }
+            }
+        }
+        neighbours
+    }
+
+    ///
+    /// Returns which wedge of the second group each wedge of the first group becomes if the first group is collapsed into the second group
+    /// or `None` if the collapse would break a seam, change the topology or flip a triangle.
+    ///
+    fn collapse_wedges(&self, from: u32, to: u32) -> Option<HashMap<u32, u32>> {
+        let mut wedge_map = HashMap::new();
+        let mut opposite = Vec::new();
+        for &triangle_index in self.group_triangles[from as usize].iter() {
+            let triangle = self.triangles[triangle_index as usize];
+            let g = triangle.map(|w| self.groups[w as usize]);
+            let k = g.iter().position(|&g| g == from).unwrap();
+            if let Some(l) = g.iter().position(|&g| g == to) {
+                if *wedge_map.entry(triangle[k]).or_insert(triangle[l]) != triangle[l] {
+                    return None;
+                }
+                opposite.push(g[3 - k - l]);
+            }
+        }
+        if opposite.is_empty() {
+            return None;
+        }
+
+        // The groups connected to both groups must be the opposite corners of the triangles which are removed, otherwise the mesh is folded
+        let to_neighbours = self.neighbours(to);
+        let shared_neighbours = self
+            .neighbours(from)
+            .into_iter()
+            .filter(|g| to_neighbours.contains(g))
+            .count();
+        if shared_neighbours != opposite.len() {
+            return None;
+        }
+
+        for &triangle_index in self.group_triangles[from as usize].iter() {
+            let triangle = self.triangles[triangle_index as usize];
+            let g = triangle.map(|w| self.groups[w as usize]);
+            if g.contains(&to) {
+                continue;
+            }
+            let k = g.iter().position(|&g| g == from).unwrap();
+            if !wedge_map.contains_key(&triangle[k]) {
+                return None;
+            }
+            let p = g.map(|g| self.positions[g as usize]);
+            let mut q = p;
+            q[k] = self.positions[to as usize];
+            let normal = (p[1] - p[0]).cross(p[2] - p[0]);
+            let new_normal = (q[1] - q[0]).cross(q[2] - q[0]);
+            if normal.dot(new_normal) <= 0.0 {
+                return None;
+            }
+        }
+        Some(wedge_map)
+    }
+
+    fn collapse(&mut self, from: u32, to: u32, wedge_map: &HashMap<u32, u32>) {
+        let from_triangles = std::mem::take(&mut self.group_triangles[from as usize]);
+        let groups = &self.groups;
+        for &triangle_index in from_triangles.iter() {
+            let triangle = &mut self.triangles[triangle_index as usize];
+            if triangle.iter().any(|&w| groups[w as usize] == to) {
+                self.removed[triangle_index as usize] = true;
+                self.triangle_count -= 1;
+            } else {
+                for wedge in triangle.iter_mut() {
+                    if let Some(&new_wedge) = wedge_map.get(wedge) {
+                        *wedge = new_wedge;
+                    }
+                }
+                self.group_triangles[to as usize].push(triangle_index);
+            }
+        }
+        let removed = &self.removed;
+        for g in self.neighbours(to).into_iter().chain(std::iter::once(to)) {
+            self.group_triangles[g as usize].retain(|&t| !removed[t as usize]);
+        }
+
+        let quadric = self.quadrics[from as usize];
+        self.quadrics[to as usize].add(&quadric);
+        self.alive[from as usize] = false;
+        self.versions[from as usize] += 1;
+        self.update(to, false);
+        for neighbour in self.neighbours(to) {
+            self.update(neighbour, false);
+        }
+    }
+
+    ///
+    /// Returns the simplified mesh containing the remaining triangles and only the wedges used by them.
+    ///
+    fn mesh(&self) -> CPUMesh {
+        let mut vertices = Vec::new();
+        let mut new_indices = HashMap::new();
+        let mut indices = Vec::with_capacity(self.triangle_count * 3);
+        for (triangle, _) in self
+            .triangles
+            .iter()
+            .zip(self.removed.iter())
+            .filter(|(_, removed)| !**removed)
+        {
+            for &wedge in triangle.iter() {
+                indices.push(*new_indices.entry(wedge).or_insert_with(|| {
+                    vertices.push(wedge);
+                    vertices.len() as u32 - 1
+                }));
+            }
+        }
+        let mut mesh = self.mesh.vertex_subset(&vertices);
+        mesh.indices = Some(Indices::U32(indices));
+        mesh.shrink_indices();
+        mesh
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn triangles(mesh: &CPUMesh) -> Vec<[Vec3; 3]> {
+        let mut triangles = Vec::new();
+        mesh.for_each_triangle(|i0, i1, i2| {
+            triangles.push([mesh.position(i0), mesh.position(i1), mesh.position(i2)])
+        });
+        triangles
+    }
+
+    fn normal(triangle: &[Vec3; 3]) -> Vec3 {
+        (triangle[1] - triangle[0]).cross(triangle[2] - triangle[0])
+    }
+
+    fn area(triangles: &[[Vec3; 3]]) -> f32 {
+        triangles.iter().map(|t| 0.5 * normal(t).magnitude()).sum()
+    }
+
+    ///
+    /// Returns a grid spanning `[-1..1]` in the x and y axes made of two halves with a uv seam at `x = 0`.
+    ///
+    fn grid_with_seam() -> CPUMesh {
+        let mut left = CPUMesh::grid(4, 8);
+        left.transform(
+            &(Mat4::from_translation(vec3(-0.5, 0.0, 0.0))
+                * Mat4::from_nonuniform_scale(0.5, 1.0, 1.0)),
+        );
+        let mut right = CPUMesh::grid(4, 8);
+        right.transform(
+            &(Mat4::from_translation(vec3(0.5, 0.0, 0.0))
+                * Mat4::from_nonuniform_scale(0.5, 1.0, 1.0)),
+        );
+        CPUMesh::merge(&[left, right])
+    }
+
+    #[test]
+    pub fn simplify_to_target_triangle_count() {
+        let mut sphere = CPUMesh::sphere(16);
+        let triangle_count = triangles(&sphere).len();
+        let error = sphere.simplify(triangle_count / 4, f32::INFINITY);
+        sphere.validate().unwrap();
+        let simplified_count = triangles(&sphere).len();
+        assert!(simplified_count <= triangle_count / 4);
+        assert!(simplified_count > 0);
+        assert!(error > 0.0 && error < 1.0);
+
+        // The vertices are only moved onto other vertices, so they are still on the sphere
+        for i in 0..sphere.positions.len() / 3 {
+            assert!((sphere.position(i).magnitude() - 1.0).abs() < 0.0001);
+        }
+    }
+
+    #[test]
+    pub fn simplify_to_max_error() {
+        let mut sphere = CPUMesh::sphere(16);
+        let triangle_count = triangles(&sphere).len();
+        let max_error = 0.01;
+        let error = sphere.simplify(0, max_error);
+        assert!(error <= max_error);
+        let simplified_count = triangles(&sphere).len();
+        assert!(simplified_count < triangle_count);
+
+        let mut coarse = CPUMesh::sphere(16);
+        let coarse_error = coarse.simplify(0, 10.0 * max_error);
+        assert!(coarse_error <= 10.0 * max_error);
+        assert!(coarse_error >= error);
+        assert!(triangles(&coarse).len() < simplified_count);
+
+        let mut unchanged = CPUMesh::sphere(16);
+        assert_eq!(unchanged.simplify(0, 0.0), 0.0);
+        assert_eq!(triangles(&unchanged).len(), triangle_count);
+    }
+
+    #[test]
+    pub fn simplify_keeps_borders() {
+        let mut grid = CPUMesh::grid(8, 8);
+        let error = grid.simplify(0, 0.0);
+        assert_eq!(error, 0.0);
+        let simplified = triangles(&grid);
+        assert!(simplified.len() < 128);
+        assert!((area(&simplified) - 4.0).abs() < 0.0001);
+
+        for i in 0..=8 {
+            let t = i as f32 / 4.0 - 1.0;
+            for &border in [
+                vec3(t, -1.0, 0.0),
+                vec3(t, 1.0, 0.0),
+                vec3(-1.0, t, 0.0),
+                vec3(1.0, t, 0.0),
+            ]
+            .iter()
+            {
+                assert!(
+                    (0..grid.positions.len() / 3)
+                        .any(|i| grid.position(i).distance(border) < 0.0001),
+                    "the border vertex {:?} is removed",
+                    border
+                );
+            }
+        }
+    }
+
+    #[test]
+    pub fn simplify_keeps_seams() {
+        let mut grid = grid_with_seam();
+        grid.simplify(0, f32::INFINITY);
+        let simplified = triangles(&grid);
+        assert!(simplified.len() < 128);
+
+        // No triangle crosses the seam, so both halves still cover half of the grid
+        for triangle in simplified.iter() {
+            assert!(triangle.iter().all(|p| p.x <= 0.0) || triangle.iter().all(|p| p.x >= 0.0));
+        }
+        let left = simplified
+            .iter()
+            .filter(|t| t.iter().all(|p| p.x <= 0.0))
+            .cloned()
+            .collect::<Vec<_>>();
+        assert!((area(&left) - 2.0).abs() < 0.0001);
+        assert!((area(&simplified) - 4.0).abs() < 0.0001);
+
+        // The uv coordinates of each half are kept
+        grid.for_each_triangle(|i0, i1, i2| {
+            let u = [i0, i1, i2].map(|i| grid.uv(i).unwrap().x);
+            let x = [i0, i1, i2].map(|i| grid.position(i).x);
+            for k in 0..3 {
+                let expected = if x.iter().all(|&x| x <= 0.0) {
+                    x[k] + 1.0
+                } else {
+                    x[k]
+                };
+                assert!((u[k] - expected).abs() < 0.0001);
+            }
+        });
+    }
+
+    #[test]
+    pub fn simplify_never_flips_triangles() {
+        let mut sphere = CPUMesh::sphere(16);
+        sphere.simplify(8, f32::INFINITY);
+        for triangle in triangles(&sphere).iter() {
+            let center = (triangle[0] + triangle[1] + triangle[2]) / 3.0;
+            assert!(normal(triangle).dot(center) > 0.0);
+        }
+
+        let mut grid = grid_with_seam();
+        grid.simplify(0, f32::INFINITY);
+        for triangle in triangles(&grid).iter() {
+            assert!(normal(triangle).z > 0.0);
+        }
+    }
+
+    #[test]
+    pub fn lod_chain() {
+        let sphere = CPUMesh::sphere(16);
+        let levels = sphere.lod_chain(5, 0.5);
+        assert_eq!(levels.len(), 5);
+        assert_eq!(levels[0].error, 0.0);
+        assert_eq!(triangles(&levels[0].mesh), triangles(&sphere));
+        for pair in levels.windows(2) {
+            pair[1].mesh.validate().unwrap();
+            assert!(pair[1].error >= pair[0].error);
+            assert!(triangles(&pair[1].mesh).len() < triangles(&pair[0].mesh).len());
+        }
+
+        // The chain ends when the mesh cannot be simplified any further
+        let levels = CPUMesh::square().lod_chain(5, 0.5);
+        assert_eq!(levels.len(), 1);
+    }
+}
@@ -0,0 +1,162 @@
+use crate::core::*;
+
+///
+/// A node in a [CPUScene] hierarchy.
+/// Contains a transformation relative to the parent node and references to the meshes and materials attached to the node.
+///
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CPUNode {
+    /// Name.
+    pub name: String,
+    /// The transformation of this node relative to the parent node.
+    pub transformation: Mat4,
+    /// The index of the parent node in [CPUScene::nodes] or `None` if this node does not have a parent.
+    pub parent: Option<usize>,
+    /// The indices of the child nodes in [CPUScene::nodes].
+    pub children: Vec<usize>,
+    /// The indices of the meshes attached to this node in the list of [CPUMesh]es loaded together with the scene.
+    pub meshes: Vec<usize>,
+    /// The indices of the materials in the list of [CPUMaterial]s loaded together with the scene.
+    /// Contains one entry for each mesh in [Self::meshes] which is `None` if the mesh does not have a material.
+    pub materials: Vec<Option<usize>>,
+    /// The index of the skin in [CPUScene::skins] which is applied to the meshes attached to this node or `None` if the meshes are not skinned.
+    pub skin: Option<usize>,
+    /// The default weights of the morph targets of the meshes attached to this node, see [CPUMesh::morph_targets].
+    /// Empty if the meshes do not have morph targets.
+    pub morph_weights: Vec<f32>,
+}
+
+impl Default for CPUNode {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            transformation: Mat4::identity(),
+            parent: None,
+            children: Vec::new(),
+            meshes: Vec::new(),
+            materials: Vec::new(),
+            skin: None,
+            morph_weights: Vec::new(),
+        }
+    }
+}
+
+///
+/// A skin, ie. a hierarchy of joints which deforms the vertices of a skinned mesh, see [CPUMesh::joints] and [CPUMesh::weights].
+///
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CPUSkin {
+    /// Name.
+    pub name: String,
+    /// The indices of the nodes in [CPUScene::nodes] which are used as joints.
+    /// The joint indices in [CPUMesh::joints] refer to the nodes in this list.
+    pub joints: Vec<usize>,
+    /// The matrices which transform the vertices of the mesh into the local space of each joint in the bind pose.
+    /// Contains one matrix for each joint in [Self::joints].
+    pub inverse_bind_matrices: Vec<Mat4>,
+}
+
+impl CPUSkin {
+    ///
+    /// Returns the joint transformations to use for a [SkinnedModel](crate::SkinnedModel) given the world transformation of each node in the scene,
+    /// for example computed using [CPUAnimation::world_transformations], and the world transformation of the node which the skinned mesh is attached to.
+    /// The world transformation of the mesh node should be used as the transformation of the skinned model.
+    ///
+    pub fn joint_transformations(
+        &self,
+        world_transformations: &[Mat4],
+        mesh_world_transformation: &Mat4,
+    ) -> Vec<Mat4> {
+        let inverse_mesh_transformation = mesh_world_transformation
+            .invert()
+            .unwrap_or(Mat4::identity());
+        self.joints
+            .iter()
+            .enumerate()
+            .map(|(i, joint)| {
+                inverse_mesh_transformation
+                    * world_transformations[*joint]
+                    * self
+                        .inverse_bind_matrices
+                        .get(i)
+                        .cloned()
+                        .unwrap_or(Mat4::identity())
+            })
+            .collect()
+    }
+}
+
+///
+/// A CPU-side version of a scene, ie. a hierarchy of nodes where each node has a transformation relative to its parent
+/// and references a number of meshes and materials.
+/// Can be constructed manually or loaded via [io](crate::io).
+///
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CPUScene {
+    /// Name.
+    pub name: String,
+    /// All nodes in the scene. The nodes refer to each other using the index into this list.
+    pub nodes: Vec<CPUNode>,
+    /// The indices of the root nodes in [Self::nodes].
+    pub roots: Vec<usize>,
+    /// Key frame animations of the nodes in the scene.
+    pub animations: Vec<CPUAnimation>,
+    /// Skins used for skinning the meshes attached to the nodes in the scene.
+    pub skins: Vec<CPUSkin>,
+    /// The lights attached to the nodes in the scene.
+    pub lights: Vec<CPULight>,
+    /// The cameras attached to the nodes in the scene.
+    pub cameras: Vec<CPUCamera>,
+}
+
+impl CPUScene {
+    ///
+    /// Returns the transformation from the local space of the node with the given index to world space,
+    /// ie. the transformation of the node multiplied with the transformations of all of its ancestors.
+    ///
+    pub fn world_transformation(&self, node_index: usize) -> Mat4 {
+        let node = &self.nodes[node_index];
+        if let Some(parent) = node.parent {
+            self.world_transformation(parent) * node.transformation
+        } else {
+            node.transformation
+        }
+    }
+
+    ///
+    /// Returns a flat list of meshes where the world transformation of each node is baked into the
+    /// meshes attached to that node using [CPUMesh::transform].
+    /// The meshes are cloned from the given list of meshes, once for each node referencing them, in depth-first order starting from the root nodes.
+    ///
+    pub fn flatten(&self, meshes: &[CPUMesh]) -> Vec<CPUMesh> {
+        let mut flat_meshes = Vec::new();
+        for root in self.roots.iter() {
+            self.flatten_node(*root, &Mat4::identity(), meshes, &mut flat_meshes);
+        }
+        flat_meshes
+    }
+
+    fn flatten_node(
+        &self,
+        node_index: usize,
+        parent_transformation: &Mat4,
+        meshes: &[CPUMesh],
+        flat_meshes: &mut Vec<CPUMesh>,
+    ) {
+        let node = &self.nodes[node_index];
+        let transformation = parent_transformation * node.transformation;
+        for mesh_index in node.meshes.iter() {
+            let mut mesh = meshes[*mesh_index].clone();
+            if transformation != Mat4::identity() {
+                mesh.transform(&transformation);
+            }
+            flat_meshes.push(mesh);
+        }
+        for child in node.children.iter() {
+            self.flatten_node(*child, &transformation, meshes, flat_meshes);
+        }
+    }
+}
@@ -14,6 +14,19 @@ pub struct Mesh {
     pub uv_buffer: Option<VertexBuffer>,
     /// Buffer with the color data, ie. `(r, g, b)` for each vertex.
     pub color_buffer: Option<VertexBuffer>,
+    /// Buffer with the joint indices used for skinning, ie. `(j0, j1, j2, j3)` for each vertex.
+    pub joint_buffer: Option<VertexBuffer>,
+    /// Buffer with the joint weights used for skinning, ie. `(w0, w1, w2, w3)` for each vertex.
+    pub weight_buffer: Option<VertexBuffer>,
+    /// Texture with the position displacements of the morph targets, see [MorphTarget].
+    /// The displacements of each morph target are stored in consecutive rows of texels with one texel for each vertex.
+    pub morph_position_texture: Option<Texture2D<f32>>,
+    /// Texture with the normal displacements of the morph targets, stored in the same layout as [Self::morph_position_texture].
+    pub morph_normal_texture: Option<Texture2D<f32>>,
+    /// Texture with the tangent displacements of the morph targets, stored in the same layout as [Self::morph_position_texture].
+    pub morph_tangent_texture: Option<Texture2D<f32>>,
+    /// The number of morph targets.
+    pub morph_target_count: usize,
     /// Buffer with the index data, ie. three contiguous integers define the triangle where each integer is and index into the other vertex buffers.
     pub index_buffer: Option<ElementBuffer>,
     /// Optional name of the mesh.
@@ -58,6 +71,45 @@ impl Mesh {
         } else {
             None
         };
+        let joint_buffer = if let Some(ref joints) = cpu_mesh.joints {
+            Some(VertexBuffer::new_with_static(context, joints)?)
+        } else {
+            None
+        };
+        let weight_buffer = if let Some(ref weights) = cpu_mesh.weights {
+            Some(VertexBuffer::new_with_static(context, weights)?)
+        } else {
+            None
+        };
+        let vertex_count = cpu_mesh.positions.len() / 3;
+        let morph_targets = &cpu_mesh.morph_targets;
+        let morph_position_texture = if !morph_targets.is_empty() {
+            Some(morph_texture(
+                context,
+                vertex_count,
+                morph_targets.iter().map(|t| Some(&t.positions)),
+            )?)
+        } else {
+            None
+        };
+        let morph_normal_texture = if morph_targets.iter().any(|t| t.normals.is_some()) {
+            Some(morph_texture(
+                context,
+                vertex_count,
+                morph_targets.iter().map(|t| t.normals.as_ref()),
+            )?)
+        } else {
+            None
+        };
+        let morph_tangent_texture = if morph_targets.iter().any(|t| t.tangents.is_some()) {
+            Some(morph_texture(
+                context,
+                vertex_count,
+                morph_targets.iter().map(|t| t.tangents.as_ref()),
+            )?)
+        } else {
+            None
+        };
         Ok(Self {
             position_buffer,
             normal_buffer,
@@ -65,7 +117,46 @@ impl Mesh {
             index_buffer,
             uv_buffer,
             color_buffer,
+            joint_buffer,
+            weight_buffer,
+            morph_position_texture,
+            morph_normal_texture,
+            morph_tangent_texture,
+            morph_target_count: morph_targets.len(),
             name: cpu_mesh.name.clone(),
         })
     }
 }
+
+const MAX_MORPH_TEXTURE_WIDTH: usize = 2048;
+
+fn morph_texture<'a>(
+    context: &Context,
+    vertex_count: usize,
+    displacements: impl Iterator<Item = Option<&'a Vec<f32>>>,
+) -> ThreeDResult<Texture2D<f32>> {
+    let width = vertex_count.clamp(1, MAX_MORPH_TEXTURE_WIDTH);
+    let rows = vertex_count.div_ceil(width);
+    let mut data = Vec::new();
+    for displacement in displacements {
+        let start = data.len();
+        if let Some(displacement) = displacement {
+            data.extend(displacement.iter().take(vertex_count * 3));
+        }
+        data.resize(start + rows * width * 3, 0.0);
+    }
+    Texture2D::new(
+        context,
+        &CPUTexture {
+            height: (data.len() / (width * 3)) as u32,
+            data,
+            width: width as u32,
+            format: Format::RGB,
+            min_filter: Interpolation::Nearest,
+            mag_filter: Interpolation::Nearest,
+            mip_map_filter: None,
+            wrap_s: Wrapping::ClampToEdge,
+            wrap_t: Wrapping::ClampToEdge,
+        },
+    )
+}
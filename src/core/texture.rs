@@ -25,11 +25,20 @@ mod depth_target_texture_cube_map;
 #[doc(inline)]
 pub use depth_target_texture_cube_map::*;
 
+mod compressed;
+#[doc(inline)]
+pub use compressed::*;
+
+mod cube_map_layout;
+#[doc(inline)]
+pub use cube_map_layout::*;
+
 ///
 /// Possible modes of interpolation which determines the texture output between texture pixels.
 ///
 #[allow(missing_docs)]
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Interpolation {
     Nearest,
     Linear,
@@ -41,6 +50,7 @@ pub enum Interpolation {
 ///
 #[allow(missing_docs)]
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Wrapping {
     Repeat,
     MirroredRepeat,
@@ -63,6 +73,7 @@ impl TextureDataType for f32 {}
 ///
 #[allow(missing_docs)]
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Format {
     R,
     RG,
@@ -87,6 +98,7 @@ impl Format {
 /// Can be constructed manually or loaded via [Loader](crate::Loader).
 ///
 #[allow(missing_docs)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CPUTexture<T: TextureDataType> {
     pub data: Vec<T>,
     pub width: u32,
@@ -166,6 +178,7 @@ impl<T: TextureDataType> std::fmt::Debug for CPUTexture<T> {
 /// Can be constructed manually or loaded via [Loader](crate::Loader).
 ///
 #[allow(missing_docs)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CPUTextureCube<T: TextureDataType> {
     /// The pixel data for the right image
     pub right_data: Vec<T>,
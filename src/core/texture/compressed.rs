@@ -0,0 +1,927 @@
+use crate::core::texture::*;
+
+///
+/// Possible block compressed formats for the pixels in a texture, see [CPUCompressedTexture].
+/// All formats are decompressed into 8 bit channels.
+///
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CompressedFormat {
+    /// S3TC/DXT1 compressed RGB.
+    BC1,
+    /// S3TC/DXT1 compressed RGB with 1 bit alpha.
+    BC1A,
+    /// S3TC/DXT3 compressed RGBA with explicit alpha.
+    BC2,
+    /// S3TC/DXT5 compressed RGBA with interpolated alpha.
+    BC3,
+    /// RGTC compressed single channel.
+    BC4,
+    /// RGTC compressed two channels.
+    BC5,
+    /// BPTC compressed RGBA.
+    BC7,
+    /// ETC2 compressed RGB.
+    ETC2RGB,
+    /// ETC2 compressed RGB with 1 bit alpha.
+    ETC2RGBA1,
+    /// ETC2 compressed RGB with EAC compressed alpha.
+    ETC2RGBA,
+    /// EAC compressed single channel.
+    EACR,
+    /// EAC compressed two channels.
+    EACRG,
+    /// ASTC compressed RGBA with the given block width and height.
+    ASTC(u8, u8),
+}
+
+impl CompressedFormat {
+    ///
+    /// Returns the width and height in pixels of a compressed block.
+    ///
+    pub fn block_size(&self) -> (u32, u32) {
+        match self {
+            CompressedFormat::ASTC(width, height) => (*width as u32, *height as u32),
+            _ => (4, 4),
+        }
+    }
+
+    ///
+    /// Returns the number of bytes of a compressed block.
+    ///
+    pub fn block_byte_size(&self) -> usize {
+        match self {
+            CompressedFormat::BC1
+            | CompressedFormat::BC1A
+            | CompressedFormat::BC4
+            | CompressedFormat::ETC2RGB
+            | CompressedFormat::ETC2RGBA1
+            | CompressedFormat::EACR => 8,
+            _ => 16,
+        }
+    }
+
+    ///
+    /// Returns the number of bytes needed to store an image with the given width and height in this format.
+    ///
+    pub fn byte_size(&self, width: u32, height: u32) -> usize {
+        let (block_width, block_height) = self.block_size();
+        let blocks_x = (width as usize).div_ceil(block_width.max(1) as usize);
+        let blocks_y = (height as usize).div_ceil(block_height.max(1) as usize);
+        blocks_x
+            .saturating_mul(blocks_y)
+            .saturating_mul(self.block_byte_size())
+    }
+
+    ///
+    /// Returns the uncompressed format of the pixels.
+    ///
+    pub fn format(&self) -> Format {
+        match self {
+            CompressedFormat::BC1 | CompressedFormat::ETC2RGB => Format::RGB,
+            CompressedFormat::BC4 | CompressedFormat::EACR => Format::R,
+            CompressedFormat::BC5 | CompressedFormat::EACRG => Format::RG,
+            _ => Format::RGBA,
+        }
+    }
+
+    pub(in crate::core) fn internal_format(&self) -> ThreeDResult<u32> {
+        Ok(match self {
+            CompressedFormat::BC1 => COMPRESSED_RGB_S3TC_DXT1_EXT,
+            CompressedFormat::BC1A => COMPRESSED_RGBA_S3TC_DXT1_EXT,
+            CompressedFormat::BC2 => COMPRESSED_RGBA_S3TC_DXT3_EXT,
+            CompressedFormat::BC3 => COMPRESSED_RGBA_S3TC_DXT5_EXT,
+            CompressedFormat::BC4 => COMPRESSED_RED_RGTC1,
+            CompressedFormat::BC5 => COMPRESSED_RG_RGTC2,
+            CompressedFormat::BC7 => COMPRESSED_RGBA_BPTC_UNORM,
+            CompressedFormat::ETC2RGB => COMPRESSED_RGB8_ETC2,
+            CompressedFormat::ETC2RGBA1 => COMPRESSED_RGB8_PUNCHTHROUGH_ALPHA1_ETC2,
+            CompressedFormat::ETC2RGBA => COMPRESSED_RGBA8_ETC2_EAC,
+            CompressedFormat::EACR => COMPRESSED_R11_EAC,
+            CompressedFormat::EACRG => COMPRESSED_RG11_EAC,
+            CompressedFormat::ASTC(width, height) => {
+                COMPRESSED_RGBA_ASTC_4X4_KHR + astc_block_size_index(*width, *height)? as u32
+            }
+        })
+    }
+
+    ///
+    /// The extension which is required to use this format or `None` if it is always available.
+    ///
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(in crate::core) fn extension(&self) -> Option<&'static str> {
+        match self {
+            CompressedFormat::BC1
+            | CompressedFormat::BC1A
+            | CompressedFormat::BC2
+            | CompressedFormat::BC3 => Some("GL_EXT_texture_compression_s3tc"),
+            CompressedFormat::BC4 | CompressedFormat::BC5 => None,
+            CompressedFormat::BC7 => Some("GL_ARB_texture_compression_bptc"),
+            CompressedFormat::ETC2RGB
+            | CompressedFormat::ETC2RGBA1
+            | CompressedFormat::ETC2RGBA
+            | CompressedFormat::EACR
+            | CompressedFormat::EACRG => Some("GL_ARB_ES3_compatibility"),
+            CompressedFormat::ASTC(_, _) => Some("GL_KHR_texture_compression_astc_ldr"),
+        }
+    }
+
+    ///
+    /// The extension which is required to use this format or `None` if it is always available.
+    ///
+    #[cfg(target_arch = "wasm32")]
+    pub(in crate::core) fn extension(&self) -> Option<&'static str> {
+        Some(match self {
+            CompressedFormat::BC1
+            | CompressedFormat::BC1A
+            | CompressedFormat::BC2
+            | CompressedFormat::BC3 => "WEBGL_compressed_texture_s3tc",
+            CompressedFormat::BC4 | CompressedFormat::BC5 => "EXT_texture_compression_rgtc",
+            CompressedFormat::BC7 => "EXT_texture_compression_bptc",
+            CompressedFormat::ETC2RGB
+            | CompressedFormat::ETC2RGBA1
+            | CompressedFormat::ETC2RGBA
+            | CompressedFormat::EACR
+            | CompressedFormat::EACRG => "WEBGL_compressed_texture_etc",
+            CompressedFormat::ASTC(_, _) => "WEBGL_compressed_texture_astc",
+        })
+    }
+}
+
+// The compressed formats are extensions on some platforms, so the constants are not always available in the bindings.
+const COMPRESSED_RGB_S3TC_DXT1_EXT: u32 = 0x83F0;
+const COMPRESSED_RGBA_S3TC_DXT1_EXT: u32 = 0x83F1;
+const COMPRESSED_RGBA_S3TC_DXT3_EXT: u32 = 0x83F2;
+const COMPRESSED_RGBA_S3TC_DXT5_EXT: u32 = 0x83F3;
+const COMPRESSED_RED_RGTC1: u32 = 0x8DBB;
+const COMPRESSED_RG_RGTC2: u32 = 0x8DBD;
+const COMPRESSED_RGBA_BPTC_UNORM: u32 = 0x8E8C;
+const COMPRESSED_R11_EAC: u32 = 0x9270;
+const COMPRESSED_RG11_EAC: u32 = 0x9272;
+const COMPRESSED_RGB8_ETC2: u32 = 0x9274;
+const COMPRESSED_RGB8_PUNCHTHROUGH_ALPHA1_ETC2: u32 = 0x9276;
+const COMPRESSED_RGBA8_ETC2_EAC: u32 = 0x9278;
+const COMPRESSED_RGBA_ASTC_4X4_KHR: u32 = 0x93B0;
+
+///
+/// The valid block sizes of the [CompressedFormat::ASTC] format in the order of the OpenGL and Vulkan formats, starting with 4x4.
+///
+pub(crate) const ASTC_BLOCK_SIZES: [(u8, u8); 14] = [
+    (4, 4),
+    (5, 4),
+    (5, 5),
+    (6, 5),
+    (6, 6),
+    (8, 5),
+    (8, 6),
+    (8, 8),
+    (10, 5),
+    (10, 6),
+    (10, 8),
+    (10, 10),
+    (12, 10),
+    (12, 12),
+];
+
+fn astc_block_size_index(width: u8, height: u8) -> ThreeDResult<usize> {
+    Ok(ASTC_BLOCK_SIZES
+        .iter()
+        .position(|size| *size == (width, height))
+        .ok_or(CoreError::InvalidASTCBlockSize(width, height))?)
+}
+
+///
+/// A CPU-side version of a [Texture2D] which contains pre-compressed pixel data for each mip level.
+/// Can be constructed manually or loaded from a .ktx2 or .dds file via [Loader](crate::Loader)
+/// and is used to create a [Texture2D] with [Texture2D::new_compressed].
+///
+#[allow(missing_docs)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CPUCompressedTexture {
+    /// The compressed data for each mip level starting with the full size image.
+    /// The width and height of each mip level is half the size of the previous level, but at least one pixel.
+    pub data: Vec<Vec<u8>>,
+    pub width: u32,
+    pub height: u32,
+    pub format: CompressedFormat,
+    pub min_filter: Interpolation,
+    pub mag_filter: Interpolation,
+    /// Specifies what type of interpolation to use between the two closest mip levels.
+    /// Note, however, that the mip levels are not generated, so this is only used if [data](Self::data) contains more than one mip level.
+    pub mip_map_filter: Option<Interpolation>,
+    pub wrap_s: Wrapping,
+    pub wrap_t: Wrapping,
+}
+
+impl CPUCompressedTexture {
+    ///
+    /// Decompresses the full size image into a [CPUTexture].
+    ///
+    /// # Errors
+    /// Returns an error if the data is too short or if the format cannot be decompressed on the CPU which is the case for [CompressedFormat::BC7] and [CompressedFormat::ASTC].
+    ///
+    pub fn decompress(&self) -> ThreeDResult<CPUTexture<u8>> {
+        check_compressed_data_length(self.width, self.height, self.format, &self.data)?;
+        Ok(CPUTexture {
+            data: decompress(self.width, self.height, self.format, &self.data[0])?,
+            width: self.width,
+            height: self.height,
+            format: self.format.format(),
+            min_filter: self.min_filter,
+            mag_filter: self.mag_filter,
+            mip_map_filter: self.mip_map_filter,
+            wrap_s: self.wrap_s,
+            wrap_t: self.wrap_t,
+        })
+    }
+}
+
+impl Default for CPUCompressedTexture {
+    fn default() -> Self {
+        Self {
+            data: vec![vec![0; 8]],
+            width: 1,
+            height: 1,
+            format: CompressedFormat::BC1,
+            min_filter: Interpolation::Linear,
+            mag_filter: Interpolation::Linear,
+            mip_map_filter: Some(Interpolation::Linear),
+            wrap_s: Wrapping::Repeat,
+            wrap_t: Wrapping::Repeat,
+        }
+    }
+}
+
+impl std::fmt::Debug for CPUCompressedTexture {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CPUCompressedTexture")
+            .field("format", &self.format)
+            .field("mip levels", &self.data.len())
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .field("min_filter", &self.min_filter)
+            .field("mag_filter", &self.mag_filter)
+            .field("mip_map_filter", &self.mip_map_filter)
+            .field("wrap_s", &self.wrap_s)
+            .field("wrap_t", &self.wrap_t)
+            .finish()
+    }
+}
+
+///
+/// A CPU-side version of a [TextureCubeMap] which contains pre-compressed pixel data for each mip level of each of the 6 images.
+/// All 6 images must have the same dimensions, format and number of mip levels.
+/// Can be constructed manually or loaded from a .ktx2 or .dds file via [Loader](crate::Loader)
+/// and is used to create a [TextureCubeMap] with [TextureCubeMap::new_compressed].
+///
+#[allow(missing_docs)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CPUCompressedTextureCube {
+    /// The compressed data for each mip level of the right image
+    pub right_data: Vec<Vec<u8>>,
+    /// The compressed data for each mip level of the left image
+    pub left_data: Vec<Vec<u8>>,
+    /// The compressed data for each mip level of the top image
+    pub top_data: Vec<Vec<u8>>,
+    /// The compressed data for each mip level of the bottom image
+    pub bottom_data: Vec<Vec<u8>>,
+    /// The compressed data for each mip level of the front image
+    pub front_data: Vec<Vec<u8>>,
+    /// The compressed data for each mip level of the back image
+    pub back_data: Vec<Vec<u8>>,
+    /// The width of each of the 6 images
+    pub width: u32,
+    /// The height of each of the 6 images
+    pub height: u32,
+    pub format: CompressedFormat,
+    pub min_filter: Interpolation,
+    pub mag_filter: Interpolation,
+    /// Specifies what type of interpolation to use between the two closest mip levels.
+    /// Note, however, that the mip levels are not generated, so this is only used if the images contain more than one mip level.
+    pub mip_map_filter: Option<Interpolation>,
+    pub wrap_s: Wrapping,
+    pub wrap_t: Wrapping,
+    pub wrap_r: Wrapping,
+}
+
+impl CPUCompressedTextureCube {
+    ///
+    /// Decompresses the full size images into a [CPUTextureCube].
+    ///
+    /// # Errors
+    /// Returns an error if the data is too short or if the format cannot be decompressed on the CPU which is the case for [CompressedFormat::BC7] and [CompressedFormat::ASTC].
+    ///
+    pub fn decompress(&self) -> ThreeDResult<CPUTextureCube<u8>> {
+        let mut images = Vec::with_capacity(6);
+        for data in self.images().iter() {
+            check_compressed_data_length(self.width, self.height, self.format, data)?;
+            images.push(decompress(self.width, self.height, self.format, &data[0])?);
+        }
+        let mut images = images.into_iter();
+        Ok(CPUTextureCube {
+            right_data: images.next().unwrap(),
+            left_data: images.next().unwrap(),
+            top_data: images.next().unwrap(),
+            bottom_data: images.next().unwrap(),
+            front_data: images.next().unwrap(),
+            back_data: images.next().unwrap(),
+            width: self.width,
+            height: self.height,
+            format: self.format.format(),
+            min_filter: self.min_filter,
+            mag_filter: self.mag_filter,
+            mip_map_filter: self.mip_map_filter,
+            wrap_s: self.wrap_s,
+            wrap_t: self.wrap_t,
+            wrap_r: self.wrap_r,
+        })
+    }
+
+    pub(in crate::core) fn images(&self) -> [&Vec<Vec<u8>>; 6] {
+        [
+            &self.right_data,
+            &self.left_data,
+            &self.top_data,
+            &self.bottom_data,
+            &self.front_data,
+            &self.back_data,
+        ]
+    }
+}
+
+impl Default for CPUCompressedTextureCube {
+    fn default() -> Self {
+        Self {
+            right_data: vec![],
+            left_data: vec![],
+            top_data: vec![],
+            bottom_data: vec![],
+            front_data: vec![],
+            back_data: vec![],
+            width: 1,
+            height: 1,
+            format: CompressedFormat::BC1,
+            min_filter: Interpolation::Linear,
+            mag_filter: Interpolation::Linear,
+            mip_map_filter: Some(Interpolation::Linear),
+            wrap_s: Wrapping::Repeat,
+            wrap_t: Wrapping::Repeat,
+            wrap_r: Wrapping::Repeat,
+        }
+    }
+}
+
+impl std::fmt::Debug for CPUCompressedTextureCube {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CPUCompressedTextureCube")
+            .field("format", &self.format)
+            .field("mip levels", &self.right_data.len())
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .field("min_filter", &self.min_filter)
+            .field("mag_filter", &self.mag_filter)
+            .field("mip_map_filter", &self.mip_map_filter)
+            .field("wrap_s", &self.wrap_s)
+            .field("wrap_t", &self.wrap_t)
+            .field("wrap_r", &self.wrap_r)
+            .finish()
+    }
+}
+
+pub(in crate::core) fn check_compressed_data_length(
+    width: u32,
+    height: u32,
+    format: CompressedFormat,
+    data: &[Vec<u8>],
+) -> ThreeDResult<()> {
+    if let CompressedFormat::ASTC(block_width, block_height) = format {
+        astc_block_size_index(block_width, block_height)?;
+    }
+    if data.is_empty() {
+        Err(CoreError::InvalidCompressedTextureLength(
+            0,
+            0,
+            format.byte_size(width, height),
+        ))?;
+    }
+    for (level, level_data) in data.iter().enumerate() {
+        let expected = format.byte_size((width >> level).max(1), (height >> level).max(1));
+        if level_data.len() != expected {
+            Err(CoreError::InvalidCompressedTextureLength(
+                level as u32,
+                level_data.len(),
+                expected,
+            ))?;
+        }
+    }
+    Ok(())
+}
+
+///
+/// Decompresses a single image, the data length must already be checked.
+///
+fn decompress(
+    width: u32,
+    height: u32,
+    format: CompressedFormat,
+    data: &[u8],
+) -> ThreeDResult<Vec<u8>> {
+    let decode_block: fn(&[u8], &mut [[u8; 4]; 16]) = match format {
+        CompressedFormat::BC1 | CompressedFormat::BC1A => {
+            |block, pixels| decode_bc1(block, pixels, false)
+        }
+        CompressedFormat::BC2 => |block, pixels| {
+            decode_bc1(&block[8..], pixels, true);
+            for i in 0..16 {
+                pixels[i][3] = ((block[i / 2] >> (4 * (i % 2))) & 0xF) * 17;
+            }
+        },
+        CompressedFormat::BC3 => |block, pixels| {
+            decode_bc1(&block[8..], pixels, true);
+            decode_bc4(block, pixels, 3);
+        },
+        CompressedFormat::BC4 => |block, pixels| decode_bc4(block, pixels, 0),
+        CompressedFormat::BC5 => |block, pixels| {
+            decode_bc4(block, pixels, 0);
+            decode_bc4(&block[8..], pixels, 1);
+        },
+        CompressedFormat::ETC2RGB => |block, pixels| decode_etc2(block, pixels, false),
+        CompressedFormat::ETC2RGBA1 => |block, pixels| decode_etc2(block, pixels, true),
+        CompressedFormat::ETC2RGBA => |block, pixels| {
+            decode_etc2(&block[8..], pixels, false);
+            decode_eac(block, pixels, 3, false);
+        },
+        CompressedFormat::EACR => |block, pixels| decode_eac(block, pixels, 0, true),
+        CompressedFormat::EACRG => |block, pixels| {
+            decode_eac(block, pixels, 0, true);
+            decode_eac(&block[8..], pixels, 1, true);
+        },
+        CompressedFormat::BC7 | CompressedFormat::ASTC(_, _) => {
+            Err(CoreError::UnsupportedCompressedFormat(format))?
+        }
+    };
+
+    let channels = format.format().color_channel_count() as usize;
+    let block_byte_size = format.block_byte_size();
+    let blocks_x = (width as usize).div_ceil(4);
+    let mut result = vec![0u8; width as usize * height as usize * channels];
+    let mut pixels = [[0u8, 0, 0, 255]; 16];
+    for (index, block) in data.chunks_exact(block_byte_size).enumerate() {
+        decode_block(block, &mut pixels);
+        let block_x = (index % blocks_x) * 4;
+        let block_y = (index / blocks_x) * 4;
+        for y in 0..4usize.min(height as usize - block_y) {
+            for x in 0..4usize.min(width as usize - block_x) {
+                let offset = ((block_y + y) * width as usize + block_x + x) * channels;
+                result[offset..offset + channels].copy_from_slice(&pixels[y * 4 + x][..channels]);
+            }
+        }
+    }
+    Ok(result)
+}
+
+fn decode_bc1(block: &[u8], pixels: &mut [[u8; 4]; 16], always_opaque: bool) {
+    let c0 = u16::from_le_bytes([block[0], block[1]]);
+    let c1 = u16::from_le_bytes([block[2], block[3]]);
+    let rgb = |c: u16| {
+        let r = (c >> 11) as u8 & 0x1F;
+        let g = (c >> 5) as u8 & 0x3F;
+        let b = c as u8 & 0x1F;
+        [
+            (r << 3) | (r >> 2),
+            (g << 2) | (g >> 4),
+            (b << 3) | (b >> 2),
+        ]
+    };
+    let (rgb0, rgb1) = (rgb(c0), rgb(c1));
+    let mix = |w0: u16, w1: u16| {
+        let mut color = [0u8, 0, 0, 255];
+        for i in 0..3 {
+            color[i] =
+                ((w0 * rgb0[i] as u16 + w1 * rgb1[i] as u16 + (w0 + w1) / 2) / (w0 + w1)) as u8;
+        }
+        color
+    };
+    let colors = if c0 > c1 || always_opaque {
+        [mix(1, 0), mix(0, 1), mix(2, 1), mix(1, 2)]
+    } else {
+        [mix(1, 0), mix(0, 1), mix(1, 1), [0, 0, 0, 0]]
+    };
+    let indices = u32::from_le_bytes([block[4], block[5], block[6], block[7]]);
+    for (i, pixel) in pixels.iter_mut().enumerate() {
+        *pixel = colors[(indices >> (2 * i)) as usize & 3];
+    }
+}
+
+fn decode_bc4(block: &[u8], pixels: &mut [[u8; 4]; 16], channel: usize) {
+    let a0 = block[0] as u32;
+    let a1 = block[1] as u32;
+    let mut values = [a0, a1, 0, 0, 0, 0, 0, 255];
+    if a0 > a1 {
+        for i in 1..7 {
+            values[i + 1] = ((7 - i as u32) * a0 + i as u32 * a1) / 7;
+        }
+    } else {
+        for i in 1..5 {
+            values[i + 1] = ((5 - i as u32) * a0 + i as u32 * a1) / 5;
+        }
+    }
+    let mut indices = 0u64;
+    for i in 0..6 {
+        indices |= (block[2 + i] as u64) << (8 * i);
+    }
+    for (i, pixel) in pixels.iter_mut().enumerate() {
+        pixel[channel] = values[(indices >> (3 * i)) as usize & 7] as u8;
+    }
+}
+
+const ETC_MODIFIERS: [[i32; 2]; 8] = [
+    [2, 8],
+    [5, 17],
+    [9, 29],
+    [13, 42],
+    [18, 60],
+    [24, 80],
+    [33, 106],
+    [47, 183],
+];
+
+const ETC_DISTANCES: [i32; 8] = [3, 6, 11, 16, 23, 32, 41, 64];
+
+fn decode_etc2(block: &[u8], pixels: &mut [[u8; 4]; 16], punchthrough: bool) {
+    let bits = u64::from_be_bytes([
+        block[0], block[1], block[2], block[3], block[4], block[5], block[6], block[7],
+    ]);
+    let field = |start: u32, count: u32| ((bits >> start) & ((1 << count) - 1)) as i32;
+    let extend4 = |v: i32| (v << 4) | v;
+    let extend5 = |v: i32| (v << 3) | (v >> 2);
+    let extend6 = |v: i32| (v << 2) | (v >> 4);
+    let extend7 = |v: i32| (v << 1) | (v >> 6);
+    let clamp = |v: i32| v.clamp(0, 255) as u8;
+    let color = |c: [i32; 3]| [clamp(c[0]), clamp(c[1]), clamp(c[2]), 255];
+    let offset = |c: [i32; 3], d: i32| color([c[0] + d, c[1] + d, c[2] + d]);
+    // The indices are stored column by column with the most significant bits in the upper half
+    let index = |x: usize, y: usize| {
+        let i = x * 4 + y;
+        ((field(i as u32 + 16, 1) << 1) | field(i as u32, 1)) as usize
+    };
+    let flag = field(33, 1) == 1;
+    let opaque = !punchthrough || flag;
+    let differential = punchthrough || flag;
+
+    let r = field(59, 5) + ((field(56, 3) << 29) >> 29);
+    let g = field(51, 5) + ((field(48, 3) << 29) >> 29);
+    let b = field(43, 5) + ((field(40, 3) << 29) >> 29);
+    if differential && !(0..32).contains(&r) {
+        // T mode
+        let c0 = [
+            extend4((field(59, 2) << 2) | field(56, 2)),
+            extend4(field(52, 4)),
+            extend4(field(48, 4)),
+        ];
+        let c1 = [
+            extend4(field(44, 4)),
+            extend4(field(40, 4)),
+            extend4(field(36, 4)),
+        ];
+        let d = ETC_DISTANCES[((field(34, 2) << 1) | field(32, 1)) as usize];
+        let paint = [color(c0), offset(c1, d), color(c1), offset(c1, -d)];
+        write_paint_colors(pixels, &paint, opaque, index);
+    } else if differential && !(0..32).contains(&g) {
+        // H mode
+        let c0 = [
+            field(59, 4),
+            (field(56, 3) << 1) | field(52, 1),
+            (field(51, 1) << 3) | field(47, 3),
+        ];
+        let c1 = [field(43, 4), field(39, 4), field(35, 4)];
+        let order = ((c0[0] << 8) | (c0[1] << 4) | c0[2]) >= ((c1[0] << 8) | (c1[1] << 4) | c1[2]);
+        let d = ETC_DISTANCES[((field(34, 1) << 2) | (field(32, 1) << 1) | order as i32) as usize];
+        let c0 = [extend4(c0[0]), extend4(c0[1]), extend4(c0[2])];
+        let c1 = [extend4(c1[0]), extend4(c1[1]), extend4(c1[2])];
+        let paint = [offset(c0, d), offset(c0, -d), offset(c1, d), offset(c1, -d)];
+        write_paint_colors(pixels, &paint, opaque, index);
+    } else if differential && !(0..32).contains(&b) {
+        // Planar mode
+        let o = [
+            extend6(field(57, 6)),
+            extend7((field(56, 1) << 6) | field(49, 6)),
+            extend6((field(48, 1) << 5) | (field(43, 2) << 3) | field(39, 3)),
+        ];
+        let h = [
+            extend6((field(34, 5) << 1) | field(32, 1)),
+            extend7(field(25, 7)),
+            extend6(field(19, 6)),
+        ];
+        let v = [
+            extend6(field(13, 6)),
+            extend7(field(6, 7)),
+            extend6(field(0, 6)),
+        ];
+        for y in 0..4 {
+            for x in 0..4 {
+                let mut c = [0; 3];
+                for i in 0..3 {
+                    c[i] = (x * (h[i] - o[i]) + y * (v[i] - o[i]) + 4 * o[i] + 2) >> 2;
+                }
+                pixels[y as usize * 4 + x as usize] = color(c);
+            }
+        }
+    } else {
+        // Individual or differential mode
+        let (c0, c1) = if differential {
+            (
+                [
+                    extend5(field(59, 5)),
+                    extend5(field(51, 5)),
+                    extend5(field(43, 5)),
+                ],
+                [extend5(r), extend5(g), extend5(b)],
+            )
+        } else {
+            (
+                [
+                    extend4(field(60, 4)),
+                    extend4(field(52, 4)),
+                    extend4(field(44, 4)),
+                ],
+                [
+                    extend4(field(56, 4)),
+                    extend4(field(48, 4)),
+                    extend4(field(40, 4)),
+                ],
+            )
+        };
+        let tables = [field(37, 3) as usize, field(34, 3) as usize];
+        let flip = field(32, 1) == 1;
+        for y in 0..4 {
+            for x in 0..4 {
+                let sub_block = if flip { y >= 2 } else { x >= 2 } as usize;
+                let base = if sub_block == 0 { c0 } else { c1 };
+                let [small, large] = ETC_MODIFIERS[tables[sub_block]];
+                pixels[y * 4 + x] = match index(x, y) {
+                    0 if opaque => offset(base, small),
+                    0 => offset(base, 0),
+                    1 => offset(base, large),
+                    2 if opaque => offset(base, -small),
+                    2 => [0, 0, 0, 0],
+                    _ => offset(base, -large),
+                };
+            }
+        }
+    }
+}
+
+fn write_paint_colors(
+    pixels: &mut [[u8; 4]; 16],
+    paint: &[[u8; 4]; 4],
+    opaque: bool,
+    index: impl Fn(usize, usize) -> usize,
+) {
+    for y in 0..4 {
+        for x in 0..4 {
+            let i = index(x, y);
+            pixels[y * 4 + x] = if !opaque && i == 2 {
+                [0, 0, 0, 0]
+            } else {
+                paint[i]
+            };
+        }
+    }
+}
+
+const EAC_MODIFIERS: [[i32; 8]; 16] = [
+    [-3, -6, -9, -15, 2, 5, 8, 14],
+    [-3, -7, -10, -13, 2, 6, 9, 12],
+    [-2, -5, -8, -13, 1, 4, 7, 12],
+    [-2, -4, -6, -13, 1, 3, 5, 12],
+    [-3, -6, -8, -12, 2, 5, 7, 11],
+    [-3, -7, -9, -11, 2, 6, 8, 10],
+    [-4, -7, -8, -11, 3, 6, 7, 10],
+    [-3, -5, -8, -11, 2, 4, 7, 10],
+    [-2, -6, -8, -10, 1, 5, 7, 9],
+    [-2, -5, -8, -10, 1, 4, 7, 9],
+    [-2, -4, -8, -10, 1, 3, 7, 9],
+    [-2, -5, -7, -10, 1, 4, 6, 9],
+    [-3, -4, -7, -10, 2, 3, 6, 9],
+    [-1, -2, -3, -10, 0, 1, 2, 9],
+    [-4, -6, -8, -9, 3, 5, 7, 8],
+    [-3, -5, -7, -9, 2, 4, 6, 8],
+];
+
+fn decode_eac(block: &[u8], pixels: &mut [[u8; 4]; 16], channel: usize, eleven_bits: bool) {
+    let base = block[0] as i32;
+    let multiplier = (block[1] >> 4) as i32;
+    let modifiers = EAC_MODIFIERS[(block[1] & 0xF) as usize];
+    let mut indices = 0u64;
+    for i in 0..6 {
+        indices = (indices << 8) | block[2 + i] as u64;
+    }
+    for x in 0..4 {
+        for y in 0..4 {
+            // The indices are stored column by column starting with the most significant bits
+            let modifier = modifiers[(indices >> (45 - 3 * (x * 4 + y))) as usize & 7];
+            pixels[y * 4 + x][channel] = if eleven_bits {
+                let value = if multiplier == 0 {
+                    base * 8 + 4 + modifier
+                } else {
+                    base * 8 + 4 + modifier * multiplier * 8
+                };
+                (value.clamp(0, 2047) * 255 / 2047) as u8
+            } else {
+                (base + modifier * multiplier).clamp(0, 255) as u8
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn decompress_block(format: CompressedFormat, block: &[u8]) -> Vec<u8> {
+        CPUCompressedTexture {
+            data: vec![block.to_vec()],
+            width: 4,
+            height: 4,
+            format,
+            ..Default::default()
+        }
+        .decompress()
+        .unwrap()
+        .data
+    }
+
+    // The ETC2 block in individual mode with the colors 0x88 and 0x44 in the left and right half,
+    // where the columns are offset by -8, -2, +8 and +2 respectively
+    const ETC2_BLOCK: [u8; 8] = [0x84, 0x84, 0x84, 0x00, 0x00, 0xFF, 0x0F, 0x0F];
+    const ETC2_COLUMNS: [u8; 4] = [128, 134, 76, 70];
+
+    // The EAC block with base value 128 and multiplier 1 where the two left columns are offset by +2 and the two right columns by -3
+    const EAC_BLOCK: [u8; 8] = [128, 0x10, 0x92, 0x49, 0x24, 0x00, 0x00, 0x00];
+    const EAC_COLUMNS: [u8; 4] = [130, 130, 125, 125];
+
+    // The BC4 block with the end points 255 and 0 where the index of each pixel is the pixel index modulo 8
+    const BC4_BLOCK: [u8; 8] = [255, 0, 0x88, 0xC6, 0xFA, 0x88, 0xC6, 0xFA];
+    const BC4_VALUES: [u8; 8] = [255, 0, 218, 182, 145, 109, 72, 36];
+
+    // The BC1 block with the end points red and blue where the index of each pixel is the column index
+    const BC1_BLOCK: [u8; 8] = [0x00, 0xF8, 0x1F, 0x00, 0xE4, 0xE4, 0xE4, 0xE4];
+
+    #[test]
+    pub fn decompress_bc1() {
+        let data = decompress_block(CompressedFormat::BC1, &BC1_BLOCK);
+        let colors = [[255, 0, 0], [0, 0, 255], [170, 0, 85], [85, 0, 170]];
+        for (i, pixel) in data.chunks(3).enumerate() {
+            assert_eq!(pixel, &colors[i % 4]);
+        }
+
+        // The end points in reverse order enables the transparent color
+        let block = [0x1F, 0x00, 0x00, 0xF8, 0xE4, 0xE4, 0xE4, 0xE4];
+        let data = decompress_block(CompressedFormat::BC1A, &block);
+        let colors = [
+            [0, 0, 255, 255],
+            [255, 0, 0, 255],
+            [128, 0, 128, 255],
+            [0, 0, 0, 0],
+        ];
+        for (i, pixel) in data.chunks(4).enumerate() {
+            assert_eq!(pixel, &colors[i % 4]);
+        }
+    }
+
+    #[test]
+    pub fn decompress_bc2() {
+        let mut block = vec![0x10, 0x32, 0x54, 0x76, 0x98, 0xBA, 0xDC, 0xFE];
+        block.extend_from_slice(&BC1_BLOCK);
+        let data = decompress_block(CompressedFormat::BC2, &block);
+        let colors = [[255, 0, 0], [0, 0, 255], [170, 0, 85], [85, 0, 170]];
+        for (i, pixel) in data.chunks(4).enumerate() {
+            assert_eq!(&pixel[..3], &colors[i % 4]);
+            assert_eq!(pixel[3] as usize, i * 17);
+        }
+    }
+
+    #[test]
+    pub fn decompress_bc3() {
+        let mut block = BC4_BLOCK.to_vec();
+        block.extend_from_slice(&BC1_BLOCK);
+        let data = decompress_block(CompressedFormat::BC3, &block);
+        let colors = [[255, 0, 0], [0, 0, 255], [170, 0, 85], [85, 0, 170]];
+        for (i, pixel) in data.chunks(4).enumerate() {
+            assert_eq!(&pixel[..3], &colors[i % 4]);
+            assert_eq!(pixel[3], BC4_VALUES[i % 8]);
+        }
+    }
+
+    #[test]
+    pub fn decompress_bc4_and_bc5() {
+        let data = decompress_block(CompressedFormat::BC4, &BC4_BLOCK);
+        for (i, value) in data.iter().enumerate() {
+            assert_eq!(*value, BC4_VALUES[i % 8]);
+        }
+
+        // The end points in increasing order gives six interpolated values and the values 0 and 255
+        let mut block = vec![0, 100, 0x88, 0xC6, 0xFA, 0x88, 0xC6, 0xFA];
+        block.extend_from_slice(&BC4_BLOCK);
+        let data = decompress_block(CompressedFormat::BC5, &block);
+        let values = [0, 100, 20, 40, 60, 80, 0, 255];
+        for (i, pixel) in data.chunks(2).enumerate() {
+            assert_eq!(pixel, &[values[i % 8], BC4_VALUES[i % 8]]);
+        }
+    }
+
+    #[test]
+    pub fn decompress_etc2() {
+        let data = decompress_block(CompressedFormat::ETC2RGB, &ETC2_BLOCK);
+        for (i, pixel) in data.chunks(3).enumerate() {
+            let value = ETC2_COLUMNS[i % 4];
+            assert_eq!(pixel, &[value, value, value]);
+        }
+
+        let mut block = EAC_BLOCK.to_vec();
+        block.extend_from_slice(&ETC2_BLOCK);
+        let data = decompress_block(CompressedFormat::ETC2RGBA, &block);
+        for (i, pixel) in data.chunks(4).enumerate() {
+            let value = ETC2_COLUMNS[i % 4];
+            assert_eq!(pixel, &[value, value, value, EAC_COLUMNS[i % 4]]);
+        }
+    }
+
+    #[test]
+    pub fn decompress_eac() {
+        let data = decompress_block(CompressedFormat::EACR, &EAC_BLOCK);
+        for (i, value) in data.iter().enumerate() {
+            assert_eq!(*value, EAC_COLUMNS[i % 4]);
+        }
+
+        let mut block = EAC_BLOCK.to_vec();
+        // The largest modifier is added to the largest base value, so the result is clamped
+        block.extend_from_slice(&[255, 0x10, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]);
+        let data = decompress_block(CompressedFormat::EACRG, &block);
+        for (i, pixel) in data.chunks(2).enumerate() {
+            assert_eq!(pixel, &[EAC_COLUMNS[i % 4], 255]);
+        }
+    }
+
+    #[test]
+    pub fn decompress_partial_blocks() {
+        // A 6x2 image consists of two blocks where only the upper left part of each block is used
+        let mut data = BC1_BLOCK.to_vec();
+        data.extend_from_slice(&[0x00, 0xF8, 0x00, 0xF8, 0, 0, 0, 0]);
+        let texture = CPUCompressedTexture {
+            data: vec![data],
+            width: 6,
+            height: 2,
+            format: CompressedFormat::BC1,
+            ..Default::default()
+        }
+        .decompress()
+        .unwrap();
+        let colors = [[255, 0, 0], [0, 0, 255], [170, 0, 85], [85, 0, 170]];
+        for (i, pixel) in texture.data.chunks(3).enumerate() {
+            let x = i % 6;
+            assert_eq!(pixel, if x < 4 { &colors[x] } else { &colors[0] });
+        }
+    }
+
+    #[test]
+    pub fn decompress_invalid() {
+        let texture = |format, data: Vec<u8>| CPUCompressedTexture {
+            data: vec![data],
+            width: 4,
+            height: 4,
+            format,
+            ..Default::default()
+        };
+        assert!(texture(CompressedFormat::BC1, vec![0; 7])
+            .decompress()
+            .is_err());
+        assert!(texture(CompressedFormat::BC3, vec![0; 8])
+            .decompress()
+            .is_err());
+        assert!(texture(CompressedFormat::BC7, vec![0; 16])
+            .decompress()
+            .is_err());
+        assert!(texture(CompressedFormat::ASTC(4, 4), vec![0; 16])
+            .decompress()
+            .is_err());
+    }
+
+    #[test]
+    pub fn astc_block_sizes() {
+        assert_eq!(
+            CompressedFormat::ASTC(4, 4).internal_format().unwrap(),
+            COMPRESSED_RGBA_ASTC_4X4_KHR
+        );
+        assert_eq!(
+            CompressedFormat::ASTC(12, 12).internal_format().unwrap(),
+            0x93BD
+        );
+        for &(width, height) in [(0, 0), (7, 7), (4, 5), (12, 8), (16, 16)].iter() {
+            let format = CompressedFormat::ASTC(width, height);
+            assert!(format.internal_format().is_err());
+            assert!(check_compressed_data_length(4, 4, format, &[vec![0; 16]]).is_err());
+        }
+    }
+}
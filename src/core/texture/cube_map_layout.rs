@@ -0,0 +1,348 @@
+use crate::core::texture::*;
+
+///
+/// The layouts in which the 6 sides of a cube map can be packed into a single image.
+/// All layouts use the OpenGL cube map convention, so the sides are oriented the same way as
+/// when loaded as 6 separate images with [Loaded::cube_image](crate::Loaded::cube_image).
+///
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum CubeMapLayout {
+    ///
+    /// A 4x3 cross where the middle row contains the left, front, right and back sides
+    /// and the top and bottom sides are above and below the front side.
+    ///
+    HorizontalCross,
+    ///
+    /// A 3x4 cross where the middle column contains the top, front, bottom and back sides
+    /// and the left and right sides are on each side of the front side.
+    /// The back side is rotated 180 degrees.
+    ///
+    VerticalCross,
+    ///
+    /// A 6x1 strip with the sides in the order right, left, top, bottom, front, back.
+    ///
+    HorizontalStrip,
+    ///
+    /// A 1x6 strip with the sides in the order right, left, top, bottom, front, back.
+    ///
+    VerticalStrip,
+}
+
+impl CubeMapLayout {
+    ///
+    /// Returns the layout which matches the aspect ratio of an image with the given size
+    /// or `None` if the size does not match any of the layouts.
+    ///
+    pub fn detect(width: u32, height: u32) -> Option<Self> {
+        let (width, height) = (width as u64, height as u64);
+        if width == 0 || height == 0 {
+            None
+        } else if width * 3 == height * 4 {
+            Some(CubeMapLayout::HorizontalCross)
+        } else if width * 4 == height * 3 {
+            Some(CubeMapLayout::VerticalCross)
+        } else if width == height * 6 {
+            Some(CubeMapLayout::HorizontalStrip)
+        } else if width * 6 == height {
+            Some(CubeMapLayout::VerticalStrip)
+        } else {
+            None
+        }
+    }
+
+    ///
+    /// Returns the number of columns and rows of sides in this layout.
+    ///
+    pub fn grid_size(&self) -> (u32, u32) {
+        match self {
+            CubeMapLayout::HorizontalCross => (4, 3),
+            CubeMapLayout::VerticalCross => (3, 4),
+            CubeMapLayout::HorizontalStrip => (6, 1),
+            CubeMapLayout::VerticalStrip => (1, 6),
+        }
+    }
+
+    ///
+    /// Returns the column and row of the given side and whether it is rotated 180 degrees.
+    ///
+    fn cell(&self, side: CubeMapSide) -> (u32, u32, bool) {
+        match self {
+            CubeMapLayout::HorizontalCross | CubeMapLayout::VerticalCross => match side {
+                CubeMapSide::Right => (2, 1, false),
+                CubeMapSide::Left => (0, 1, false),
+                CubeMapSide::Top => (1, 0, false),
+                CubeMapSide::Bottom => (1, 2, false),
+                CubeMapSide::Front => (1, 1, false),
+                CubeMapSide::Back if *self == CubeMapLayout::HorizontalCross => (3, 1, false),
+                CubeMapSide::Back => (1, 3, true),
+            },
+            CubeMapLayout::HorizontalStrip => (side_index(side), 0, false),
+            CubeMapLayout::VerticalStrip => (0, side_index(side), false),
+        }
+    }
+}
+
+fn side_index(side: CubeMapSide) -> u32 {
+    match side {
+        CubeMapSide::Right => 0,
+        CubeMapSide::Left => 1,
+        CubeMapSide::Top => 2,
+        CubeMapSide::Bottom => 3,
+        CubeMapSide::Front => 4,
+        CubeMapSide::Back => 5,
+    }
+}
+
+impl<T: TextureDataType> CPUTextureCube<T> {
+    ///
+    /// Slices a single image containing all 6 sides of a cube map in the given layout into a [CPUTextureCube].
+    /// Use [CubeMapLayout::detect] to find the layout from the size of the image.
+    /// The sampling and wrapping parameters are copied from the given texture.
+    ///
+    pub fn from_layout(cpu_texture: &CPUTexture<T>, layout: CubeMapLayout) -> ThreeDResult<Self> {
+        let (columns, rows) = layout.grid_size();
+        let size = cpu_texture.width / columns;
+        if size == 0 || cpu_texture.width != size * columns || cpu_texture.height != size * rows {
+            Err(CoreError::InvalidCubeMapLayout(
+                cpu_texture.width,
+                cpu_texture.height,
+                layout,
+            ))?;
+        }
+        check_data_length(
+            cpu_texture.width,
+            cpu_texture.height,
+            1,
+            cpu_texture.format,
+            cpu_texture.data.len(),
+        )?;
+
+        let channels = cpu_texture.format.color_channel_count() as usize;
+        let side_data = |side: CubeMapSide| {
+            let (column, row, rotated) = layout.cell(side);
+            let size = size as usize;
+            let mut data = Vec::with_capacity(size * size * channels);
+            for y in 0..size {
+                for x in 0..size {
+                    let (x, y) = if rotated {
+                        (size - 1 - x, size - 1 - y)
+                    } else {
+                        (x, y)
+                    };
+                    let index = ((row as usize * size + y) * cpu_texture.width as usize
+                        + column as usize * size
+                        + x)
+                        * channels;
+                    data.extend_from_slice(&cpu_texture.data[index..index + channels]);
+                }
+            }
+            data
+        };
+        Ok(Self {
+            right_data: side_data(CubeMapSide::Right),
+            left_data: side_data(CubeMapSide::Left),
+            top_data: side_data(CubeMapSide::Top),
+            bottom_data: side_data(CubeMapSide::Bottom),
+            front_data: side_data(CubeMapSide::Front),
+            back_data: side_data(CubeMapSide::Back),
+            width: size,
+            height: size,
+            format: cpu_texture.format,
+            min_filter: cpu_texture.min_filter,
+            mag_filter: cpu_texture.mag_filter,
+            mip_map_filter: cpu_texture.mip_map_filter,
+            wrap_s: cpu_texture.wrap_s,
+            wrap_t: cpu_texture.wrap_t,
+            wrap_r: cpu_texture.wrap_s,
+        })
+    }
+
+    ///
+    /// Packs the 6 sides of this cube map into a single image in the given layout, which is for example useful for saving the cube map to a file.
+    /// The unused parts of a cross layout are filled with zeros.
+    /// The sides must be square.
+    ///
+    pub fn to_layout(&self, layout: CubeMapLayout) -> ThreeDResult<CPUTexture<T>> {
+        if self.width != self.height {
+            Err(CoreError::InvalidCubeMapLayout(
+                self.width,
+                self.height,
+                layout,
+            ))?;
+        }
+        let (columns, rows) = layout.grid_size();
+        let size = self.width as usize;
+        let channels = self.format.color_channel_count() as usize;
+        let width = size * columns as usize;
+        let mut data = vec![T::default(); width * size * rows as usize * channels];
+        for side in CubeMapSide::iter() {
+            let side_data = match side {
+                CubeMapSide::Right => &self.right_data,
+                CubeMapSide::Left => &self.left_data,
+                CubeMapSide::Top => &self.top_data,
+                CubeMapSide::Bottom => &self.bottom_data,
+                CubeMapSide::Front => &self.front_data,
+                CubeMapSide::Back => &self.back_data,
+            };
+            check_data_length(self.width, self.height, 1, self.format, side_data.len())?;
+            let (column, row, rotated) = layout.cell(side);
+            for y in 0..size {
+                for x in 0..size {
+                    let source = if rotated {
+                        ((size - 1 - y) * size + size - 1 - x) * channels
+                    } else {
+                        (y * size + x) * channels
+                    };
+                    let target =
+                        ((row as usize * size + y) * width + column as usize * size + x) * channels;
+                    data[target..target + channels]
+                        .copy_from_slice(&side_data[source..source + channels]);
+                }
+            }
+        }
+        Ok(CPUTexture {
+            data,
+            width: width as u32,
+            height: (size * rows as usize) as u32,
+            format: self.format,
+            min_filter: self.min_filter,
+            mag_filter: self.mag_filter,
+            mip_map_filter: self.mip_map_filter,
+            wrap_s: self.wrap_s,
+            wrap_t: self.wrap_t,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SIZE: u32 = 4;
+
+    fn color(side: CubeMapSide) -> [u8; 3] {
+        let i = side_index(side) as u8 + 1;
+        [i * 10, i * 20, i * 30]
+    }
+
+    ///
+    /// Returns an image in the given layout where each side has a solid color, except for a marker in the top left corner of each side in the image.
+    ///
+    fn packed(layout: CubeMapLayout) -> CPUTexture<u8> {
+        let (columns, rows) = layout.grid_size();
+        let width = SIZE * columns;
+        let mut data = vec![0u8; (width * SIZE * rows * 3) as usize];
+        for side in CubeMapSide::iter() {
+            let (column, row, _) = layout.cell(side);
+            for y in 0..SIZE {
+                for x in 0..SIZE {
+                    let index = (((row * SIZE + y) * width + column * SIZE + x) * 3) as usize;
+                    let color = if x == 0 && y == 0 {
+                        [255, 255, 255]
+                    } else {
+                        color(side)
+                    };
+                    data[index..index + 3].copy_from_slice(&color);
+                }
+            }
+        }
+        CPUTexture {
+            data,
+            width,
+            height: SIZE * rows,
+            format: Format::RGB,
+            ..Default::default()
+        }
+    }
+
+    fn side_data(cube: &CPUTextureCube<u8>, side: CubeMapSide) -> &[u8] {
+        match side {
+            CubeMapSide::Right => &cube.right_data,
+            CubeMapSide::Left => &cube.left_data,
+            CubeMapSide::Top => &cube.top_data,
+            CubeMapSide::Bottom => &cube.bottom_data,
+            CubeMapSide::Front => &cube.front_data,
+            CubeMapSide::Back => &cube.back_data,
+        }
+    }
+
+    #[test]
+    pub fn detect() {
+        assert_eq!(
+            CubeMapLayout::detect(64, 48),
+            Some(CubeMapLayout::HorizontalCross)
+        );
+        assert_eq!(
+            CubeMapLayout::detect(48, 64),
+            Some(CubeMapLayout::VerticalCross)
+        );
+        assert_eq!(
+            CubeMapLayout::detect(96, 16),
+            Some(CubeMapLayout::HorizontalStrip)
+        );
+        assert_eq!(
+            CubeMapLayout::detect(16, 96),
+            Some(CubeMapLayout::VerticalStrip)
+        );
+        assert_eq!(CubeMapLayout::detect(16, 16), None);
+        assert_eq!(CubeMapLayout::detect(65, 48), None);
+        assert_eq!(CubeMapLayout::detect(0, 0), None);
+        assert_eq!(CubeMapLayout::detect(u32::MAX, u32::MAX / 4 * 3), None);
+    }
+
+    #[test]
+    pub fn slice_and_pack() {
+        for &layout in [
+            CubeMapLayout::HorizontalCross,
+            CubeMapLayout::VerticalCross,
+            CubeMapLayout::HorizontalStrip,
+            CubeMapLayout::VerticalStrip,
+        ]
+        .iter()
+        {
+            let image = packed(layout);
+            assert_eq!(
+                CubeMapLayout::detect(image.width, image.height),
+                Some(layout)
+            );
+            let cube = CPUTextureCube::from_layout(&image, layout).unwrap();
+            assert_eq!((cube.width, cube.height), (SIZE, SIZE));
+            for side in CubeMapSide::iter() {
+                let data = side_data(&cube, side);
+                assert_eq!(data.len(), (SIZE * SIZE * 3) as usize);
+                // Only the back side of the vertical cross is rotated, which moves the marker to the bottom right corner
+                let marker = if layout == CubeMapLayout::VerticalCross && side == CubeMapSide::Back
+                {
+                    SIZE * SIZE - 1
+                } else {
+                    0
+                };
+                for (i, pixel) in data.chunks(3).enumerate() {
+                    let expected = if i as u32 == marker {
+                        [255, 255, 255]
+                    } else {
+                        color(side)
+                    };
+                    assert_eq!(pixel, expected, "{:?} {:?} {}", layout, side, i);
+                }
+            }
+            assert_eq!(cube.to_layout(layout).unwrap().data, image.data);
+        }
+    }
+
+    #[test]
+    pub fn invalid_layout() {
+        let mut image = packed(CubeMapLayout::HorizontalCross);
+        assert!(CPUTextureCube::from_layout(&image, CubeMapLayout::VerticalCross).is_err());
+        image.data.pop();
+        assert!(CPUTextureCube::from_layout(&image, CubeMapLayout::HorizontalCross).is_err());
+
+        let mut cube = CPUTextureCube::from_layout(
+            &packed(CubeMapLayout::HorizontalStrip),
+            CubeMapLayout::HorizontalStrip,
+        )
+        .unwrap();
+        cube.back_data.pop();
+        assert!(cube.to_layout(CubeMapLayout::HorizontalStrip).is_err());
+    }
+}
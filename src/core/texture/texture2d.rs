@@ -211,6 +211,83 @@ impl<T: TextureDataType> Texture2D<T> {
     }
 }
 
+impl Texture2D<u8> {
+    ///
+    /// Constructs a new texture from the given pre-compressed data.
+    /// If the graphics context supports the compression format (see [Context::supports_compressed_format]), the compressed mip levels are uploaded directly,
+    /// otherwise the full size image is decompressed on the CPU and uploaded as a [Texture2D::new] would.
+    ///
+    /// **Note:** A compressed texture cannot be filled or written to after construction.
+    ///
+    /// # Errors
+    /// Returns an error if the length of the data for any mip level does not correspond to the width, height and format
+    /// or if the format is not supported by the graphics context and cannot be decompressed on the CPU.
+    /// An error is also returned if the format is [CompressedFormat::ASTC] with a block size which is not defined by the ASTC specification.
+    ///
+    pub fn new_compressed(
+        context: &Context,
+        cpu_texture: &CPUCompressedTexture,
+    ) -> ThreeDResult<Self> {
+        if !context.supports_compressed_format(cpu_texture.format) {
+            return Self::new(context, &cpu_texture.decompress()?);
+        }
+        check_compressed_data_length(
+            cpu_texture.width,
+            cpu_texture.height,
+            cpu_texture.format,
+            &cpu_texture.data,
+        )?;
+        let id = generate(context)?;
+        let number_of_mip_maps = cpu_texture.data.len() as u32;
+        set_parameters(
+            context,
+            &id,
+            consts::TEXTURE_2D,
+            cpu_texture.min_filter,
+            cpu_texture.mag_filter,
+            if number_of_mip_maps == 1 {
+                None
+            } else {
+                cpu_texture.mip_map_filter
+            },
+            cpu_texture.wrap_s,
+            cpu_texture.wrap_t,
+            None,
+        );
+        let internal_format = cpu_texture.format.internal_format()?;
+        context.tex_storage_2d(
+            consts::TEXTURE_2D,
+            number_of_mip_maps,
+            internal_format,
+            cpu_texture.width,
+            cpu_texture.height,
+        );
+        for (level, data) in cpu_texture.data.iter().enumerate() {
+            context.compressed_tex_sub_image_2d(
+                consts::TEXTURE_2D,
+                level as u32,
+                0,
+                0,
+                (cpu_texture.width >> level).max(1),
+                (cpu_texture.height >> level).max(1),
+                internal_format,
+                data,
+            );
+        }
+        let format = cpu_texture.format.format();
+        Ok(Self {
+            context: context.clone(),
+            id,
+            width: cpu_texture.width,
+            height: cpu_texture.height,
+            number_of_mip_maps,
+            format,
+            transparent: format == Format::RGBA,
+            _dummy: 0,
+        })
+    }
+}
+
 impl<T: TextureDataType> Texture for Texture2D<T> {
     fn bind(&self, location: u32) {
         bind_at(&self.context, &self.id, consts::TEXTURE_2D, location);
@@ -346,6 +346,69 @@ impl<T: TextureDataType> TextureCubeMap<T> {
         )
     }
 
+    ///
+    /// Returns the pixels of the given mip level of the given side, ordered in the same way as the data of a [CPUTextureCube].
+    /// **Note:** Only works for the RGBA format.
+    ///
+    pub fn read(&self, side: CubeMapSide, mip_level: u32) -> ThreeDResult<Vec<T>> {
+        if self.format != Format::RGBA {
+            Err(CoreError::ReadWrongFormat)?;
+        }
+        if mip_level >= self.number_of_mip_maps {
+            Err(CoreError::IndexOutOfRange(
+                mip_level as usize,
+                self.number_of_mip_maps as usize - 1,
+            ))?;
+        }
+        let width = (self.width >> mip_level).max(1);
+        let height = (self.height >> mip_level).max(1);
+        let id = crate::core::render_target::new_framebuffer(&self.context)?;
+
+        self.context
+            .bind_framebuffer(consts::DRAW_FRAMEBUFFER, Some(&id));
+        self.context
+            .bind_framebuffer(consts::READ_FRAMEBUFFER, Some(&id));
+        self.context.draw_buffers(&[consts::COLOR_ATTACHMENT0]);
+        self.bind_as_color_target(side, 0, mip_level);
+
+        #[cfg(feature = "debug")]
+        check(&self.context)?;
+
+        let mut pixels =
+            vec![
+                T::default();
+                width as usize * height as usize * self.format.color_channel_count() as usize
+            ];
+        T::read(
+            &self.context,
+            Viewport::new_at_origo(width, height),
+            self.format,
+            &mut pixels,
+        );
+        self.context.delete_framebuffer(Some(&id));
+        Ok(pixels)
+    }
+
+    ///
+    /// Reads all 6 sides of the given mip level back into a [CPUTextureCube], for example to save the cube map as a single image
+    /// using [CPUTextureCube::to_layout] for debugging.
+    /// **Note:** Only works for the RGBA format.
+    ///
+    pub fn to_cpu_texture(&self, mip_level: u32) -> ThreeDResult<CPUTextureCube<T>> {
+        Ok(CPUTextureCube {
+            right_data: self.read(CubeMapSide::Right, mip_level)?,
+            left_data: self.read(CubeMapSide::Left, mip_level)?,
+            top_data: self.read(CubeMapSide::Top, mip_level)?,
+            bottom_data: self.read(CubeMapSide::Bottom, mip_level)?,
+            front_data: self.read(CubeMapSide::Front, mip_level)?,
+            back_data: self.read(CubeMapSide::Back, mip_level)?,
+            width: (self.width >> mip_level).max(1),
+            height: (self.height >> mip_level).max(1),
+            format: self.format,
+            ..Default::default()
+        })
+    }
+
     pub(in crate::core) fn generate_mip_maps(&self) {
         if self.number_of_mip_maps > 1 {
             self.context
@@ -370,6 +433,88 @@ impl<T: TextureDataType> TextureCubeMap<T> {
     }
 }
 
+impl TextureCubeMap<u8> {
+    ///
+    /// Creates a new texture cube map from the given pre-compressed data.
+    /// If the graphics context supports the compression format (see [Context::supports_compressed_format]), the compressed mip levels are uploaded directly,
+    /// otherwise the full size images are decompressed on the CPU and uploaded as a [TextureCubeMap::new] would.
+    ///
+    /// **Note:** A compressed texture cannot be filled or written to after construction.
+    ///
+    /// # Errors
+    /// Returns an error if the length of the data for any mip level of any of the 6 images does not correspond to the width, height and format
+    /// or if the format is not supported by the graphics context and cannot be decompressed on the CPU.
+    /// An error is also returned if the format is [CompressedFormat::ASTC] with a block size which is not defined by the ASTC specification.
+    ///
+    pub fn new_compressed(
+        context: &Context,
+        cpu_texture: &CPUCompressedTextureCube,
+    ) -> ThreeDResult<Self> {
+        if !context.supports_compressed_format(cpu_texture.format) {
+            return Self::new(context, &cpu_texture.decompress()?);
+        }
+        let images = cpu_texture.images();
+        for data in images.iter() {
+            check_compressed_data_length(
+                cpu_texture.width,
+                cpu_texture.height,
+                cpu_texture.format,
+                data,
+            )?;
+        }
+        // Only the mip levels which are available for all 6 images are used
+        let number_of_mip_maps = images.iter().map(|data| data.len()).min().unwrap() as u32;
+        let id = generate(context)?;
+        set_parameters(
+            context,
+            &id,
+            consts::TEXTURE_CUBE_MAP,
+            cpu_texture.min_filter,
+            cpu_texture.mag_filter,
+            if number_of_mip_maps == 1 {
+                None
+            } else {
+                cpu_texture.mip_map_filter
+            },
+            cpu_texture.wrap_s,
+            cpu_texture.wrap_t,
+            Some(cpu_texture.wrap_r),
+        );
+        let internal_format = cpu_texture.format.internal_format()?;
+        context.tex_storage_2d(
+            consts::TEXTURE_CUBE_MAP,
+            number_of_mip_maps,
+            internal_format,
+            cpu_texture.width,
+            cpu_texture.height,
+        );
+        for (i, data) in images.iter().enumerate() {
+            for (level, data) in data.iter().take(number_of_mip_maps as usize).enumerate() {
+                context.compressed_tex_sub_image_2d(
+                    consts::TEXTURE_CUBE_MAP_POSITIVE_X + i as u32,
+                    level as u32,
+                    0,
+                    0,
+                    (cpu_texture.width >> level).max(1),
+                    (cpu_texture.height >> level).max(1),
+                    internal_format,
+                    data,
+                );
+            }
+        }
+        Ok(Self {
+            context: context.clone(),
+            id,
+            width: cpu_texture.width,
+            height: cpu_texture.height,
+            number_of_mip_maps,
+            format: cpu_texture.format.format(),
+            is_hdr: false,
+            _dummy: 0,
+        })
+    }
+}
+
 impl<T: TextureDataType> TextureCube for TextureCubeMap<T> {
     fn bind(&self, location: u32) {
         bind_at(&self.context, &self.id, consts::TEXTURE_CUBE_MAP, location);
@@ -12,6 +12,10 @@ mod parser;
 #[doc(inline)]
 pub use parser::*;
 
+mod resolver;
+#[doc(inline)]
+pub use resolver::*;
+
 #[cfg(not(target_arch = "wasm32"))]
 mod saver;
 #[doc(inline)]
@@ -34,6 +38,9 @@ pub enum IOError {
     #[cfg(feature = "obj-io")]
     #[error("error while parsing an .obj file")]
     Obj(#[from] wavefront_obj::ParseError),
+    #[cfg(feature = "obj-io")]
+    #[error("error while parsing the .mtl file {0}: {1}")]
+    Mtl(String, String),
     #[cfg(feature = "gltf-io")]
     #[error("error while parsing a .gltf file")]
     Gltf(#[from] ::gltf::Error),
@@ -43,9 +50,37 @@ pub enum IOError {
     #[cfg(feature = "gltf-io")]
     #[error("the .gltf file contain missing buffer data")]
     GltfMissingData,
+    #[cfg(feature = "gltf-io")]
+    #[error("error while serializing a .gltf file: {0}")]
+    GltfSerialization(#[from] serde_json::Error),
+    #[cfg(feature = "ply-io")]
+    #[error("error while parsing the .ply file {0}: {1}")]
+    Ply(String, String),
+    #[cfg(feature = "stl-io")]
+    #[error("error while parsing the .stl file {0}: {1}")]
+    Stl(String, String),
+    #[cfg(feature = "exr-io")]
+    #[error("error while reading or writing an .exr file")]
+    Exr(#[from] ::exr::error::Error),
+    #[cfg(feature = "compressed-texture-io")]
+    #[error("error while parsing the compressed texture {0}: {1}")]
+    CompressedTexture(String, String),
+    #[cfg(feature = "archive-io")]
+    #[error("error while reading a .zip archive")]
+    Zip(#[from] zip::result::ZipError),
     #[cfg(not(target_arch = "wasm32"))]
     #[error("error while loading a file")]
     Load(#[from] std::io::Error),
+    #[error("the file {0} does not contain valid UTF-8 text")]
+    InvalidUtf8(String),
+    #[error("the file {0} is corrupt: {1}")]
+    Corrupt(String, String),
+    #[error("failed to load {0}: {1}")]
+    FailedLoading(String, String),
+    #[error("the format of the file {0} is not supported")]
+    UnsupportedFormat(String),
+    #[error("the loading was cancelled")]
+    LoadingCancelled,
     #[error("tried to use {0} which was not loaded")]
     NotLoaded(String),
 }
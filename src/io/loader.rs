@@ -4,15 +4,27 @@ use std::cell::RefCell;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 
 ///
 /// Convenience functionality to load some resources and, when loaded, use them to create one or more objects (for example a 3D model, a skybox, a texture etc).
 /// To get the loaded object, use the `borrow()` or `borrow_mut()` methods which returns `Some` reference to the object if loaded and `None` otherwise.
+/// The resources are loaded in the background, see [Loader::load_in_background], and the `on_load` closure is called
+/// on the thread which owns this struct the first time the object is accessed after all of the resources are loaded.
+///
+/// **Note:** The `on_load` closure is also called lazily when not building for the wasm32 architecture,
+/// so the object is not available right after [Loading::new] returns, but only when it is accessed after the resources are loaded,
+/// for example using [is_loaded](Self::is_loaded) or `borrow()`.
 ///
 pub struct Loading<T> {
     load: Rc<RefCell<Option<ThreeDResult<T>>>>,
+    handle: LoadingHandle,
+    on_load: RefCell<Option<OnLoad<T>>>,
 }
 
+type OnLoad<T> = Box<dyn FnOnce(Loaded) -> ThreeDResult<T>>;
+
 impl<T: 'static> Loading<T> {
     ///
     /// Starts loading the resources defined by `paths` and calls the `on_load` closure when everything is loaded.
@@ -22,32 +34,85 @@ impl<T: 'static> Loading<T> {
         paths: &[impl AsRef<Path>],
         on_load: impl 'static + FnOnce(Context, Loaded) -> ThreeDResult<T>,
     ) -> Self {
-        let load = Rc::new(RefCell::new(None));
-        let load_clone = load.clone();
-        let context_clone = context.clone();
-        Loader::load(paths, move |loaded| {
-            *load_clone.borrow_mut() = Some(on_load(context_clone, loaded));
-        });
-        Self { load }
+        Self::new_with_resolvers(context, paths, Vec::new(), on_load)
+    }
+
+    ///
+    /// Starts loading the resources defined by `paths` using the given [resolvers](ResourceResolver) before the file system and urls
+    /// and calls the `on_load` closure when everything is loaded.
+    ///
+    pub fn new_with_resolvers(
+        context: &Context,
+        paths: &[impl AsRef<Path>],
+        resolvers: Vec<Arc<dyn ResourceResolver>>,
+        on_load: impl 'static + FnOnce(Context, Loaded) -> ThreeDResult<T>,
+    ) -> Self {
+        let context = context.clone();
+        Self {
+            load: Rc::new(RefCell::new(None)),
+            handle: Loader::load_in_background_with_resolvers(paths, resolvers),
+            on_load: RefCell::new(Some(Box::new(move |loaded| on_load(context, loaded)))),
+        }
     }
 
     ///
     /// Returns true if the object is loaded and mapped by the `on_load` closure.
+    /// This is also the case if the loading is cancelled, in which case the object is an [IOError::LoadingCancelled] error.
     ///
     pub fn is_loaded(&self) -> bool {
+        self.poll();
         self.load.borrow().is_some()
     }
+
+    ///
+    /// Returns the current progress of loading the resources.
+    ///
+    pub fn progress(&self) -> LoadingProgress {
+        self.handle.progress()
+    }
+
+    ///
+    /// Cancels the loading of the resources. The `on_load` closure is not called if it has not been called already.
+    ///
+    pub fn cancel(&self) {
+        self.handle.cancel();
+        self.poll();
+    }
+
+    ///
+    /// Returns the handle to the resources loading in the background.
+    ///
+    pub fn handle(&self) -> &LoadingHandle {
+        &self.handle
+    }
+
+    fn poll(&self) {
+        if let Ok(mut load) = self.load.try_borrow_mut() {
+            if load.is_none() {
+                if self.handle.is_cancelled() {
+                    self.on_load.borrow_mut().take();
+                    *load = Some(Err(IOError::LoadingCancelled.into()));
+                } else if let Some(loaded) = self.handle.take_loaded() {
+                    if let Some(on_load) = self.on_load.borrow_mut().take() {
+                        *load = Some(on_load(loaded));
+                    }
+                }
+            }
+        }
+    }
 }
 
 impl<T: 'static> std::ops::Deref for Loading<T> {
     type Target = Rc<RefCell<Option<ThreeDResult<T>>>>;
     fn deref(&self) -> &Self::Target {
+        self.poll();
         &self.load
     }
 }
 
 impl<T: 'static> std::ops::DerefMut for Loading<T> {
     fn deref_mut(&mut self) -> &mut Self::Target {
+        self.poll();
         &mut self.load
     }
 }
@@ -56,10 +121,13 @@ impl<T: 'static> std::ops::DerefMut for Loading<T> {
 /// Contains the resources loaded using the [Loader](crate::Loader) and/or manually inserted using the [insert_bytes](Self::insert_bytes) method.
 /// Use the [remove_bytes](crate::Loaded::remove_bytes) or [get_bytes](crate::Loaded::get_bytes) function to extract the raw byte array for the loaded resource
 /// or one of the other methods to both extract and deserialize a loaded resource.
+/// Resources which are not loaded are requested from the [resolvers](ResourceResolver) of this struct, if any,
+/// which for example makes it possible to load the textures of a .gltf file from the same archive as the .gltf file.
 ///
 #[derive(Default)]
 pub struct Loaded {
     loaded: HashMap<PathBuf, std::result::Result<Vec<u8>, std::io::Error>>,
+    resolvers: Vec<Arc<dyn ResourceResolver>>,
 }
 
 impl Loaded {
@@ -76,23 +144,17 @@ impl Loaded {
     /// The byte array then has to be deserialized to whatever type this resource is (image, 3D model etc.).
     ///
     pub fn remove_bytes(&mut self, path: impl AsRef<Path>) -> ThreeDResult<Vec<u8>> {
-        if let Some((path, bytes)) = self.loaded.remove_entry(path.as_ref()) {
-            Ok(bytes.map_err(|_| IOError::NotLoaded(path.to_str().unwrap().to_string()))?)
-        } else {
-            let key = self
-                .loaded
-                .iter()
-                .find(|(k, _)| {
-                    k.to_str()
-                        .unwrap()
-                        .contains(path.as_ref().to_str().unwrap())
-                })
-                .ok_or(IOError::NotLoaded(
-                    path.as_ref().to_str().unwrap().to_owned(),
-                ))?
-                .0
-                .clone();
-            Ok(self.loaded.remove(&key).unwrap()?)
+        let key = self.find_key(path.as_ref())?;
+        match self.loaded.remove(&key) {
+            Some(Ok(bytes)) => Ok(bytes),
+            Some(Err(e)) if e.kind() == std::io::ErrorKind::Interrupted => {
+                Err(IOError::LoadingCancelled)?
+            }
+            Some(Err(e)) => Err(IOError::FailedLoading(
+                key.to_string_lossy().to_string(),
+                e.to_string(),
+            ))?,
+            None => Err(IOError::NotLoaded(key.to_string_lossy().to_string()))?,
         }
     }
 
@@ -101,32 +163,40 @@ impl Loaded {
     /// The byte array then has to be deserialized to whatever type this resource is (image, 3D model etc.).
     ///
     pub fn get_bytes(&mut self, path: impl AsRef<Path>) -> ThreeDResult<&[u8]> {
-        if let Some(bytes) = self.loaded.get(path.as_ref()) {
-            Ok(bytes
-                .as_ref()
-                .map_err(|_| IOError::NotLoaded(path.as_ref().to_str().unwrap().to_string()))?)
-        } else {
-            let key = self
-                .loaded
-                .iter()
-                .find(|(k, _)| {
-                    k.to_str()
-                        .unwrap()
-                        .contains(path.as_ref().to_str().unwrap())
-                })
-                .ok_or(IOError::NotLoaded(
-                    path.as_ref().to_str().unwrap().to_owned(),
-                ))?
-                .0;
-            Ok(self
-                .loaded
-                .get(key)
-                .unwrap()
-                .as_ref()
-                .map_err(|e| std::io::Error::from(e.kind()))?)
+        let key = self.find_key(path.as_ref())?;
+        match self.loaded.get(&key) {
+            Some(Ok(bytes)) => Ok(bytes),
+            Some(Err(e)) if e.kind() == std::io::ErrorKind::Interrupted => {
+                Err(IOError::LoadingCancelled)?
+            }
+            Some(Err(e)) => Err(IOError::FailedLoading(
+                key.to_string_lossy().to_string(),
+                e.to_string(),
+            ))?,
+            None => Err(IOError::NotLoaded(key.to_string_lossy().to_string()))?,
         }
     }
 
+    ///
+    /// Returns the key of the resource at the given path, either an exact match, the path if one of the resolvers has the resource
+    /// or otherwise the first key which contains the given path.
+    ///
+    fn find_key(&mut self, path: &Path) -> ThreeDResult<PathBuf> {
+        if self.loaded.contains_key(path) {
+            return Ok(path.to_path_buf());
+        }
+        if let Some(result) = resolve(&self.resolvers, path) {
+            self.loaded.insert(path.to_path_buf(), result);
+            return Ok(path.to_path_buf());
+        }
+        let name = path.to_string_lossy();
+        self.loaded
+            .keys()
+            .find(|key| key.to_string_lossy().contains(name.as_ref()))
+            .cloned()
+            .ok_or_else(|| IOError::NotLoaded(name.to_string()).into())
+    }
+
     ///
     /// Inserts the given bytes into the set of loaded files which is useful if you want to load the data from an unsuported source.
     /// The files can then be parsed as usual using the functionality on Loaded.
@@ -134,6 +204,14 @@ impl Loaded {
     pub fn insert_bytes(&mut self, path: impl AsRef<Path>, bytes: Vec<u8>) {
         self.loaded.insert(path.as_ref().to_path_buf(), Ok(bytes));
     }
+
+    ///
+    /// Adds a [resolver](ResourceResolver) which is used to get resources which are not loaded when they are requested,
+    /// for example when parsing a file which refers to other files.
+    ///
+    pub fn add_resolver(&mut self, resolver: Arc<dyn ResourceResolver>) {
+        self.resolvers.push(resolver);
+    }
 }
 
 impl std::fmt::Debug for Loaded {
@@ -154,6 +232,205 @@ impl std::fmt::Debug for Loaded {
     }
 }
 
+///
+/// The maximum number of background threads used to load the resources given to one call of the [Loader].
+///
+#[cfg(not(target_arch = "wasm32"))]
+const THREAD_COUNT: usize = 4;
+
+///
+/// Returns the error which is stored for the resources which are not loaded because the loading was cancelled,
+/// it is reported as [IOError::LoadingCancelled] when trying to use the resource.
+///
+fn cancelled_error() -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::Interrupted,
+        IOError::LoadingCancelled.to_string(),
+    )
+}
+
+fn resolve(
+    resolvers: &[Arc<dyn ResourceResolver>],
+    path: &Path,
+) -> Option<std::io::Result<Vec<u8>>> {
+    resolvers.iter().find_map(|resolver| resolver.resolve(path))
+}
+
+///
+/// The progress of loading a single resource, see [LoadingProgress].
+///
+#[derive(Clone, Debug)]
+pub struct ResourceProgress {
+    /// The path or url of the resource.
+    pub path: PathBuf,
+    /// The number of bytes loaded so far.
+    pub bytes_loaded: u64,
+    /// The total number of bytes of the resource if known, ie. the size of the file or the content length of the HTTP response.
+    pub bytes_total: Option<u64>,
+    /// Whether or not the resource is done loading, either successfully or with an error.
+    pub done: bool,
+}
+
+///
+/// The progress of loading a set of resources, see [LoadingHandle::progress] and [Loading::progress].
+///
+#[derive(Clone, Debug, Default)]
+pub struct LoadingProgress {
+    /// The progress of each of the resources in the order they were given.
+    pub resources: Vec<ResourceProgress>,
+}
+
+impl LoadingProgress {
+    ///
+    /// Returns the number of resources which are done loading.
+    ///
+    pub fn completed_count(&self) -> usize {
+        self.resources.iter().filter(|r| r.done).count()
+    }
+
+    ///
+    /// Returns the total number of resources.
+    ///
+    pub fn total_count(&self) -> usize {
+        self.resources.len()
+    }
+
+    ///
+    /// Returns the number of bytes loaded so far of all of the resources.
+    ///
+    pub fn bytes_loaded(&self) -> u64 {
+        self.resources.iter().map(|r| r.bytes_loaded).sum()
+    }
+
+    ///
+    /// Returns the total number of bytes of all of the resources or `None` if the size of any of the resources is not known (yet).
+    ///
+    pub fn bytes_total(&self) -> Option<u64> {
+        self.resources.iter().map(|r| r.bytes_total).sum()
+    }
+
+    ///
+    /// Returns true if all of the resources are done loading.
+    ///
+    pub fn is_done(&self) -> bool {
+        self.resources.iter().all(|r| r.done)
+    }
+}
+
+struct LoadingState {
+    progress: Mutex<LoadingProgress>,
+    loaded: Mutex<Option<Loaded>>,
+    cancelled: AtomicBool,
+    resolvers: Vec<Arc<dyn ResourceResolver>>,
+}
+
+impl LoadingState {
+    fn update(&self, index: usize, callback: impl FnOnce(&mut ResourceProgress)) {
+        if let Ok(mut progress) = self.progress.lock() {
+            if let Some(resource) = progress.resources.get_mut(index) {
+                callback(resource);
+            }
+        }
+    }
+
+    fn finish(&self, index: usize, path: PathBuf, result: std::io::Result<Vec<u8>>) {
+        if let Ok(mut loaded) = self.loaded.lock() {
+            if let Some(ref mut loaded) = *loaded {
+                loaded.loaded.insert(path, result);
+            }
+        }
+        self.update(index, |resource| resource.done = true);
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+///
+/// A handle to a set of resources which are loading in the background, see [Loader::load_in_background].
+/// Use it to poll the progress, to cancel the loading or to get the [loaded resources](crate::Loaded) when all of them are done loading.
+///
+#[derive(Clone)]
+pub struct LoadingHandle {
+    state: Arc<LoadingState>,
+}
+
+impl LoadingHandle {
+    fn new(paths: &[impl AsRef<Path>], resolvers: Vec<Arc<dyn ResourceResolver>>) -> Self {
+        Self {
+            state: Arc::new(LoadingState {
+                progress: Mutex::new(LoadingProgress {
+                    resources: paths
+                        .iter()
+                        .map(|path| ResourceProgress {
+                            path: path.as_ref().to_path_buf(),
+                            bytes_loaded: 0,
+                            bytes_total: None,
+                            done: false,
+                        })
+                        .collect(),
+                }),
+                loaded: Mutex::new(Some(Loaded {
+                    resolvers: resolvers.clone(),
+                    ..Default::default()
+                })),
+                cancelled: AtomicBool::new(false),
+                resolvers,
+            }),
+        }
+    }
+
+    ///
+    /// Returns the current progress of the loading.
+    ///
+    pub fn progress(&self) -> LoadingProgress {
+        self.state
+            .progress
+            .lock()
+            .map(|progress| progress.clone())
+            .unwrap_or_default()
+    }
+
+    ///
+    /// Returns true if all of the resources are done loading.
+    ///
+    pub fn is_done(&self) -> bool {
+        self.progress().is_done()
+    }
+
+    ///
+    /// Cancels the loading of the resources which are not done loading yet.
+    /// Using one of the cancelled resources returns an [IOError::LoadingCancelled] error.
+    ///
+    pub fn cancel(&self) {
+        self.state.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    ///
+    /// Returns true if the loading has been cancelled.
+    ///
+    pub fn is_cancelled(&self) -> bool {
+        self.state.is_cancelled()
+    }
+
+    ///
+    /// Returns the [loaded resources](crate::Loaded) if all of the resources are done loading.
+    /// Returns `None` if the resources are still loading or if the loaded resources have already been taken.
+    ///
+    pub fn take_loaded(&self) -> Option<Loaded> {
+        if self.is_done() {
+            self.state
+                .loaded
+                .lock()
+                .ok()
+                .and_then(|mut loaded| loaded.take())
+        } else {
+            None
+        }
+    }
+}
+
 ///
 /// Functionality for loading any type of resource runtime on both desktop and web.
 ///
@@ -162,53 +439,380 @@ pub struct Loader {}
 impl Loader {
     ///
     /// Loads all of the resources in the given paths then calls `on_done` with all of the [loaded resources](crate::Loaded).
+    /// On desktop, the resources are loaded in parallel on background threads, but this function blocks until all of them are loaded.
+    /// Use [Loader::load_in_background] to avoid blocking.
     ///
     pub fn load(paths: &[impl AsRef<Path>], on_done: impl 'static + FnOnce(Loaded)) {
+        Self::load_with_resolvers(paths, Vec::new(), on_done)
+    }
+
+    ///
+    /// Loads all of the resources in the given paths using the given [resolvers](ResourceResolver) before the file system and urls
+    /// then calls `on_done` with all of the [loaded resources](crate::Loaded).
+    /// The resolvers are also available when parsing the loaded resources, see [Loaded::add_resolver].
+    ///
+    pub fn load_with_resolvers(
+        paths: &[impl AsRef<Path>],
+        resolvers: Vec<Arc<dyn ResourceResolver>>,
+        on_done: impl 'static + FnOnce(Loaded),
+    ) {
         #[cfg(target_arch = "wasm32")]
         {
             wasm_bindgen_futures::spawn_local(Self::load_files_async(
                 paths.iter().map(|p| p.as_ref().to_path_buf()).collect(),
+                resolvers,
                 on_done,
             ));
         }
         #[cfg(not(target_arch = "wasm32"))]
         {
-            let mut loaded = Loaded::new();
-            for path in paths {
-                let result = if let Ok(url) = reqwest::Url::parse(path.as_ref().to_str().unwrap()) {
-                    Ok(reqwest::blocking::get(url)
-                        .map(|r| (*r.bytes().unwrap()).to_vec())
-                        .unwrap())
+            let handle = LoadingHandle::new(paths, resolvers);
+            for thread in Self::spawn_threads(paths, &handle) {
+                thread.join().ok();
+            }
+            on_done(handle.take_loaded().unwrap_or_default())
+        }
+    }
+
+    ///
+    /// Starts loading all of the resources in the given paths and returns immediately with a [LoadingHandle]
+    /// which can be used to poll the progress, cancel the loading and get the [loaded resources](crate::Loaded) when done.
+    /// On desktop, the resources are loaded by a small pool of background threads and on web, each resource is loaded by a separate request.
+    ///
+    pub fn load_in_background(paths: &[impl AsRef<Path>]) -> LoadingHandle {
+        Self::load_in_background_with_resolvers(paths, Vec::new())
+    }
+
+    ///
+    /// Starts loading all of the resources in the given paths using the given [resolvers](ResourceResolver) before the file system and urls,
+    /// see [Loader::load_in_background].
+    ///
+    pub fn load_in_background_with_resolvers(
+        paths: &[impl AsRef<Path>],
+        resolvers: Vec<Arc<dyn ResourceResolver>>,
+    ) -> LoadingHandle {
+        let handle = LoadingHandle::new(paths, resolvers);
+        #[cfg(target_arch = "wasm32")]
+        {
+            for (index, path) in paths.iter().enumerate() {
+                let state = handle.state.clone();
+                let path = path.as_ref().to_path_buf();
+                wasm_bindgen_futures::spawn_local(async move {
+                    let result = if state.is_cancelled() {
+                        Err(cancelled_error())
+                    } else if let Some(result) = resolve(&state.resolvers, &path) {
+                        result
+                    } else {
+                        Self::load_file_async(&path)
+                            .await
+                            .map_err(std::io::Error::other)
+                    };
+                    if let Ok(ref bytes) = result {
+                        state.update(index, |resource| {
+                            resource.bytes_loaded = bytes.len() as u64;
+                            resource.bytes_total = Some(bytes.len() as u64);
+                        });
+                    }
+                    state.finish(index, path, result);
+                });
+            }
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        Self::spawn_threads(paths, &handle);
+        handle
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn spawn_threads(
+        paths: &[impl AsRef<Path>],
+        handle: &LoadingHandle,
+    ) -> Vec<std::thread::JoinHandle<()>> {
+        let queue = Arc::new(Mutex::new(
+            paths
+                .iter()
+                .map(|path| path.as_ref().to_path_buf())
+                .enumerate()
+                .collect::<std::collections::VecDeque<_>>(),
+        ));
+        let mut threads = Vec::new();
+        for _ in 0..paths.len().min(THREAD_COUNT) {
+            let state = handle.state.clone();
+            let thread_queue = queue.clone();
+            match std::thread::Builder::new().spawn(move || loop {
+                let next = thread_queue
+                    .lock()
+                    .ok()
+                    .and_then(|mut queue| queue.pop_front());
+                if let Some((index, path)) = next {
+                    let result = Self::load_file(&path, index, &state);
+                    state.finish(index, path, result);
                 } else {
-                    std::fs::read(path.as_ref())
-                };
-                loaded.loaded.insert(path.as_ref().to_path_buf(), result);
+                    break;
+                }
+            }) {
+                Ok(thread) => threads.push(thread),
+                Err(e) if threads.is_empty() => {
+                    // Without any threads, none of the resources can be loaded
+                    if let Ok(mut queue) = queue.lock() {
+                        for (index, path) in queue.drain(..) {
+                            let error = std::io::Error::new(e.kind(), e.to_string());
+                            handle.state.finish(index, path, Err(error));
+                        }
+                    }
+                    break;
+                }
+                Err(_) => break,
             }
-            on_done(loaded)
         }
+        threads
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load_file(path: &Path, index: usize, state: &LoadingState) -> std::io::Result<Vec<u8>> {
+        use std::io::{Error, ErrorKind, Read};
+        if state.is_cancelled() {
+            Err(cancelled_error())?;
+        }
+        if let Some(result) = resolve(&state.resolvers, path) {
+            let bytes = result?;
+            state.update(index, |resource| {
+                resource.bytes_loaded = bytes.len() as u64;
+                resource.bytes_total = Some(bytes.len() as u64);
+            });
+            return Ok(bytes);
+        }
+        let (mut reader, bytes_total): (Box<dyn Read>, Option<u64>) =
+            if let Some(url) = path.to_str().and_then(|p| reqwest::Url::parse(p).ok()) {
+                let response = reqwest::blocking::get(url)
+                    .and_then(|response| response.error_for_status())
+                    .map_err(Error::other)?;
+                let bytes_total = response.content_length();
+                (Box::new(response), bytes_total)
+            } else {
+                let file = std::fs::File::open(path)?;
+                let bytes_total = file.metadata().ok().map(|metadata| metadata.len());
+                (Box::new(file), bytes_total)
+            };
+        state.update(index, |resource| resource.bytes_total = bytes_total);
+
+        let mut bytes = Vec::new();
+        let mut buffer = vec![0u8; 64 * 1024];
+        loop {
+            if state.is_cancelled() {
+                Err(cancelled_error())?;
+            }
+            match reader.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(count) => bytes.extend_from_slice(&buffer[..count]),
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => Err(e)?,
+            }
+            state.update(index, |resource| resource.bytes_loaded = bytes.len() as u64);
+        }
+        Ok(bytes)
     }
 
     #[cfg(target_arch = "wasm32")]
-    async fn load_files_async(paths: Vec<PathBuf>, on_done: impl 'static + FnOnce(Loaded)) {
-        let mut loads = Loaded::new();
+    async fn load_files_async(
+        paths: Vec<PathBuf>,
+        resolvers: Vec<Arc<dyn ResourceResolver>>,
+        on_done: impl 'static + FnOnce(Loaded),
+    ) {
+        let mut loads = Loaded {
+            resolvers,
+            ..Default::default()
+        };
         for path in paths.iter() {
-            let url = reqwest::Url::parse(path.to_str().unwrap()).unwrap_or_else(|_| {
+            let result = if let Some(result) = resolve(&loads.resolvers, path) {
+                result
+            } else {
+                Self::load_file_async(path)
+                    .await
+                    .map_err(std::io::Error::other)
+            };
+            loads.loaded.insert(path.clone(), result);
+        }
+        on_done(loads)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    async fn load_file_async(path: &Path) -> Result<Vec<u8>, String> {
+        let url = match path.to_str().and_then(|p| reqwest::Url::parse(p).ok()) {
+            Some(url) => url,
+            None => {
                 let u = web_sys::window()
-                    .unwrap()
-                    .document()
-                    .unwrap()
-                    .url()
-                    .unwrap();
+                    .and_then(|window| window.document())
+                    .and_then(|document| document.url().ok())
+                    .ok_or("the url of the document is not available")?;
                 let p = if !u.ends_with("/") {
-                    std::path::PathBuf::from(u).parent().unwrap().join(path)
+                    PathBuf::from(&u)
+                        .parent()
+                        .map(|parent| parent.join(path))
+                        .unwrap_or(path.to_path_buf())
                 } else {
-                    std::path::PathBuf::from(u.clone()).join(path)
+                    PathBuf::from(u).join(path)
                 };
-                reqwest::Url::parse(p.to_str().unwrap()).unwrap()
-            });
-            let data = reqwest::get(url).await.unwrap().bytes().await.unwrap();
-            loads.loaded.insert(path.clone(), Ok((*data).to_vec()));
+                reqwest::Url::parse(&p.to_string_lossy()).map_err(|e| e.to_string())?
+            }
+        };
+        let response = reqwest::get(url)
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| e.to_string())?;
+        Ok(response.bytes().await.map_err(|e| e.to_string())?.to_vec())
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod test {
+    use super::*;
+    use std::io::{Read, Write};
+
+    ///
+    /// Starts a minimal HTTP server which responds with the body of the requested path or 404 if the path is unknown.
+    /// The body of a path starting with `/slow` is sent in small pieces until the client closes the connection.
+    /// Returns the url of the server.
+    ///
+    fn serve(files: Vec<(&'static str, Vec<u8>)>) -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let files = Arc::new(files.into_iter().collect::<HashMap<_, _>>());
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let files = files.clone();
+                std::thread::spawn(move || {
+                    let mut stream = stream?;
+                    let mut request = Vec::new();
+                    let mut buffer = [0u8; 1024];
+                    while !request.ends_with(b"\r\n\r\n") {
+                        let count = stream.read(&mut buffer)?;
+                        if count == 0 {
+                            return Ok(());
+                        }
+                        request.extend_from_slice(&buffer[..count]);
+                    }
+                    let request = String::from_utf8_lossy(&request);
+                    let path = request.split_whitespace().nth(1).unwrap_or("");
+                    if path.starts_with("/slow") {
+                        stream.write_all(
+                            b"HTTP/1.1 200 OK\r\nContent-Length: 1000000000\r\nConnection: close\r\n\r\n",
+                        )?;
+                        loop {
+                            stream.write_all(&[0u8; 100])?;
+                            std::thread::sleep(std::time::Duration::from_millis(10));
+                        }
+                    } else if let Some(body) = files.get(path) {
+                        write!(
+                            stream,
+                            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                            body.len()
+                        )?;
+                        stream.write_all(body)?;
+                    } else {
+                        stream.write_all(
+                            b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                        )?;
+                    }
+                    std::io::Result::Ok(())
+                });
+            }
+        });
+        url
+    }
+
+    fn wait(handle: &LoadingHandle) {
+        let start = std::time::Instant::now();
+        while !handle.is_done() {
+            assert!(
+                start.elapsed() < std::time::Duration::from_secs(30),
+                "the loading did not finish"
+            );
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    pub fn load_in_background() {
+        let files = (0..10)
+            .map(|i| (i, vec![i as u8; 1000 * (i + 1)]))
+            .collect::<Vec<_>>();
+        let paths = ["/0", "/1", "/2", "/3", "/4", "/5", "/6", "/7", "/8", "/9"];
+        let url = serve(
+            files
+                .iter()
+                .map(|(i, bytes)| (paths[*i], bytes.clone()))
+                .collect(),
+        );
+        let urls = paths
+            .iter()
+            .map(|path| format!("{}{}", url, path))
+            .collect::<Vec<_>>();
+        let handle = Loader::load_in_background(&urls);
+        wait(&handle);
+
+        let progress = handle.progress();
+        assert_eq!(progress.total_count(), 10);
+        assert_eq!(progress.completed_count(), 10);
+        assert_eq!(progress.bytes_loaded(), 55000);
+        assert_eq!(progress.bytes_total(), Some(55000));
+        assert!(!handle.is_cancelled());
+
+        let mut loaded = handle.take_loaded().unwrap();
+        assert!(handle.take_loaded().is_none());
+        for (i, bytes) in files.iter() {
+            assert_eq!(loaded.get_bytes(&urls[*i]).unwrap(), &bytes[..]);
+        }
+    }
+
+    #[test]
+    pub fn load_missing_resource() {
+        let url = serve(vec![("/found", vec![1, 2, 3])]);
+        let urls = [format!("{}/found", url), format!("{}/missing", url)];
+        let handle = Loader::load_in_background(&urls);
+        wait(&handle);
+
+        let progress = handle.progress();
+        assert_eq!(progress.completed_count(), 2);
+        assert_eq!(progress.bytes_loaded(), 3);
+
+        let mut loaded = handle.take_loaded().unwrap();
+        assert_eq!(loaded.get_bytes(&urls[0]).unwrap(), &[1, 2, 3]);
+        match loaded.get_bytes(&urls[1]) {
+            Ok(_) => panic!("the missing resource was loaded"),
+            Err(e) => match e.downcast_ref::<IOError>() {
+                Some(IOError::FailedLoading(path, _)) => assert_eq!(path, &urls[1]),
+                _ => panic!("unexpected error {}", e),
+            },
+        }
+    }
+
+    #[test]
+    pub fn cancel_loading() {
+        let url = serve(Vec::new());
+        // More resources than threads, so some of them are still waiting in the queue when cancelled
+        let urls = (0..2 * THREAD_COUNT)
+            .map(|i| format!("{}/slow{}", url, i))
+            .collect::<Vec<_>>();
+        let handle = Loader::load_in_background(&urls);
+        let start = std::time::Instant::now();
+        while handle.progress().bytes_loaded() == 0 {
+            assert!(start.elapsed() < std::time::Duration::from_secs(30));
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        assert_eq!(handle.progress().completed_count(), 0);
+        handle.cancel();
+        assert!(handle.is_cancelled());
+        wait(&handle);
+
+        let mut loaded = handle.take_loaded().unwrap();
+        for url in urls.iter() {
+            match loaded.remove_bytes(url) {
+                Ok(_) => panic!("the cancelled resource was loaded"),
+                Err(e) => match e.downcast_ref::<IOError>() {
+                    Some(IOError::LoadingCancelled) => {}
+                    _ => panic!("unexpected error {}", e),
+                },
+            }
         }
-        on_done(loads)
     }
 }
@@ -19,9 +19,274 @@ mod gltf;
 #[cfg(feature = "gltf-io")]
 pub use self::gltf::*;
 
+#[cfg(feature = "ply-io")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ply-io")))]
+mod ply;
+#[doc(inline)]
+#[cfg(feature = "ply-io")]
+pub use ply::*;
+
+#[cfg(feature = "stl-io")]
+#[cfg_attr(docsrs, doc(cfg(feature = "stl-io")))]
+mod stl;
+
+#[cfg(feature = "exr-io")]
+#[cfg_attr(docsrs, doc(cfg(feature = "exr-io")))]
+mod exr;
+#[doc(inline)]
+#[cfg(feature = "exr-io")]
+pub use self::exr::*;
+
+#[cfg(feature = "compressed-texture-io")]
+#[cfg_attr(docsrs, doc(cfg(feature = "compressed-texture-io")))]
+mod compressed;
+
 #[cfg(feature = "image-io")]
 #[cfg_attr(docsrs, doc(cfg(feature = "image-io")))]
 mod img;
 #[cfg(feature = "image-io")]
 #[doc(inline)]
 pub use img::*;
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod test {
+    use crate::core::*;
+    use crate::io::*;
+
+    ///
+    /// Returns a truncated, a garbled and an empty version of the given valid file contents,
+    /// where the garbled version has eight bytes overwritten starting at the given offset.
+    ///
+    fn malformed(bytes: &[u8], garbled_offset: usize) -> Vec<(&'static str, Vec<u8>)> {
+        let mut garbled = bytes.to_vec();
+        for byte in garbled[garbled_offset..garbled_offset + 8].iter_mut() {
+            *byte = 0xFF;
+        }
+        vec![
+            ("truncated", bytes[..bytes.len() / 2].to_vec()),
+            ("garbled", garbled),
+            ("empty", Vec::new()),
+        ]
+    }
+
+    ///
+    /// Checks that the given valid file contents can be loaded and that loading a truncated, a garbled and an empty
+    /// version of it returns an [IOError] which names the path instead of panicking.
+    ///
+    fn assert_malformed_fails<T>(
+        path: &str,
+        bytes: &[u8],
+        garbled_offset: usize,
+        load: impl Fn(&mut Loaded, &str) -> ThreeDResult<T>,
+    ) {
+        let mut loaded = Loaded::new();
+        loaded.insert_bytes(path, bytes.to_vec());
+        assert!(load(&mut loaded, path).is_ok(), "the valid {} failed", path);
+        for (kind, bytes) in malformed(bytes, garbled_offset) {
+            let mut loaded = Loaded::new();
+            loaded.insert_bytes(path, bytes);
+            match load(&mut loaded, path) {
+                Ok(_) => panic!("the {} {} was loaded", kind, path),
+                Err(e) => {
+                    assert!(
+                        e.downcast_ref::<IOError>().is_some(),
+                        "the {} {} failed with {}",
+                        kind,
+                        path,
+                        e
+                    );
+                    assert!(
+                        e.to_string().contains(path),
+                        "the error for the {} {} does not name the path: {}",
+                        kind,
+                        path,
+                        e
+                    );
+                }
+            }
+        }
+    }
+
+    fn quad() -> CPUMesh {
+        CPUMesh {
+            name: "quad".to_string(),
+            positions: vec![
+                -1.0, -1.0, 0.0, 1.0, -1.0, 0.0, 1.0, 1.0, 0.0, -1.0, 1.0, 0.0,
+            ],
+            indices: Some(Indices::U32(vec![0, 1, 2, 2, 3, 0])),
+            normals: Some(vec![
+                0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0,
+            ]),
+            uvs: Some(vec![0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0, 1.0]),
+            ..Default::default()
+        }
+    }
+
+    #[cfg(feature = "image-io")]
+    fn png() -> Vec<u8> {
+        let mut bytes = Vec::new();
+        image::png::PngEncoder::new(&mut bytes)
+            .encode(&[128; 4 * 4 * 4], 4, 4, image::ColorType::Rgba8)
+            .unwrap();
+        bytes
+    }
+
+    #[cfg(feature = "gltf-io")]
+    #[test]
+    pub fn malformed_gltf() {
+        let material = CPUMaterial {
+            name: "material".to_string(),
+            albedo_texture: Some(CPUTexture {
+                data: vec![255; 2 * 2 * 4],
+                width: 2,
+                height: 2,
+                format: Format::RGBA,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let mesh = CPUMesh {
+            material_name: Some("material".to_string()),
+            ..quad()
+        };
+        let materials = [material];
+        let glb = Saver::serialize_glb(std::slice::from_ref(&mesh), &materials).unwrap();
+        assert_malformed_fails("scene.glb", &glb, 24, |loaded, path| loaded.gltf(path));
+
+        let (json, bin) = Saver::serialize_gltf(&[mesh], &materials, "scene.bin").unwrap();
+        assert_malformed_fails("scene.gltf", &json, 10, |loaded, path| {
+            loaded.insert_bytes("scene.bin", bin.clone());
+            loaded.gltf(path)
+        });
+        for bin in [bin[..bin.len() / 2].to_vec(), Vec::new()].iter() {
+            let mut loaded = Loaded::new();
+            loaded.insert_bytes("scene.gltf", json.clone());
+            loaded.insert_bytes("scene.bin", bin.clone());
+            match loaded.gltf("scene.gltf") {
+                Ok(_) => panic!("the truncated buffer was loaded"),
+                Err(e) => assert!(e.to_string().contains("scene.bin"), "{}", e),
+            }
+        }
+    }
+
+    #[cfg(feature = "obj-io")]
+    #[test]
+    pub fn malformed_obj() {
+        let obj =
+            "o quad\nv -1 -1 0\nv 1 -1 0\nv 1 1 0\nv -1 1 0\nvt 0 0\nvt 1 0\nvt 1 1\nvt 0 1\n\
+                   f 1/1 2/2 3/3\nf 3/3 4/4 1/1\n";
+        let mut loaded = Loaded::new();
+        loaded.insert_bytes("quad.obj", obj.as_bytes().to_vec());
+        assert!(loaded.obj("quad.obj").is_ok());
+        for bytes in [
+            &b"o quad\nv -1 -1 0\nv 1 -1 0\nv 1 1"[..],
+            &b"o quad\nv -1 -1 0\nv 1 -1 0\nv 1 1 0\nf 1 2 4\n"[..],
+            &b"o quad\nv -1 -1 0\nv 1 -one 0\nv 1 1 0\nf 1 2 3\n"[..],
+            &b"o quad\nv -1 -1 0\nv 1 -1 0\nv 1 1 0\nf 1 2 \xFF\n"[..],
+            &b""[..],
+        ]
+        .iter()
+        {
+            let mut loaded = Loaded::new();
+            loaded.insert_bytes("quad.obj", bytes.to_vec());
+            match loaded.obj("quad.obj") {
+                Ok(_) => panic!("the malformed .obj file was loaded"),
+                Err(e) => {
+                    assert!(e.downcast_ref::<IOError>().is_some());
+                    assert!(e.to_string().contains("quad.obj"), "{}", e);
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "ply-io")]
+    #[test]
+    pub fn malformed_ply() {
+        let ascii = Saver::serialize_ply(&quad(), PlyFormat::Ascii);
+        assert_malformed_fails("quad.ply", &ascii, 4, |loaded, path| loaded.ply(path));
+        let binary = Saver::serialize_ply(&quad(), PlyFormat::BinaryLittleEndian);
+        assert_malformed_fails("quad.ply", &binary, 4, |loaded, path| loaded.ply(path));
+    }
+
+    #[cfg(feature = "stl-io")]
+    #[test]
+    pub fn malformed_stl() {
+        let stl = Saver::serialize_stl(&quad()).unwrap();
+        assert_malformed_fails("quad.stl", &stl, 80, |loaded, path| loaded.stl(path));
+        let ascii = b"solid quad\nfacet normal 0 0 1\nouter loop\nvertex -1 -1 0\nvertex 1 -1 0\nvertex 1 1 0\n\
+                      endloop\nendfacet\nendsolid quad\n";
+        assert_malformed_fails("quad.stl", ascii, 48, |loaded, path| loaded.stl(path));
+    }
+
+    #[cfg(feature = "image-io")]
+    #[test]
+    pub fn malformed_image() {
+        assert_malformed_fails("image.png", &png(), 16, |loaded, path| loaded.image(path));
+    }
+
+    #[cfg(feature = "image-io")]
+    #[test]
+    pub fn malformed_hdr_image() {
+        let mut hdr = Vec::new();
+        image::codecs::hdr::HdrEncoder::new(&mut hdr)
+            .encode(&[image::Rgb([0.5f32, 1.0, 2.0]); 4 * 4], 4, 4)
+            .unwrap();
+        assert_malformed_fails("image.hdr", &hdr, 2, |loaded, path| loaded.hdr_image(path));
+    }
+
+    #[cfg(feature = "exr-io")]
+    #[test]
+    pub fn malformed_exr_image() {
+        let exr = super::exr::serialize_exr(4, 4, &[[0.5, 1.0, 2.0, 1.0]; 4 * 4]).unwrap();
+        assert_malformed_fails("image.exr", &exr, 47, |loaded, path| loaded.exr_image(path));
+    }
+
+    #[cfg(feature = "compressed-texture-io")]
+    #[test]
+    pub fn malformed_compressed_image() {
+        let mut dds = vec![0; 128 + 8];
+        dds[..4].copy_from_slice(b"DDS ");
+        dds[4..8].copy_from_slice(&124u32.to_le_bytes());
+        dds[12..16].copy_from_slice(&4u32.to_le_bytes());
+        dds[16..20].copy_from_slice(&4u32.to_le_bytes());
+        dds[80..84].copy_from_slice(&4u32.to_le_bytes());
+        dds[84..88].copy_from_slice(b"DXT1");
+        assert_malformed_fails("image.dds", &dds, 84, |loaded, path| {
+            loaded.compressed_image(path)
+        });
+
+        let mut ktx2 = vec![0; 104 + 8];
+        ktx2[..12].copy_from_slice(&[
+            0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+        ]);
+        ktx2[12..16].copy_from_slice(&131u32.to_le_bytes());
+        ktx2[16..20].copy_from_slice(&1u32.to_le_bytes());
+        ktx2[20..24].copy_from_slice(&4u32.to_le_bytes());
+        ktx2[24..28].copy_from_slice(&4u32.to_le_bytes());
+        ktx2[36..40].copy_from_slice(&1u32.to_le_bytes());
+        ktx2[40..44].copy_from_slice(&1u32.to_le_bytes());
+        ktx2[80..88].copy_from_slice(&104u64.to_le_bytes());
+        ktx2[88..96].copy_from_slice(&8u64.to_le_bytes());
+        ktx2[96..104].copy_from_slice(&8u64.to_le_bytes());
+        assert_malformed_fails("image.ktx2", &ktx2, 12, |loaded, path| {
+            loaded.compressed_image(path)
+        });
+        assert_malformed_fails("image.ktx2", &ktx2, 80, |loaded, path| {
+            loaded.compressed_image(path)
+        });
+    }
+
+    #[cfg(feature = "3d-io")]
+    #[test]
+    pub fn malformed_three_d_scene() {
+        let material = CPUMaterial {
+            name: "material".to_string(),
+            ..Default::default()
+        };
+        let bytes =
+            Saver::serialize_3d_scene(&CPUScene::default(), &[quad()], &[material]).unwrap();
+        assert_malformed_fails("scene.3d", &bytes, 12, |loaded, path| {
+            loaded.three_d_scene(path)
+        });
+    }
+}
@@ -0,0 +1,478 @@
+use crate::core::*;
+use crate::io::*;
+use std::path::Path;
+
+impl Loaded {
+    ///
+    /// Deserialize a loaded .ktx2 or .dds file resource into a [CPUCompressedTexture] without decompressing it.
+    /// The CPUCompressedTexture can then be used to create a [Texture2D] using [Texture2D::new_compressed].
+    /// Supported formats: BC1-BC5 and BC7 in both containers and ETC2, EAC and ASTC in .ktx2 files. Supercompressed .ktx2 files are not supported.
+    ///
+    pub fn compressed_image(
+        &mut self,
+        path: impl AsRef<Path>,
+    ) -> ThreeDResult<CPUCompressedTexture> {
+        let image = self.compressed_images(path.as_ref())?;
+        if image.faces.len() != 1 {
+            Err(IOError::CompressedTexture(
+                path.as_ref().to_string_lossy().to_string(),
+                format!("expected a 2D texture but got {} faces", image.faces.len()),
+            ))?;
+        }
+        Ok(CPUCompressedTexture {
+            data: image.faces.into_iter().next().unwrap(),
+            width: image.width,
+            height: image.height,
+            format: image.format,
+            ..Default::default()
+        })
+    }
+
+    ///
+    /// Deserialize a loaded .ktx2 or .dds file resource which contains a cube map into a [CPUCompressedTextureCube] without decompressing it.
+    /// The CPUCompressedTextureCube can then be used to create a [TextureCubeMap] using [TextureCubeMap::new_compressed].
+    /// See [compressed_image](Self::compressed_image) for the supported formats.
+    ///
+    pub fn compressed_cube_image(
+        &mut self,
+        path: impl AsRef<Path>,
+    ) -> ThreeDResult<CPUCompressedTextureCube> {
+        let image = self.compressed_images(path.as_ref())?;
+        if image.faces.len() != 6 {
+            Err(IOError::CompressedTexture(
+                path.as_ref().to_string_lossy().to_string(),
+                format!("expected a cube map but got {} faces", image.faces.len()),
+            ))?;
+        }
+        let mut faces = image.faces.into_iter();
+        Ok(CPUCompressedTextureCube {
+            right_data: faces.next().unwrap(),
+            left_data: faces.next().unwrap(),
+            top_data: faces.next().unwrap(),
+            bottom_data: faces.next().unwrap(),
+            front_data: faces.next().unwrap(),
+            back_data: faces.next().unwrap(),
+            width: image.width,
+            height: image.height,
+            format: image.format,
+            ..Default::default()
+        })
+    }
+
+    fn compressed_images(&mut self, path: &Path) -> ThreeDResult<CompressedImages> {
+        let bytes = self.get_bytes(path)?;
+        let result = if bytes.starts_with(&KTX2_IDENTIFIER) {
+            parse_ktx2(bytes)
+        } else if bytes.starts_with(b"DDS ") {
+            parse_dds(bytes)
+        } else {
+            Err("the file is neither a .ktx2 nor a .dds file".to_owned())
+        };
+        Ok(
+            result
+                .map_err(|e| IOError::CompressedTexture(path.to_string_lossy().to_string(), e))?,
+        )
+    }
+}
+
+///
+/// The contents of a .ktx2 or .dds file with the compressed data for each mip level of each face.
+///
+struct CompressedImages {
+    format: CompressedFormat,
+    width: u32,
+    height: u32,
+    faces: Vec<Vec<Vec<u8>>>,
+}
+
+const KTX2_IDENTIFIER: [u8; 12] = [
+    0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+];
+
+///
+/// Returns whether the given bytes are the contents of a .ktx2 or a .dds file.
+///
+pub(super) fn is_compressed_image(bytes: &[u8]) -> bool {
+    bytes.starts_with(&KTX2_IDENTIFIER) || bytes.starts_with(b"DDS ")
+}
+
+fn parse_ktx2(bytes: &[u8]) -> Result<CompressedImages, String> {
+    let vk_format = read_u32(bytes, 12)?;
+    let width = read_u32(bytes, 20)?;
+    let height = read_u32(bytes, 24)?;
+    let depth = read_u32(bytes, 28)?;
+    let layer_count = read_u32(bytes, 32)?;
+    let face_count = read_u32(bytes, 36)?;
+    let level_count = read_u32(bytes, 40)?.max(1);
+    let supercompression_scheme = read_u32(bytes, 44)?;
+
+    let format = format_from_vk_format(vk_format)
+        .ok_or_else(|| format!("the Vulkan format {} is not supported", vk_format))?;
+    if supercompression_scheme != 0 {
+        Err(format!(
+            "the supercompression scheme {} is not supported",
+            supercompression_scheme
+        ))?;
+    }
+    if depth > 1 || layer_count > 1 {
+        Err("3D textures and texture arrays are not supported")?;
+    }
+    if face_count != 1 && face_count != 6 {
+        Err(format!("invalid number of faces {}", face_count))?;
+    }
+    if width == 0 || height == 0 || level_count > 32 {
+        Err("invalid size")?;
+    }
+
+    // The images for each mip level are stored after each other with the faces for each level next to each other
+    let mut faces = vec![Vec::with_capacity(level_count as usize); face_count as usize];
+    for level in 0..level_count {
+        let offset = read_u64(bytes, 80 + 24 * level as usize)? as usize;
+        let image_size = format.byte_size((width >> level).max(1), (height >> level).max(1));
+        for (face, data) in faces.iter_mut().enumerate() {
+            data.push(read_bytes(
+                bytes,
+                offset.saturating_add(face.saturating_mul(image_size)),
+                image_size,
+            )?);
+        }
+    }
+    Ok(CompressedImages {
+        format,
+        width,
+        height,
+        faces,
+    })
+}
+
+fn format_from_vk_format(vk_format: u32) -> Option<CompressedFormat> {
+    // Both the UNORM and SRGB variant of each format is accepted, the color space is handled by the shaders.
+    Some(match vk_format {
+        131 | 132 => CompressedFormat::BC1,
+        133 | 134 => CompressedFormat::BC1A,
+        135 | 136 => CompressedFormat::BC2,
+        137 | 138 => CompressedFormat::BC3,
+        139 => CompressedFormat::BC4,
+        141 => CompressedFormat::BC5,
+        145 | 146 => CompressedFormat::BC7,
+        147 | 148 => CompressedFormat::ETC2RGB,
+        149 | 150 => CompressedFormat::ETC2RGBA1,
+        151 | 152 => CompressedFormat::ETC2RGBA,
+        153 => CompressedFormat::EACR,
+        155 => CompressedFormat::EACRG,
+        157..=184 => {
+            let (width, height) = ASTC_BLOCK_SIZES.get((vk_format as usize - 157) / 2)?;
+            CompressedFormat::ASTC(*width, *height)
+        }
+        _ => None?,
+    })
+}
+
+fn parse_dds(bytes: &[u8]) -> Result<CompressedImages, String> {
+    const DDSD_MIPMAPCOUNT: u32 = 0x20000;
+    const DDPF_ALPHAPIXELS: u32 = 0x1;
+    const DDPF_FOURCC: u32 = 0x4;
+    const DDSCAPS2_CUBEMAP: u32 = 0x200;
+    const DDS_RESOURCE_MISC_TEXTURECUBE: u32 = 0x4;
+
+    let flags = read_u32(bytes, 8)?;
+    let height = read_u32(bytes, 12)?;
+    let width = read_u32(bytes, 16)?;
+    let mip_map_count = if flags & DDSD_MIPMAPCOUNT != 0 {
+        read_u32(bytes, 28)?.max(1)
+    } else {
+        1
+    };
+    let pixel_format_flags = read_u32(bytes, 80)?;
+    let four_cc = read_bytes(bytes, 84, 4)?;
+    let caps2 = read_u32(bytes, 112)?;
+    if pixel_format_flags & DDPF_FOURCC == 0 {
+        Err("uncompressed .dds files are not supported")?;
+    }
+
+    let (format, mut cube, offset) = if four_cc == b"DX10" {
+        let dxgi_format = read_u32(bytes, 128)?;
+        let array_size = read_u32(bytes, 140)?;
+        if array_size > 1 {
+            Err("texture arrays are not supported")?;
+        }
+        let format = match dxgi_format {
+            71 | 72 => CompressedFormat::BC1A,
+            74 | 75 => CompressedFormat::BC2,
+            77 | 78 => CompressedFormat::BC3,
+            80 => CompressedFormat::BC4,
+            83 => CompressedFormat::BC5,
+            98 | 99 => CompressedFormat::BC7,
+            _ => Err(format!("the DXGI format {} is not supported", dxgi_format))?,
+        };
+        (
+            format,
+            read_u32(bytes, 136)? & DDS_RESOURCE_MISC_TEXTURECUBE != 0,
+            148,
+        )
+    } else {
+        let format = match &four_cc[..] {
+            b"DXT1" if pixel_format_flags & DDPF_ALPHAPIXELS != 0 => CompressedFormat::BC1A,
+            b"DXT1" => CompressedFormat::BC1,
+            b"DXT2" | b"DXT3" => CompressedFormat::BC2,
+            b"DXT4" | b"DXT5" => CompressedFormat::BC3,
+            b"ATI1" | b"BC4U" => CompressedFormat::BC4,
+            b"ATI2" | b"BC5U" => CompressedFormat::BC5,
+            _ => Err(format!(
+                "the format {} is not supported",
+                String::from_utf8_lossy(&four_cc)
+            ))?,
+        };
+        (format, false, 128)
+    };
+    cube |= caps2 & DDSCAPS2_CUBEMAP != 0;
+    if width == 0 || height == 0 || mip_map_count > 32 {
+        Err("invalid size")?;
+    }
+
+    // All of the mip levels for each face are stored after each other
+    let mut faces = Vec::new();
+    let mut offset = offset;
+    for _ in 0..if cube { 6 } else { 1 } {
+        let mut levels = Vec::with_capacity(mip_map_count as usize);
+        for level in 0..mip_map_count {
+            let size = format.byte_size((width >> level).max(1), (height >> level).max(1));
+            levels.push(read_bytes(bytes, offset, size)?);
+            offset = offset.saturating_add(size);
+        }
+        faces.push(levels);
+    }
+    Ok(CompressedImages {
+        format,
+        width,
+        height,
+        faces,
+    })
+}
+
+fn read_bytes(bytes: &[u8], offset: usize, length: usize) -> Result<Vec<u8>, String> {
+    bytes
+        .get(offset..offset.saturating_add(length))
+        .map(|data| data.to_vec())
+        .ok_or_else(|| "unexpected end of file".to_owned())
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32, String> {
+    let data = read_bytes(bytes, offset, 4)?;
+    Ok(u32::from_le_bytes([data[0], data[1], data[2], data[3]]))
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> Result<u64, String> {
+    let data = read_bytes(bytes, offset, 8)?;
+    let mut value = [0; 8];
+    value.copy_from_slice(&data);
+    Ok(u64::from_le_bytes(value))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    ///
+    /// Returns a .ktx2 file where each byte of the image data of each mip level and face is the index of the image.
+    ///
+    fn ktx2(vk_format: u32, width: u32, height: u32, face_count: u32, level_count: u32) -> Vec<u8> {
+        let format = format_from_vk_format(vk_format).unwrap_or(CompressedFormat::BC1);
+        let mut bytes = vec![0; 80 + 24 * level_count as usize];
+        bytes[..12].copy_from_slice(&KTX2_IDENTIFIER);
+        for (i, value) in [vk_format, 1, width, height, 0, 0, face_count, level_count]
+            .iter()
+            .enumerate()
+        {
+            bytes[12 + 4 * i..16 + 4 * i].copy_from_slice(&value.to_le_bytes());
+        }
+        let mut image = 0;
+        for level in 0..level_count {
+            let size = format.byte_size((width >> level).max(1), (height >> level).max(1));
+            let index = 80 + 24 * level as usize;
+            let offset = bytes.len() as u64;
+            bytes[index..index + 8].copy_from_slice(&offset.to_le_bytes());
+            let length = size as u64 * face_count as u64;
+            bytes[index + 8..index + 16].copy_from_slice(&length.to_le_bytes());
+            bytes[index + 16..index + 24].copy_from_slice(&length.to_le_bytes());
+            for _ in 0..face_count {
+                bytes.extend_from_slice(&vec![image; size]);
+                image += 1;
+            }
+        }
+        bytes
+    }
+
+    ///
+    /// Returns a .dds file with the given four character code where each byte of the image data of each face and mip level is the index of the image.
+    ///
+    fn dds(four_cc: &[u8; 4], width: u32, height: u32, cube: bool, level_count: u32) -> Vec<u8> {
+        let mut bytes = vec![0; 128];
+        bytes[..4].copy_from_slice(b"DDS ");
+        bytes[4..8].copy_from_slice(&124u32.to_le_bytes());
+        bytes[8..12].copy_from_slice(&0x20000u32.to_le_bytes());
+        bytes[12..16].copy_from_slice(&height.to_le_bytes());
+        bytes[16..20].copy_from_slice(&width.to_le_bytes());
+        bytes[28..32].copy_from_slice(&level_count.to_le_bytes());
+        bytes[80..84].copy_from_slice(&4u32.to_le_bytes());
+        bytes[84..88].copy_from_slice(four_cc);
+        if cube {
+            bytes[112..116].copy_from_slice(&0x200u32.to_le_bytes());
+        }
+        let format = if four_cc == b"DXT1" {
+            CompressedFormat::BC1
+        } else {
+            CompressedFormat::BC3
+        };
+        let mut image = 0;
+        for _ in 0..if cube { 6 } else { 1 } {
+            for level in 0..level_count {
+                let size = format.byte_size((width >> level).max(1), (height >> level).max(1));
+                bytes.extend_from_slice(&vec![image; size]);
+                image += 1;
+            }
+        }
+        bytes
+    }
+
+    fn load(bytes: Vec<u8>) -> ThreeDResult<CompressedImages> {
+        let mut loaded = Loaded::new();
+        loaded.insert_bytes("image", bytes);
+        loaded.compressed_images(Path::new("image"))
+    }
+
+    #[test]
+    pub fn parse_ktx2_texture() {
+        let mut loaded = Loaded::new();
+        loaded.insert_bytes("image.ktx2", ktx2(131, 8, 4, 1, 3));
+        let texture = loaded.compressed_image("image.ktx2").unwrap();
+        assert_eq!(texture.format, CompressedFormat::BC1);
+        assert_eq!((texture.width, texture.height), (8, 4));
+        assert_eq!(texture.data, vec![vec![0; 16], vec![1; 8], vec![2; 8]]);
+        assert!(loaded.compressed_cube_image("image.ktx2").is_err());
+    }
+
+    #[test]
+    pub fn parse_ktx2_cube_map() {
+        let mut loaded = Loaded::new();
+        loaded.insert_bytes("cube.ktx2", ktx2(137, 4, 4, 6, 2));
+        let texture = loaded.compressed_cube_image("cube.ktx2").unwrap();
+        assert_eq!(texture.format, CompressedFormat::BC3);
+        assert_eq!((texture.width, texture.height), (4, 4));
+        // The faces of each mip level are stored next to each other
+        let faces = [
+            &texture.right_data,
+            &texture.left_data,
+            &texture.top_data,
+            &texture.bottom_data,
+            &texture.front_data,
+            &texture.back_data,
+        ];
+        for (face, data) in faces.iter().enumerate() {
+            assert_eq!(**data, vec![vec![face as u8; 16], vec![face as u8 + 6; 16]]);
+        }
+        assert!(loaded.compressed_image("cube.ktx2").is_err());
+    }
+
+    #[test]
+    pub fn parse_ktx2_formats() {
+        for &(vk_format, format) in [
+            (132, CompressedFormat::BC1),
+            (141, CompressedFormat::BC5),
+            (146, CompressedFormat::BC7),
+            (148, CompressedFormat::ETC2RGB),
+            (155, CompressedFormat::EACRG),
+            (157, CompressedFormat::ASTC(4, 4)),
+            (170, CompressedFormat::ASTC(8, 6)),
+            (183, CompressedFormat::ASTC(12, 12)),
+            (184, CompressedFormat::ASTC(12, 12)),
+        ]
+        .iter()
+        {
+            let image = load(ktx2(vk_format, 16, 16, 1, 1)).unwrap();
+            assert_eq!(image.format, format);
+            assert_eq!(image.faces[0][0].len(), format.byte_size(16, 16));
+        }
+        for &vk_format in [0, 37, 140, 185, 1000066000].iter() {
+            assert!(load(ktx2(vk_format, 16, 16, 1, 1)).is_err());
+        }
+    }
+
+    #[test]
+    pub fn parse_invalid_ktx2() {
+        let mut supercompressed = ktx2(131, 4, 4, 1, 1);
+        supercompressed[44..48].copy_from_slice(&1u32.to_le_bytes());
+        assert!(load(supercompressed).is_err());
+        assert!(load(ktx2(131, 4, 4, 2, 1)).is_err());
+        assert!(load(ktx2(131, 0, 4, 1, 1)).is_err());
+        let mut array = ktx2(131, 4, 4, 1, 1);
+        array[32..36].copy_from_slice(&2u32.to_le_bytes());
+        assert!(load(array).is_err());
+        let mut truncated = ktx2(131, 8, 8, 1, 1);
+        truncated.pop();
+        assert!(load(truncated).is_err());
+    }
+
+    #[test]
+    pub fn parse_dds_texture() {
+        let image = load(dds(b"DXT1", 8, 8, false, 4)).unwrap();
+        assert_eq!(image.format, CompressedFormat::BC1);
+        assert_eq!((image.width, image.height), (8, 8));
+        assert_eq!(
+            image.faces,
+            vec![vec![vec![0; 32], vec![1; 8], vec![2; 8], vec![3; 8]]]
+        );
+
+        for &(four_cc, format) in [
+            (b"DXT3", CompressedFormat::BC2),
+            (b"DXT5", CompressedFormat::BC3),
+            (b"ATI2", CompressedFormat::BC5),
+        ]
+        .iter()
+        {
+            assert_eq!(load(dds(four_cc, 4, 4, false, 1)).unwrap().format, format);
+        }
+        assert!(load(dds(b"ABCD", 4, 4, false, 1)).is_err());
+    }
+
+    #[test]
+    pub fn parse_dds_cube_map() {
+        let mut loaded = Loaded::new();
+        loaded.insert_bytes("cube.dds", dds(b"DXT5", 4, 4, true, 2));
+        let texture = loaded.compressed_cube_image("cube.dds").unwrap();
+        assert_eq!(texture.format, CompressedFormat::BC3);
+        // All mip levels of a face are stored before the next face
+        let faces = [
+            &texture.right_data,
+            &texture.left_data,
+            &texture.top_data,
+            &texture.bottom_data,
+            &texture.front_data,
+            &texture.back_data,
+        ];
+        for (face, data) in faces.iter().enumerate() {
+            let image = 2 * face as u8;
+            assert_eq!(**data, vec![vec![image; 16], vec![image + 1; 16]]);
+        }
+    }
+
+    #[test]
+    pub fn parse_dds_dx10_header() {
+        let mut bytes = dds(b"DX10", 4, 4, false, 1);
+        let data = bytes.split_off(128);
+        // The extended header contains the DXGI format, the dimension, the misc flags, the array size and more flags
+        for value in [98u32, 3, 0, 1, 0].iter() {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes.extend(data);
+        let image = load(bytes.clone()).unwrap();
+        assert_eq!(image.format, CompressedFormat::BC7);
+        assert_eq!(image.faces, vec![vec![vec![0; 16]]]);
+
+        bytes[128..132].copy_from_slice(&2u32.to_le_bytes());
+        assert!(load(bytes.clone()).is_err());
+        bytes[128..132].copy_from_slice(&98u32.to_le_bytes());
+        bytes[140..144].copy_from_slice(&2u32.to_le_bytes());
+        assert!(load(bytes).is_err());
+    }
+}
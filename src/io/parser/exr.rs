@@ -0,0 +1,165 @@
+use crate::core::*;
+use crate::io::*;
+use std::path::Path;
+
+///
+/// Deserialize the given bytes representing an OpenEXR image into a [CPUTexture] using
+/// the [exr](https://crates.io/crates/exr) crate.
+/// Only the red, green, blue and alpha channels of the first layer are read, the alpha channel is one if it is missing.
+/// The CPUTexture can then be used to create a [Texture2D] or a [TextureCubeMap] using the `new_from_equirectangular` method.
+///
+pub fn exr_image_from_bytes(bytes: &[u8]) -> ThreeDResult<CPUTexture<f32>> {
+    use ::exr::prelude::*;
+    let image = read()
+        .no_deep_data()
+        .largest_resolution_level()
+        .rgba_channels(
+            |resolution, _| {
+                (
+                    resolution.width(),
+                    vec![0.0f32; resolution.width() * resolution.height() * 4],
+                )
+            },
+            |(width, data), position, (r, g, b, a): (f32, f32, f32, f32)| {
+                let index = (position.y() * *width + position.x()) * 4;
+                data[index..index + 4].copy_from_slice(&[r, g, b, a]);
+            },
+        )
+        .first_valid_layer()
+        .all_attributes()
+        .from_buffered(std::io::Cursor::new(bytes))
+        .map_err(IOError::from)?;
+    let size = image.layer_data.size;
+    Ok(CPUTexture {
+        data: image.layer_data.channel_data.pixels.1,
+        width: size.width() as u32,
+        height: size.height() as u32,
+        format: Format::RGBA,
+        ..Default::default()
+    })
+}
+
+impl Loaded {
+    ///
+    /// Deserialize the loaded OpenEXR image resource at the given path into a [CPUTexture] using
+    /// the [exr](https://crates.io/crates/exr) crate, see [exr_image_from_bytes].
+    /// The CPUTexture can then be used to create a [Texture2D] or a [TextureCubeMap] using the `new_from_equirectangular` method.
+    ///
+    pub fn exr_image(&mut self, path: impl AsRef<Path>) -> ThreeDResult<CPUTexture<f32>> {
+        Ok(
+            exr_image_from_bytes(self.get_bytes(path.as_ref())?).map_err(|e| {
+                IOError::Corrupt(path.as_ref().to_string_lossy().to_string(), e.to_string())
+            })?,
+        )
+    }
+}
+
+///
+/// Serializes the given RGBA pixels, ordered from the top row to the bottom row, into the contents of an OpenEXR file with 32 bit float channels.
+///
+#[cfg(not(target_arch = "wasm32"))]
+pub(super) fn serialize_exr(width: u32, height: u32, pixels: &[[f32; 4]]) -> ThreeDResult<Vec<u8>> {
+    use ::exr::prelude::*;
+    let channels = SpecificChannels::rgba(|position: Vec2<usize>| {
+        let [r, g, b, a] = pixels[position.y() * width as usize + position.x()];
+        (r, g, b, a)
+    });
+    let mut bytes = std::io::Cursor::new(Vec::new());
+    Image::from_channels((width as usize, height as usize), channels)
+        .write()
+        .to_buffered(&mut bytes)
+        .map_err(IOError::from)?;
+    Ok(bytes.into_inner())
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod test {
+    use super::*;
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("three-d-{}-{}", std::process::id(), name))
+    }
+
+    fn load_exr(path: &Path) -> CPUTexture<f32> {
+        let mut loaded = Loaded::new();
+        loaded.insert_bytes(path, std::fs::read(path).unwrap());
+        std::fs::remove_file(path).unwrap();
+        loaded.exr_image(path).unwrap()
+    }
+
+    #[test]
+    pub fn exr_texture_round_trip() {
+        let data = vec![
+            0.0f32, 0.5, 1.0, 1.0, 2.0, 4.0, 8.0, 0.5, 100.0, 0.25, 0.125, 0.0, -1.5, 3.0, 0.75,
+            1.0, 0.0, 0.0, 0.0, 1.0, 10.0, 20.0, 30.0, 0.25,
+        ];
+        let path = temp_path("texture_f32.exr");
+        Saver::save_hdr_texture(
+            &path,
+            &CPUTexture {
+                data: data.clone(),
+                width: 3,
+                height: 2,
+                format: Format::RGBA,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let texture = load_exr(&path);
+        assert_eq!((texture.width, texture.height), (3, 2));
+        assert_eq!(texture.format, Format::RGBA);
+        assert_eq!(texture.data, data);
+
+        // The values are all representable as f16, so the round trip is exact
+        let path = temp_path("texture_f16.exr");
+        Saver::save_hdr_texture(
+            &path,
+            &CPUTexture {
+                data: data.iter().map(|&v| f16::from_f32(v)).collect(),
+                width: 2,
+                height: 3,
+                format: Format::RGBA,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let texture = load_exr(&path);
+        assert_eq!((texture.width, texture.height), (2, 3));
+        assert_eq!(texture.data, data);
+
+        // Missing channels are zero, except alpha which is one
+        let path = temp_path("texture_r.exr");
+        Saver::save_hdr_texture(
+            &path,
+            &CPUTexture {
+                data: vec![f16::from_f32(2.0), f16::from_f32(0.5)],
+                width: 2,
+                height: 1,
+                format: Format::R,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            load_exr(&path).data,
+            vec![2.0, 2.0, 2.0, 1.0, 0.5, 0.5, 0.5, 1.0]
+        );
+    }
+
+    #[test]
+    pub fn exr_pixels_round_trip() {
+        // The pixels are given from the bottom row to the top row, so the rows are flipped when loaded
+        let path = temp_path("pixels_f32.exr");
+        Saver::save_hdr_pixels(&path, &[1.0f32, 2.0, 3.0, 0.5, 4.0, 5.0, 6.0, 0.25], 1, 2).unwrap();
+        let texture = load_exr(&path);
+        assert_eq!((texture.width, texture.height), (1, 2));
+        assert_eq!(texture.data, vec![4.0, 5.0, 6.0, 0.25, 1.0, 2.0, 3.0, 0.5]);
+    }
+
+    #[test]
+    pub fn invalid_exr() {
+        let mut loaded = Loaded::new();
+        loaded.insert_bytes("invalid.exr", vec![0x76, 0x2f, 0x31, 0x01, 0, 0]);
+        assert!(loaded.exr_image("invalid.exr").is_err());
+    }
+}
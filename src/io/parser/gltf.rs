@@ -1,219 +1,734 @@
 use crate::core::*;
 use crate::io::*;
 use ::gltf::Gltf;
+use serde_json::json;
 use std::path::Path;
 
 impl Loaded {
     ///
     /// Deserialize a loaded .gltf file and related .bin resource file and related texture resources or a loaded .glb file into a list of meshes and materials.
+    /// The transformations of the nodes in the scene hierarchy are baked into the meshes, see [CPUScene::flatten].
+    /// Use [gltf_scene](Self::gltf_scene) instead to keep the scene hierarchy.
     /// It uses the [gltf](https://crates.io/crates/gltf/main.rs) crate.
     ///
     pub fn gltf(
         &mut self,
         path: impl AsRef<Path>,
     ) -> ThreeDResult<(Vec<CPUMesh>, Vec<CPUMaterial>)> {
+        let (cpu_scene, cpu_meshes, cpu_materials) = self.gltf_scene(path)?;
+        Ok((cpu_scene.flatten(&cpu_meshes), cpu_materials))
+    }
+
+    ///
+    /// Deserialize a loaded .gltf file and related .bin resource file and related texture resources or a loaded .glb file into a scene hierarchy and a list of meshes and materials.
+    /// The nodes in the [CPUScene] contain the local transformations and refer to the meshes and materials by their index in the returned lists.
+    /// Each mesh is only returned once, even if it is referenced by several nodes.
+    /// The key frame animations in the file are available in [CPUScene::animations] and the skins in [CPUScene::skins].
+    /// The punctual lights (`KHR_lights_punctual`) and cameras are available in [CPUScene::lights] and [CPUScene::cameras]
+    /// with their world transformations and can be instantiated using for example [PointLight::from_cpu_light](crate::PointLight::from_cpu_light) and [Camera::from_cpu_camera].
+    /// It uses the [gltf](https://crates.io/crates/gltf/main.rs) crate.
+    ///
+    pub fn gltf_scene(
+        &mut self,
+        path: impl AsRef<Path>,
+    ) -> ThreeDResult<(CPUScene, Vec<CPUMesh>, Vec<CPUMaterial>)> {
         let mut cpu_meshes = Vec::new();
         let mut cpu_materials = Vec::new();
 
-        let Gltf { document, mut blob } = Gltf::from_slice(self.get_bytes(path.as_ref())?)?;
-        let base_path = path.as_ref().parent().unwrap();
+        let corrupt = |message: String| {
+            IOError::Corrupt(path.as_ref().to_string_lossy().to_string(), message)
+        };
+        let bytes = self.get_bytes(path.as_ref())?;
+        let Gltf { document, mut blob } =
+            Gltf::from_slice(bytes).map_err(|e| corrupt(e.to_string()))?;
+        let emissive_strengths = if document
+            .extensions_used()
+            .any(|extension| extension == "KHR_materials_emissive_strength")
+        {
+            parse_emissive_strengths(bytes).map_err(corrupt)?
+        } else {
+            Vec::new()
+        };
+        let base_path = path.as_ref().parent().unwrap_or(Path::new(""));
         let mut buffers = Vec::new();
         for buffer in document.buffers() {
-            let mut data = match buffer.source() {
-                ::gltf::buffer::Source::Uri(uri) => self.remove_bytes(base_path.join(uri))?,
-                ::gltf::buffer::Source::Bin => blob.take().ok_or(IOError::GltfMissingData)?,
+            let (mut data, buffer_path) = match buffer.source() {
+                ::gltf::buffer::Source::Uri(uri) => {
+                    let buffer_path = base_path.join(uri);
+                    (self.remove_bytes(&buffer_path)?, buffer_path)
+                }
+                ::gltf::buffer::Source::Bin => (
+                    blob.take()
+                        .ok_or_else(|| corrupt("the binary chunk is missing".to_owned()))?,
+                    path.as_ref().to_path_buf(),
+                ),
             };
             if data.len() < buffer.length() {
-                Err(IOError::GltfCorruptData)?;
+                Err(IOError::Corrupt(
+                    buffer_path.to_string_lossy().to_string(),
+                    format!(
+                        "buffer {} contains {} bytes but should contain {} bytes",
+                        buffer.index(),
+                        data.len(),
+                        buffer.length()
+                    ),
+                ))?;
             }
             while data.len() % 4 != 0 {
                 data.push(0);
             }
             buffers.push(::gltf::buffer::Data(data));
         }
+        validate_accessors(&document, &buffers).map_err(corrupt)?;
+
+        let mut mesh_primitives = Vec::new();
+        for mesh in document.meshes() {
+            mesh_primitives.push(parse_mesh(
+                &mesh,
+                self,
+                path.as_ref(),
+                &buffers,
+                &emissive_strengths,
+                &mut cpu_meshes,
+                &mut cpu_materials,
+            )?);
+        }
 
-        for scene in document.scenes() {
+        let scene = document
+            .default_scene()
+            .or_else(|| document.scenes().next());
+        let mut cpu_scene = CPUScene {
+            name: scene
+                .as_ref()
+                .and_then(|scene| scene.name())
+                .unwrap_or("")
+                .to_string(),
+            ..Default::default()
+        };
+        for node in document.nodes() {
+            let (meshes, materials) = node
+                .mesh()
+                .map(|mesh| mesh_primitives[mesh.index()].clone())
+                .unwrap_or_default()
+                .into_iter()
+                .unzip();
+            cpu_scene.nodes.push(CPUNode {
+                name: node
+                    .name()
+                    .map(|s| s.to_string())
+                    .unwrap_or(format!("index {}", node.index())),
+                transformation: node.transform().matrix().into(),
+                children: node.children().map(|child| child.index()).collect(),
+                meshes,
+                materials,
+                skin: node.skin().map(|skin| skin.index()),
+                morph_weights: node
+                    .weights()
+                    .or(node.mesh().and_then(|mesh| mesh.weights()))
+                    .map(|weights| weights.to_vec())
+                    .unwrap_or_default(),
+                ..Default::default()
+            });
+        }
+        for index in 0..cpu_scene.nodes.len() {
+            for child in cpu_scene.nodes[index].children.clone() {
+                if cpu_scene.nodes[child].parent.is_some() {
+                    Err(corrupt(format!("node {} has more than one parent", child)))?;
+                }
+                cpu_scene.nodes[child].parent = Some(index);
+            }
+        }
+        // Each node has at most one parent, so the nodes which cannot be reached from a node without a parent are part of a cycle or below one
+        let mut reachable = vec![false; cpu_scene.nodes.len()];
+        let mut stack = (0..cpu_scene.nodes.len())
+            .filter(|index| cpu_scene.nodes[*index].parent.is_none())
+            .collect::<Vec<_>>();
+        while let Some(index) = stack.pop() {
+            reachable[index] = true;
+            stack.extend_from_slice(&cpu_scene.nodes[index].children);
+        }
+        if let Some(mut index) = reachable.iter().position(|reachable| !reachable) {
+            // Walk up the hierarchy until a node is visited twice, that node is part of the cycle
+            let mut visited = vec![false; cpu_scene.nodes.len()];
+            while !visited[index] {
+                visited[index] = true;
+                index = cpu_scene.nodes[index].parent.unwrap();
+            }
+            Err(corrupt(format!("node {} is its own ancestor", index)))?;
+        }
+        if let Some(scene) = scene {
+            let mut is_root = vec![false; cpu_scene.nodes.len()];
             for node in scene.nodes() {
-                parse_tree(
-                    &node,
-                    self,
-                    &base_path,
-                    &buffers,
-                    &mut cpu_meshes,
-                    &mut cpu_materials,
-                )?;
+                if cpu_scene.nodes[node.index()].parent.is_some() {
+                    Err(corrupt(format!(
+                        "the root node {} of the scene has a parent",
+                        node.index()
+                    )))?;
+                }
+                if !is_root[node.index()] {
+                    is_root[node.index()] = true;
+                    cpu_scene.roots.push(node.index());
+                }
             }
         }
-        Ok((cpu_meshes, cpu_materials))
+        for node in document.nodes() {
+            if let Some(light) = node.light() {
+                cpu_scene.lights.push(CPULight {
+                    name: light
+                        .name()
+                        .map(|s| s.to_string())
+                        .unwrap_or(format!("index {}", light.index())),
+                    light_type: match light.kind() {
+                        ::gltf::khr_lights_punctual::Kind::Directional => CPULightType::Directional,
+                        ::gltf::khr_lights_punctual::Kind::Point => CPULightType::Point,
+                        ::gltf::khr_lights_punctual::Kind::Spot {
+                            inner_cone_angle,
+                            outer_cone_angle,
+                        } => CPULightType::Spot {
+                            inner_cone_angle: radians(inner_cone_angle),
+                            outer_cone_angle: radians(outer_cone_angle),
+                        },
+                    },
+                    color: Color::from_rgb_slice(&light.color()),
+                    intensity: light.intensity(),
+                    range: light.range(),
+                    node: Some(node.index()),
+                    transformation: cpu_scene.world_transformation(node.index()),
+                });
+            }
+            if let Some(camera) = node.camera() {
+                cpu_scene.cameras.push(CPUCamera {
+                    name: camera
+                        .name()
+                        .map(|s| s.to_string())
+                        .unwrap_or(format!("index {}", camera.index())),
+                    projection: match camera.projection() {
+                        ::gltf::camera::Projection::Orthographic(orthographic) => {
+                            CPUProjection::Orthographic {
+                                height: 2.0 * orthographic.ymag(),
+                                width: 2.0 * orthographic.xmag(),
+                                z_near: orthographic.znear(),
+                                z_far: orthographic.zfar(),
+                            }
+                        }
+                        ::gltf::camera::Projection::Perspective(perspective) => {
+                            CPUProjection::Perspective {
+                                field_of_view_y: radians(perspective.yfov()),
+                                aspect_ratio: perspective.aspect_ratio(),
+                                z_near: perspective.znear(),
+                                z_far: perspective.zfar(),
+                            }
+                        }
+                    },
+                    node: Some(node.index()),
+                    transformation: cpu_scene.world_transformation(node.index()),
+                });
+            }
+        }
+        for skin in document.skins() {
+            let reader = skin.reader(|buffer| Some(&buffers[buffer.index()]));
+            cpu_scene.skins.push(CPUSkin {
+                name: skin
+                    .name()
+                    .map(|s| s.to_string())
+                    .unwrap_or(format!("index {}", skin.index())),
+                joints: skin.joints().map(|joint| joint.index()).collect(),
+                inverse_bind_matrices: reader
+                    .read_inverse_bind_matrices()
+                    .map(|values| values.map(|m| m.into()).collect())
+                    .unwrap_or_default(),
+            });
+        }
+        for animation in document.animations() {
+            cpu_scene
+                .animations
+                .push(parse_animation(&animation, path.as_ref(), &buffers)?);
+        }
+        Ok((cpu_scene, cpu_meshes, cpu_materials))
     }
 }
 
-fn parse_tree<'a>(
-    node: &::gltf::Node,
-    loaded: &mut Loaded,
-    path: &Path,
+///
+/// Checks that the accessors which are read have the expected types and only refer to data inside the buffers,
+/// since the gltf crate panics when reading an accessor which does not.
+///
+fn validate_accessors(
+    document: &::gltf::Document,
     buffers: &[::gltf::buffer::Data],
-    cpu_meshes: &mut Vec<CPUMesh>,
-    cpu_materials: &mut Vec<CPUMaterial>,
-) -> ThreeDResult<()> {
-    if let Some(mesh) = node.mesh() {
-        let name: String = mesh
-            .name()
-            .map(|s| s.to_string())
-            .unwrap_or(format!("index {}", mesh.index()));
+) -> Result<(), String> {
+    use ::gltf::accessor::{DataType::*, Dimensions::*};
+    use ::gltf::animation::Property;
+    use ::gltf::mesh::Semantic;
+    for mesh in document.meshes() {
         for primitive in mesh.primitives() {
-            let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
-            if let Some(read_positions) = reader.read_positions() {
-                let mut positions = Vec::new();
-                for value in read_positions {
-                    positions.push(value[0]);
-                    positions.push(value[1]);
-                    positions.push(value[2]);
-                }
-
-                let normals = reader
-                    .read_normals()
-                    .map(|values| values.flatten().collect::<Vec<_>>());
-
-                let tangents = reader
-                    .read_tangents()
-                    .map(|values| values.flatten().collect::<Vec<_>>());
-
-                let indices = reader.read_indices().map(|values| match values {
-                    ::gltf::mesh::util::ReadIndices::U8(iter) => {
-                        let mut inds = Vec::new();
-                        for value in iter {
-                            inds.push(value);
-                        }
-                        Indices::U8(inds)
+            for (semantic, accessor) in primitive.attributes() {
+                match semantic {
+                    Semantic::Positions | Semantic::Normals => {
+                        validate_accessor(&accessor, buffers, &[F32], &[Vec3])?
                     }
-                    ::gltf::mesh::util::ReadIndices::U16(iter) => {
-                        let mut inds = Vec::new();
-                        for value in iter {
-                            inds.push(value);
-                        }
-                        Indices::U16(inds)
+                    Semantic::Tangents => validate_accessor(&accessor, buffers, &[F32], &[Vec4])?,
+                    Semantic::Colors(0) => {
+                        validate_accessor(&accessor, buffers, &[U8, U16, F32], &[Vec3, Vec4])?
                     }
-                    ::gltf::mesh::util::ReadIndices::U32(iter) => {
-                        let mut inds = Vec::new();
-                        for value in iter {
-                            inds.push(value);
-                        }
-                        Indices::U32(inds)
+                    Semantic::TexCoords(0) => {
+                        validate_accessor(&accessor, buffers, &[U8, U16, F32], &[Vec2])?
                     }
+                    Semantic::Joints(0) => {
+                        validate_accessor(&accessor, buffers, &[U8, U16], &[Vec4])?
+                    }
+                    Semantic::Weights(0) => {
+                        validate_accessor(&accessor, buffers, &[U8, U16, F32], &[Vec4])?
+                    }
+                    _ => {}
+                }
+            }
+            if let Some(accessor) = primitive.indices() {
+                validate_accessor(&accessor, buffers, &[U8, U16, U32], &[Scalar])?;
+            }
+            for target in primitive.morph_targets() {
+                for accessor in target
+                    .positions()
+                    .into_iter()
+                    .chain(target.normals())
+                    .chain(target.tangents())
+                {
+                    validate_accessor(&accessor, buffers, &[F32], &[Vec3])?;
+                }
+            }
+        }
+    }
+    for skin in document.skins() {
+        if let Some(accessor) = skin.inverse_bind_matrices() {
+            validate_accessor(&accessor, buffers, &[F32], &[Mat4])?;
+        }
+    }
+    for animation in document.animations() {
+        for channel in animation.channels() {
+            let sampler = channel.sampler();
+            validate_accessor(&sampler.input(), buffers, &[F32], &[Scalar])?;
+            match channel.target().property() {
+                Property::Translation | Property::Scale => {
+                    validate_accessor(&sampler.output(), buffers, &[F32], &[Vec3])?
+                }
+                Property::Rotation => validate_accessor(
+                    &sampler.output(),
+                    buffers,
+                    &[I8, U8, I16, U16, F32],
+                    &[Vec4],
+                )?,
+                Property::MorphTargetWeights => validate_accessor(
+                    &sampler.output(),
+                    buffers,
+                    &[I8, U8, I16, U16, F32],
+                    &[Scalar],
+                )?,
+            }
+        }
+    }
+    Ok(())
+}
+
+fn validate_accessor(
+    accessor: &::gltf::Accessor,
+    buffers: &[::gltf::buffer::Data],
+    data_types: &[::gltf::accessor::DataType],
+    dimensions: &[::gltf::accessor::Dimensions],
+) -> Result<(), String> {
+    let error = |message: &str| format!("accessor {} {}", accessor.index(), message);
+    if !data_types.contains(&accessor.data_type()) || !dimensions.contains(&accessor.dimensions()) {
+        Err(error("has an unexpected type"))?;
+    }
+    if accessor.count() == 0 {
+        Err(error("is empty"))?;
+    }
+    if accessor.sparse().is_some() {
+        Err(error("is sparse which is not supported"))?;
+    }
+    if let Some(view) = accessor.view() {
+        let buffer_length = view
+            .buffer()
+            .length()
+            .min(buffers[view.buffer().index()].len());
+        if view
+            .offset()
+            .checked_add(view.length())
+            .filter(|end| *end <= buffer_length)
+            .is_none()
+        {
+            Err(error("refers to a buffer view outside of its buffer"))?;
+        }
+        let size = accessor.size();
+        let stride = view.stride().unwrap_or(size);
+        let end = stride
+            .checked_mul(accessor.count() - 1)
+            .and_then(|end| end.checked_add(size))
+            .and_then(|end| end.checked_add(accessor.offset()))
+            .filter(|end| *end <= view.length());
+        if stride < size || end.is_none() {
+            Err(error("refers to data outside of its buffer view"))?;
+        }
+    }
+    Ok(())
+}
+
+fn parse_animation(
+    animation: &::gltf::Animation,
+    path: &Path,
+    buffers: &[::gltf::buffer::Data],
+) -> ThreeDResult<CPUAnimation> {
+    let mut cpu_animation = CPUAnimation {
+        name: animation
+            .name()
+            .map(|s| s.to_string())
+            .unwrap_or(format!("index {}", animation.index())),
+        ..Default::default()
+    };
+    for channel in animation.channels() {
+        if channel.sampler().input().count() == 0 || channel.sampler().output().count() == 0 {
+            Err(IOError::Corrupt(
+                path.to_string_lossy().to_string(),
+                format!(
+                    "animation {} has a sampler without key frames",
+                    animation.index()
+                ),
+            ))?;
+        }
+        let reader = channel.reader(|buffer| Some(&buffers[buffer.index()]));
+        let missing_data = || {
+            IOError::Corrupt(
+                path.to_string_lossy().to_string(),
+                format!("animation {} refers to missing data", animation.index()),
+            )
+        };
+        let times = reader
+            .read_inputs()
+            .ok_or_else(missing_data)?
+            .collect::<Vec<_>>();
+        let interpolation = match channel.sampler().interpolation() {
+            ::gltf::animation::Interpolation::Step => KeyFrameInterpolation::Step,
+            ::gltf::animation::Interpolation::Linear => KeyFrameInterpolation::Linear,
+            ::gltf::animation::Interpolation::CubicSpline => KeyFrameInterpolation::CubicSpline,
+        };
+        let node = channel.target().node().index();
+        let track_index = if let Some(index) = cpu_animation
+            .tracks
+            .iter()
+            .position(|track| track.node == node)
+        {
+            index
+        } else {
+            cpu_animation.tracks.push(CPUAnimationTrack {
+                node,
+                translation: None,
+                rotation: None,
+                scale: None,
+                morph_weights: None,
+            });
+            cpu_animation.tracks.len() - 1
+        };
+        let track = &mut cpu_animation.tracks[track_index];
+        match reader.read_outputs().ok_or_else(missing_data)? {
+            ::gltf::animation::util::ReadOutputs::Translations(values) => {
+                track.translation = Some(key_frames(
+                    animation,
+                    path,
+                    interpolation,
+                    times,
+                    values.map(|v| vec3(v[0], v[1], v[2])).collect(),
+                )?);
+            }
+            ::gltf::animation::util::ReadOutputs::Rotations(values) => {
+                track.rotation = Some(key_frames(
+                    animation,
+                    path,
+                    interpolation,
+                    times,
+                    values
+                        .into_f32()
+                        .map(|v| Quat::new(v[3], v[0], v[1], v[2]))
+                        .collect(),
+                )?);
+            }
+            ::gltf::animation::util::ReadOutputs::Scales(values) => {
+                track.scale = Some(key_frames(
+                    animation,
+                    path,
+                    interpolation,
+                    times,
+                    values.map(|v| vec3(v[0], v[1], v[2])).collect(),
+                )?);
+            }
+            ::gltf::animation::util::ReadOutputs::MorphTargetWeights(values) => {
+                let values = values.into_f32().collect::<Vec<_>>();
+                let value_count = if interpolation == KeyFrameInterpolation::CubicSpline {
+                    3 * times.len()
+                } else {
+                    times.len()
+                };
+                if values.is_empty() || value_count == 0 || values.len() % value_count != 0 {
+                    Err(IOError::Corrupt(
+                        path.to_string_lossy().to_string(),
+                        format!(
+                            "animation {} has {} key frames but {} morph target weights",
+                            animation.index(),
+                            times.len(),
+                            values.len()
+                        ),
+                    ))?;
+                }
+                track.morph_weights = Some(KeyFrames {
+                    interpolation,
+                    times,
+                    values: values
+                        .chunks(values.len() / value_count)
+                        .map(|weights| weights.to_vec())
+                        .collect(),
                 });
+            }
+        }
+    }
+    Ok(cpu_animation)
+}
 
-                let material = primitive.material();
-                let material_name: String = material.name().map(|s| s.to_string()).unwrap_or(
-                    material
-                        .index()
-                        .map(|i| format!("index {}", i))
-                        .unwrap_or("default".to_string()),
-                );
-                let mut parsed = false;
-                for material in cpu_materials.iter() {
-                    if material.name == material_name {
-                        parsed = true;
-                        break;
+fn key_frames<T>(
+    animation: &::gltf::Animation,
+    path: &Path,
+    interpolation: KeyFrameInterpolation,
+    times: Vec<f32>,
+    values: Vec<T>,
+) -> ThreeDResult<KeyFrames<T>> {
+    let value_count = if interpolation == KeyFrameInterpolation::CubicSpline {
+        3 * times.len()
+    } else {
+        times.len()
+    };
+    if times.is_empty() || values.len() != value_count {
+        Err(IOError::Corrupt(
+            path.to_string_lossy().to_string(),
+            format!(
+                "animation {} has {} key frames but {} output values",
+                animation.index(),
+                times.len(),
+                values.len()
+            ),
+        ))?;
+    }
+    Ok(KeyFrames {
+        interpolation,
+        times,
+        values,
+    })
+}
+
+fn parse_mesh(
+    mesh: &::gltf::Mesh,
+    loaded: &mut Loaded,
+    path: &Path,
+    buffers: &[::gltf::buffer::Data],
+    emissive_strengths: &[f32],
+    cpu_meshes: &mut Vec<CPUMesh>,
+    cpu_materials: &mut Vec<CPUMaterial>,
+) -> ThreeDResult<Vec<(usize, Option<usize>)>> {
+    let mut primitives = Vec::new();
+    let name: String = mesh
+        .name()
+        .map(|s| s.to_string())
+        .unwrap_or(format!("index {}", mesh.index()));
+    for primitive in mesh.primitives() {
+        let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+        if let Some(read_positions) = reader.read_positions() {
+            let mut positions = Vec::new();
+            for value in read_positions {
+                positions.push(value[0]);
+                positions.push(value[1]);
+                positions.push(value[2]);
+            }
+
+            let normals = reader
+                .read_normals()
+                .map(|values| values.flatten().collect::<Vec<_>>());
+
+            let tangents = reader
+                .read_tangents()
+                .map(|values| values.flatten().collect::<Vec<_>>());
+
+            let indices = reader.read_indices().map(|values| match values {
+                ::gltf::mesh::util::ReadIndices::U8(iter) => {
+                    let mut inds = Vec::new();
+                    for value in iter {
+                        inds.push(value);
                     }
+                    Indices::U8(inds)
                 }
+                ::gltf::mesh::util::ReadIndices::U16(iter) => {
+                    let mut inds = Vec::new();
+                    for value in iter {
+                        inds.push(value);
+                    }
+                    Indices::U16(inds)
+                }
+                ::gltf::mesh::util::ReadIndices::U32(iter) => {
+                    let mut inds = Vec::new();
+                    for value in iter {
+                        inds.push(value);
+                    }
+                    Indices::U32(inds)
+                }
+            });
 
-                if !parsed {
-                    let pbr = material.pbr_metallic_roughness();
-                    let color = pbr.base_color_factor();
-                    let albedo_texture = if let Some(info) = pbr.base_color_texture() {
-                        Some(parse_texture(loaded, path, buffers, info.texture())?)
+            let material = primitive.material();
+            let material_name: String = material.name().map(|s| s.to_string()).unwrap_or(
+                material
+                    .index()
+                    .map(|i| format!("index {}", i))
+                    .unwrap_or("default".to_string()),
+            );
+            let material_index = if let Some(index) = cpu_materials
+                .iter()
+                .position(|material| material.name == material_name)
+            {
+                index
+            } else {
+                let pbr = material.pbr_metallic_roughness();
+                let color = pbr.base_color_factor();
+                let (albedo_texture, texture_transform) =
+                    if let Some(info) = pbr.base_color_texture() {
+                        (
+                            Some(parse_texture(loaded, path, buffers, info.texture())?),
+                            info.texture_transform()
+                                .map(|transform| parse_texture_transform(&transform))
+                                .unwrap_or(Mat3::identity()),
+                        )
                     } else {
-                        None
+                        (None, Mat3::identity())
                     };
-                    let metallic_roughness_texture =
-                        if let Some(info) = pbr.metallic_roughness_texture() {
-                            Some(parse_texture(loaded, path, buffers, info.texture())?)
-                        } else {
-                            None
-                        };
-                    let (normal_texture, normal_scale) =
-                        if let Some(normal) = material.normal_texture() {
-                            (
-                                Some(parse_texture(loaded, path, buffers, normal.texture())?),
-                                normal.scale(),
-                            )
-                        } else {
-                            (None, 1.0)
-                        };
-                    let (occlusion_texture, occlusion_strength) =
-                        if let Some(occlusion) = material.occlusion_texture() {
-                            (
-                                Some(parse_texture(loaded, path, buffers, occlusion.texture())?),
-                                occlusion.strength(),
-                            )
-                        } else {
-                            (None, 1.0)
-                        };
-                    let emissive_texture = if let Some(info) = material.emissive_texture() {
+                let metallic_roughness_texture =
+                    if let Some(info) = pbr.metallic_roughness_texture() {
                         Some(parse_texture(loaded, path, buffers, info.texture())?)
                     } else {
                         None
                     };
-                    cpu_materials.push(CPUMaterial {
-                        name: material_name.clone(),
-                        albedo: Color::from_rgba_slice(&color),
-                        albedo_texture,
-                        metallic: pbr.metallic_factor(),
-                        roughness: pbr.roughness_factor(),
-                        metallic_roughness_texture,
-                        normal_texture,
-                        normal_scale,
-                        occlusion_texture,
-                        occlusion_strength,
-                        occlusion_metallic_roughness_texture: None,
-                        emissive: Color::from_rgb_slice(&material.emissive_factor()),
-                        emissive_texture,
-                        alpha_cutout: None,
-                        lighting_model: LightingModel::Cook(
-                            NormalDistributionFunction::TrowbridgeReitzGGX,
-                            GeometryFunction::SmithSchlickGGX,
-                        ),
-                    });
+                let (normal_texture, normal_scale) = if let Some(normal) = material.normal_texture()
+                {
+                    (
+                        Some(parse_texture(loaded, path, buffers, normal.texture())?),
+                        normal.scale(),
+                    )
+                } else {
+                    (None, 1.0)
+                };
+                let (occlusion_texture, occlusion_strength) =
+                    if let Some(occlusion) = material.occlusion_texture() {
+                        (
+                            Some(parse_texture(loaded, path, buffers, occlusion.texture())?),
+                            occlusion.strength(),
+                        )
+                    } else {
+                        (None, 1.0)
+                    };
+                let emissive_texture = if let Some(info) = material.emissive_texture() {
+                    Some(parse_texture(loaded, path, buffers, info.texture())?)
+                } else {
+                    None
+                };
+                cpu_materials.push(CPUMaterial {
+                    name: material_name.clone(),
+                    albedo: Color::from_rgba_slice(&color),
+                    albedo_texture,
+                    metallic: pbr.metallic_factor(),
+                    roughness: pbr.roughness_factor(),
+                    metallic_roughness_texture,
+                    normal_texture,
+                    normal_scale,
+                    occlusion_texture,
+                    occlusion_strength,
+                    occlusion_metallic_roughness_texture: None,
+                    emissive: Color::from_rgb_slice(&material.emissive_factor()),
+                    emissive_texture,
+                    emissive_strength: material
+                        .index()
+                        .and_then(|index| emissive_strengths.get(index))
+                        .cloned()
+                        .unwrap_or(1.0),
+                    alpha_cutout: match material.alpha_mode() {
+                        ::gltf::material::AlphaMode::Mask => {
+                            Some(material.alpha_cutoff().unwrap_or(0.5))
+                        }
+                        _ => None,
+                    },
+                    alpha_mode: match material.alpha_mode() {
+                        ::gltf::material::AlphaMode::Opaque => AlphaMode::Opaque,
+                        ::gltf::material::AlphaMode::Mask => AlphaMode::Mask,
+                        ::gltf::material::AlphaMode::Blend => AlphaMode::Blend,
+                    },
+                    double_sided: material.double_sided(),
+                    unlit: material.unlit(),
+                    texture_transform,
+                    lighting_model: LightingModel::Cook(
+                        NormalDistributionFunction::TrowbridgeReitzGGX,
+                        GeometryFunction::SmithSchlickGGX,
+                    ),
+                });
+                cpu_materials.len() - 1
+            };
+
+            let colors = reader.read_colors(0).map(|values| {
+                let mut cols = Vec::new();
+                for value in values.into_rgba_u8() {
+                    cols.push(value[0]);
+                    cols.push(value[1]);
+                    cols.push(value[2]);
+                    cols.push(value[3]);
                 }
+                cols
+            });
 
-                let colors = reader.read_colors(0).map(|values| {
-                    let mut cols = Vec::new();
-                    for value in values.into_rgb_u8() {
-                        cols.push(value[0]);
-                        cols.push(value[1]);
-                        cols.push(value[2]);
-                    }
-                    cols
-                });
+            let uvs = reader.read_tex_coords(0).map(|values| {
+                let mut uvs = Vec::new();
+                for value in values.into_f32() {
+                    uvs.push(value[0]);
+                    uvs.push(value[1]);
+                }
+                uvs
+            });
 
-                let uvs = reader.read_tex_coords(0).map(|values| {
-                    let mut uvs = Vec::new();
-                    for value in values.into_f32() {
-                        uvs.push(value[0]);
-                        uvs.push(value[1]);
-                    }
-                    uvs
-                });
+            let joints = reader
+                .read_joints(0)
+                .map(|values| values.into_u16().flatten().collect::<Vec<_>>());
 
-                cpu_meshes.push(CPUMesh {
-                    name: name.clone(),
-                    positions,
-                    normals,
-                    tangents,
-                    indices,
-                    colors,
-                    uvs,
-                    material_name: Some(material_name),
-                });
-            }
-        }
-    }
+            let weights = reader
+                .read_weights(0)
+                .map(|values| values.into_f32().flatten().collect::<Vec<_>>());
 
-    for child in node.children() {
-        parse_tree(&child, loaded, path, buffers, cpu_meshes, cpu_materials)?;
+            let vertex_count = positions.len() / 3;
+            let morph_targets = reader
+                .read_morph_targets()
+                .map(|(positions, normals, tangents)| MorphTarget {
+                    positions: positions
+                        .map(|values| values.flatten().collect::<Vec<_>>())
+                        .unwrap_or_else(|| vec![0.0; vertex_count * 3]),
+                    normals: normals.map(|values| values.flatten().collect::<Vec<_>>()),
+                    tangents: tangents.map(|values| values.flatten().collect::<Vec<_>>()),
+                })
+                .collect::<Vec<_>>();
+
+            primitives.push((cpu_meshes.len(), Some(material_index)));
+            cpu_meshes.push(CPUMesh {
+                name: name.clone(),
+                positions,
+                normals,
+                tangents,
+                indices,
+                colors,
+                uvs,
+                joints,
+                weights,
+                morph_targets,
+                material_name: Some(material_name),
+            });
+        }
     }
-    Ok(())
+    Ok(primitives)
 }
 
 fn parse_texture<'a>(
@@ -224,21 +739,1071 @@ fn parse_texture<'a>(
 ) -> ThreeDResult<CPUTexture<u8>> {
     let gltf_image = gltf_texture.source();
     let gltf_source = gltf_image.source();
-    let tex = match gltf_source {
-        ::gltf::image::Source::Uri { uri, .. } => loaded.image(path.join(Path::new(uri)))?,
+    let mut tex = match gltf_source {
+        ::gltf::image::Source::Uri { uri, .. } => {
+            loaded.image(path.parent().unwrap_or(Path::new("")).join(Path::new(uri)))?
+        }
         ::gltf::image::Source::View { view, .. } => {
-            let mut bytes = Vec::with_capacity(view.length());
-            bytes.extend(
-                (0..view.length())
-                    .map(|i| buffers[view.buffer().index()][view.offset() + i])
-                    .into_iter(),
-            );
-            if view.stride() != None {
-                unimplemented!();
-            }
-            image_from_bytes(&bytes)?
+            // The encoded image is a contiguous sequence of bytes, so a byte stride, which is only meaningful
+            // for vertex attributes, does not change which bytes belong to the image.
+            let corrupt =
+                |message: String| IOError::Corrupt(path.to_string_lossy().to_string(), message);
+            let buffer = &buffers[view.buffer().index()];
+            let bytes = view
+                .offset()
+                .checked_add(view.length())
+                .and_then(|end| buffer.get(view.offset()..end))
+                .ok_or_else(|| {
+                    corrupt(format!(
+                        "image {} refers to data outside of the buffer",
+                        gltf_image.index()
+                    ))
+                })?;
+            image_from_bytes(bytes)
+                .map_err(|e| corrupt(format!("image {} is corrupt: {}", gltf_image.index(), e)))?
         }
     };
-    // TODO: Parse sampling parameters
+    let sampler = gltf_texture.sampler();
+    if let Some(filter) = sampler.mag_filter() {
+        tex.mag_filter = match filter {
+            ::gltf::texture::MagFilter::Nearest => Interpolation::Nearest,
+            ::gltf::texture::MagFilter::Linear => Interpolation::Linear,
+        };
+    }
+    if let Some(filter) = sampler.min_filter() {
+        let (min_filter, mip_map_filter) = match filter {
+            ::gltf::texture::MinFilter::Nearest => (Interpolation::Nearest, None),
+            ::gltf::texture::MinFilter::Linear => (Interpolation::Linear, None),
+            ::gltf::texture::MinFilter::NearestMipmapNearest => {
+                (Interpolation::Nearest, Some(Interpolation::Nearest))
+            }
+            ::gltf::texture::MinFilter::LinearMipmapNearest => {
+                (Interpolation::Linear, Some(Interpolation::Nearest))
+            }
+            ::gltf::texture::MinFilter::NearestMipmapLinear => {
+                (Interpolation::Nearest, Some(Interpolation::Linear))
+            }
+            ::gltf::texture::MinFilter::LinearMipmapLinear => {
+                (Interpolation::Linear, Some(Interpolation::Linear))
+            }
+        };
+        tex.min_filter = min_filter;
+        tex.mip_map_filter = mip_map_filter;
+    }
+    tex.wrap_s = parse_wrapping(sampler.wrap_s());
+    tex.wrap_t = parse_wrapping(sampler.wrap_t());
     Ok(tex)
 }
+
+fn parse_texture_transform(transform: &::gltf::texture::TextureTransform) -> Mat3 {
+    let [x, y] = transform.offset();
+    let [sx, sy] = transform.scale();
+    let (sin, cos) = transform.rotation().sin_cos();
+    Mat3::new(1.0, 0.0, 0.0, 0.0, 1.0, 0.0, x, y, 1.0)
+        * Mat3::new(cos, -sin, 0.0, sin, cos, 0.0, 0.0, 0.0, 1.0)
+        * Mat3::new(sx, 0.0, 0.0, 0.0, sy, 0.0, 0.0, 0.0, 1.0)
+}
+
+fn parse_emissive_strengths(bytes: &[u8]) -> Result<Vec<f32>, String> {
+    // The emissive strength extension is not supported by the gltf crate, so it is read directly from the json.
+    let json = if bytes.starts_with(b"glTF") {
+        ::gltf::Glb::from_slice(bytes)
+            .map_err(|e| e.to_string())?
+            .json
+    } else {
+        std::borrow::Cow::Borrowed(bytes)
+    };
+    let root: serde_json::Value = serde_json::from_slice(&json).map_err(|e| e.to_string())?;
+    Ok(root["materials"]
+        .as_array()
+        .map(|materials| {
+            materials
+                .iter()
+                .map(|material| {
+                    material["extensions"]["KHR_materials_emissive_strength"]["emissiveStrength"]
+                        .as_f64()
+                        .unwrap_or(1.0) as f32
+                })
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+fn parse_wrapping(wrapping: ::gltf::texture::WrappingMode) -> Wrapping {
+    match wrapping {
+        ::gltf::texture::WrappingMode::ClampToEdge => Wrapping::ClampToEdge,
+        ::gltf::texture::WrappingMode::MirroredRepeat => Wrapping::MirroredRepeat,
+        ::gltf::texture::WrappingMode::Repeat => Wrapping::Repeat,
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+const ARRAY_BUFFER: u32 = 34962;
+#[cfg(not(target_arch = "wasm32"))]
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+#[cfg(not(target_arch = "wasm32"))]
+const UNSIGNED_BYTE: u32 = 5121;
+#[cfg(not(target_arch = "wasm32"))]
+const UNSIGNED_SHORT: u32 = 5123;
+#[cfg(not(target_arch = "wasm32"))]
+const UNSIGNED_INT: u32 = 5125;
+#[cfg(not(target_arch = "wasm32"))]
+const FLOAT: u32 = 5126;
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Saver {
+    ///
+    /// Saves the given meshes and materials as a .gltf file at the given path and a .bin file with the same name next to it.
+    /// The .bin file contains the mesh data and the textures of the materials encoded as PNG.
+    /// Each mesh is saved with its own node in the scene and the materials are matched with the meshes by [CPUMesh::material_name].
+    ///
+    pub fn save_gltf(
+        path: impl AsRef<Path>,
+        cpu_meshes: &[CPUMesh],
+        cpu_materials: &[CPUMaterial],
+    ) -> ThreeDResult<()> {
+        let bin_path = path.as_ref().with_extension("bin");
+        let bin_uri = bin_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or("buffer.bin".to_string());
+        let (json, bin) = Self::serialize_gltf(cpu_meshes, cpu_materials, &bin_uri)?;
+        Self::save_file(path, &json)?;
+        if !bin.is_empty() {
+            Self::save_file(bin_path, &bin)?;
+        }
+        Ok(())
+    }
+
+    ///
+    /// Saves the given meshes and materials as a binary .glb file at the given path.
+    /// The textures of the materials are encoded as PNG and embedded in the file.
+    /// Each mesh is saved with its own node in the scene and the materials are matched with the meshes by [CPUMesh::material_name].
+    ///
+    pub fn save_glb(
+        path: impl AsRef<Path>,
+        cpu_meshes: &[CPUMesh],
+        cpu_materials: &[CPUMaterial],
+    ) -> ThreeDResult<()> {
+        Self::save_file(path, &Self::serialize_glb(cpu_meshes, cpu_materials)?)
+    }
+
+    ///
+    /// Serializes the given meshes and materials into the contents of a .gltf file and the related .bin file, see [save_gltf](Self::save_gltf).
+    /// The .gltf file refers to the .bin file using the given uri.
+    /// The bytes can be deserialized again by inserting them into a [Loaded] using [Loaded::insert_bytes] and calling [Loaded::gltf].
+    ///
+    pub fn serialize_gltf(
+        cpu_meshes: &[CPUMesh],
+        cpu_materials: &[CPUMaterial],
+        bin_uri: &str,
+    ) -> ThreeDResult<(Vec<u8>, Vec<u8>)> {
+        let (root, bin) = gltf_json(cpu_meshes, cpu_materials, Some(bin_uri))?;
+        let json = serde_json::to_vec_pretty(&root).map_err(IOError::from)?;
+        Ok((json, bin))
+    }
+
+    ///
+    /// Serializes the given meshes and materials into the contents of a .glb file, see [save_glb](Self::save_glb).
+    /// The bytes can be deserialized again by inserting them into a [Loaded] using [Loaded::insert_bytes] and calling [Loaded::gltf].
+    ///
+    pub fn serialize_glb(
+        cpu_meshes: &[CPUMesh],
+        cpu_materials: &[CPUMaterial],
+    ) -> ThreeDResult<Vec<u8>> {
+        let (root, mut bin) = gltf_json(cpu_meshes, cpu_materials, None)?;
+        let mut json = serde_json::to_vec(&root).map_err(IOError::from)?;
+        json.resize(json.len().div_ceil(4) * 4, b' ');
+        bin.resize(bin.len().div_ceil(4) * 4, 0);
+        let mut length = 12 + 8 + json.len();
+        if !bin.is_empty() {
+            length += 8 + bin.len();
+        }
+        let mut bytes = Vec::with_capacity(length);
+        bytes.extend_from_slice(b"glTF");
+        bytes.extend_from_slice(&2u32.to_le_bytes());
+        bytes.extend_from_slice(&(length as u32).to_le_bytes());
+        bytes.extend_from_slice(&(json.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(b"JSON");
+        bytes.extend_from_slice(&json);
+        if !bin.is_empty() {
+            bytes.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(b"BIN\0");
+            bytes.extend_from_slice(&bin);
+        }
+        Ok(bytes)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn gltf_json(
+    cpu_meshes: &[CPUMesh],
+    cpu_materials: &[CPUMaterial],
+    bin_uri: Option<&str>,
+) -> ThreeDResult<(serde_json::Value, Vec<u8>)> {
+    let mut builder = GltfBuilder::default();
+    let mut materials = Vec::new();
+    for cpu_material in cpu_materials {
+        materials.push(builder.material(cpu_material)?);
+    }
+    let mut meshes = Vec::new();
+    let mut nodes = Vec::new();
+    for cpu_mesh in cpu_meshes {
+        let material = cpu_mesh.material_name.as_ref().and_then(|name| {
+            cpu_materials
+                .iter()
+                .position(|material| &material.name == name)
+        });
+        nodes.push(json!({ "name": cpu_mesh.name, "mesh": meshes.len() }));
+        meshes.push(builder.mesh(cpu_mesh, material));
+    }
+
+    let mut root = json!({
+        "asset": { "version": "2.0", "generator": "three-d" },
+        "scene": 0,
+        "scenes": [{}],
+    });
+    if !nodes.is_empty() {
+        root["scenes"][0]["nodes"] = json!((0..nodes.len()).collect::<Vec<_>>());
+        root["nodes"] = json!(nodes);
+        root["meshes"] = json!(meshes);
+    }
+    if !materials.is_empty() {
+        root["materials"] = json!(materials);
+    }
+    if !builder.bin.is_empty() {
+        let mut buffer = json!({ "byteLength": builder.bin.len() });
+        if let Some(uri) = bin_uri {
+            buffer["uri"] = json!(uri);
+        }
+        root["buffers"] = json!([buffer]);
+        root["bufferViews"] = json!(builder.buffer_views);
+        root["accessors"] = json!(builder.accessors);
+    }
+    if !builder.textures.is_empty() {
+        root["images"] = json!(builder.images);
+        root["samplers"] = json!(builder.samplers);
+        root["textures"] = json!(builder.textures);
+    }
+    if !builder.extensions_used.is_empty() {
+        root["extensionsUsed"] = json!(builder.extensions_used);
+    }
+    Ok((root, builder.bin))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Default)]
+struct GltfBuilder<'a> {
+    bin: Vec<u8>,
+    buffer_views: Vec<serde_json::Value>,
+    accessors: Vec<serde_json::Value>,
+    images: Vec<serde_json::Value>,
+    /// The texture each image is created from, which is used to write each image only once.
+    image_textures: Vec<&'a CPUTexture<u8>>,
+    samplers: Vec<serde_json::Value>,
+    textures: Vec<serde_json::Value>,
+    extensions_used: Vec<&'static str>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<'a> GltfBuilder<'a> {
+    fn mesh(&mut self, cpu_mesh: &CPUMesh, material: Option<usize>) -> serde_json::Value {
+        let mut attributes = json!({
+            "POSITION": self.accessor_f32(&cpu_mesh.positions, 3, true),
+        });
+        if let Some(ref normals) = cpu_mesh.normals {
+            attributes["NORMAL"] = json!(self.accessor_f32(normals, 3, false));
+        }
+        if let Some(ref tangents) = cpu_mesh.tangents {
+            attributes["TANGENT"] = json!(self.accessor_f32(tangents, 4, false));
+        }
+        if let Some(ref uvs) = cpu_mesh.uvs {
+            attributes["TEXCOORD_0"] = json!(self.accessor_f32(uvs, 2, false));
+        }
+        if let Some(ref colors) = cpu_mesh.colors {
+            let view = self.buffer_view(colors, Some(ARRAY_BUFFER));
+            attributes["COLOR_0"] =
+                json!(self.accessor(view, UNSIGNED_BYTE, colors.len() / 4, 4, true));
+        }
+        if let Some(ref joints) = cpu_mesh.joints {
+            let mut bytes = Vec::with_capacity(joints.len() * 2);
+            for joint in joints {
+                bytes.extend_from_slice(&joint.to_le_bytes());
+            }
+            let view = self.buffer_view(&bytes, Some(ARRAY_BUFFER));
+            attributes["JOINTS_0"] =
+                json!(self.accessor(view, UNSIGNED_SHORT, joints.len() / 4, 4, false));
+        }
+        if let Some(ref weights) = cpu_mesh.weights {
+            attributes["WEIGHTS_0"] = json!(self.accessor_f32(weights, 4, false));
+        }
+        let mut primitive = json!({ "attributes": attributes });
+        if let Some(ref indices) = cpu_mesh.indices {
+            let (bytes, component_type, count) = match indices {
+                Indices::U8(indices) => (indices.clone(), UNSIGNED_BYTE, indices.len()),
+                Indices::U16(indices) => {
+                    let mut bytes = Vec::with_capacity(indices.len() * 2);
+                    for index in indices {
+                        bytes.extend_from_slice(&index.to_le_bytes());
+                    }
+                    (bytes, UNSIGNED_SHORT, indices.len())
+                }
+                Indices::U32(indices) => {
+                    let mut bytes = Vec::with_capacity(indices.len() * 4);
+                    for index in indices {
+                        bytes.extend_from_slice(&index.to_le_bytes());
+                    }
+                    (bytes, UNSIGNED_INT, indices.len())
+                }
+            };
+            let view = self.buffer_view(&bytes, Some(ELEMENT_ARRAY_BUFFER));
+            primitive["indices"] = json!(self.accessor(view, component_type, count, 1, false));
+        }
+        if !cpu_mesh.morph_targets.is_empty() {
+            let mut targets = Vec::new();
+            for morph_target in cpu_mesh.morph_targets.iter() {
+                let mut target =
+                    json!({ "POSITION": self.accessor_f32(&morph_target.positions, 3, true) });
+                if let Some(ref normals) = morph_target.normals {
+                    target["NORMAL"] = json!(self.accessor_f32(normals, 3, false));
+                }
+                if let Some(ref tangents) = morph_target.tangents {
+                    target["TANGENT"] = json!(self.accessor_f32(tangents, 3, false));
+                }
+                targets.push(target);
+            }
+            primitive["targets"] = json!(targets);
+        }
+        if let Some(material) = material {
+            primitive["material"] = json!(material);
+        }
+        json!({ "name": cpu_mesh.name, "primitives": [primitive] })
+    }
+
+    fn material(&mut self, cpu_material: &'a CPUMaterial) -> ThreeDResult<serde_json::Value> {
+        let mut pbr = json!({
+            "baseColorFactor": color_to_slice(&cpu_material.albedo),
+            "metallicFactor": cpu_material.metallic,
+            "roughnessFactor": cpu_material.roughness,
+        });
+        let mut material = json!({
+            "name": cpu_material.name,
+            "emissiveFactor": &color_to_slice(&cpu_material.emissive)[..3],
+            "doubleSided": cpu_material.double_sided,
+        });
+        if let Some(ref cpu_texture) = cpu_material.albedo_texture {
+            let mut info = json!({ "index": self.texture(cpu_texture)? });
+            if cpu_material.texture_transform != Mat3::identity() {
+                info["extensions"] = json!({
+                    "KHR_texture_transform": texture_transform_json(&cpu_material.texture_transform)
+                });
+                self.use_extension("KHR_texture_transform");
+            }
+            pbr["baseColorTexture"] = info;
+        }
+        if let Some(ref cpu_texture) = cpu_material.occlusion_metallic_roughness_texture {
+            let index = self.texture(cpu_texture)?;
+            pbr["metallicRoughnessTexture"] = json!({ "index": index });
+            material["occlusionTexture"] =
+                json!({ "index": index, "strength": cpu_material.occlusion_strength });
+        } else {
+            if let Some(ref cpu_texture) = cpu_material.metallic_roughness_texture {
+                pbr["metallicRoughnessTexture"] = json!({ "index": self.texture(cpu_texture)? });
+            }
+            if let Some(ref cpu_texture) = cpu_material.occlusion_texture {
+                material["occlusionTexture"] = json!({
+                    "index": self.texture(cpu_texture)?,
+                    "strength": cpu_material.occlusion_strength,
+                });
+            }
+        }
+        if let Some(ref cpu_texture) = cpu_material.normal_texture {
+            material["normalTexture"] =
+                json!({ "index": self.texture(cpu_texture)?, "scale": cpu_material.normal_scale });
+        }
+        if let Some(ref cpu_texture) = cpu_material.emissive_texture {
+            material["emissiveTexture"] = json!({ "index": self.texture(cpu_texture)? });
+        }
+        material["pbrMetallicRoughness"] = pbr;
+
+        let alpha_mode = match cpu_material.alpha_mode {
+            AlphaMode::Auto if cpu_material.alpha_cutout.is_some() => AlphaMode::Mask,
+            AlphaMode::Auto => {
+                if cpu_material.albedo.a < 255
+                    || cpu_material
+                        .albedo_texture
+                        .as_ref()
+                        .map(|t| t.format == Format::RGBA && t.data.chunks(4).any(|c| c[3] < 255))
+                        .unwrap_or(false)
+                {
+                    AlphaMode::Blend
+                } else {
+                    AlphaMode::Opaque
+                }
+            }
+            alpha_mode => alpha_mode,
+        };
+        match alpha_mode {
+            AlphaMode::Mask => {
+                material["alphaMode"] = json!("MASK");
+                material["alphaCutoff"] = json!(cpu_material.alpha_cutout.unwrap_or(0.5));
+            }
+            AlphaMode::Blend => material["alphaMode"] = json!("BLEND"),
+            _ => material["alphaMode"] = json!("OPAQUE"),
+        }
+
+        let mut extensions = serde_json::Map::new();
+        if cpu_material.unlit {
+            extensions.insert("KHR_materials_unlit".to_string(), json!({}));
+            self.use_extension("KHR_materials_unlit");
+        }
+        if cpu_material.emissive_strength != 1.0 {
+            extensions.insert(
+                "KHR_materials_emissive_strength".to_string(),
+                json!({ "emissiveStrength": cpu_material.emissive_strength }),
+            );
+            self.use_extension("KHR_materials_emissive_strength");
+        }
+        if !extensions.is_empty() {
+            material["extensions"] = serde_json::Value::Object(extensions);
+        }
+        Ok(material)
+    }
+
+    ///
+    /// Adds the given texture and returns its index.
+    /// Textures with the same pixels, for example a texture shared by several materials, refer to the same image
+    /// and textures which also have the same sampler parameters are only added once.
+    ///
+    fn texture(&mut self, cpu_texture: &'a CPUTexture<u8>) -> ThreeDResult<usize> {
+        let image = match self.image_textures.iter().position(|image_texture| {
+            image_texture.width == cpu_texture.width
+                && image_texture.height == cpu_texture.height
+                && image_texture.format == cpu_texture.format
+                && image_texture.data == cpu_texture.data
+        }) {
+            Some(image) => image,
+            None => self.image(cpu_texture)?,
+        };
+
+        let interpolation = |interpolation: Interpolation| match interpolation {
+            Interpolation::Nearest => 9728,
+            Interpolation::Linear => 9729,
+        };
+        let min_filter = match (cpu_texture.min_filter, cpu_texture.mip_map_filter) {
+            (filter, None) => interpolation(filter),
+            (Interpolation::Nearest, Some(Interpolation::Nearest)) => 9984,
+            (Interpolation::Linear, Some(Interpolation::Nearest)) => 9985,
+            (Interpolation::Nearest, Some(Interpolation::Linear)) => 9986,
+            (Interpolation::Linear, Some(Interpolation::Linear)) => 9987,
+        };
+        let wrapping = |wrapping: Wrapping| match wrapping {
+            Wrapping::ClampToEdge => 33071,
+            Wrapping::MirroredRepeat => 33648,
+            Wrapping::Repeat => 10497,
+        };
+        let sampler = json!({
+            "magFilter": interpolation(cpu_texture.mag_filter),
+            "minFilter": min_filter,
+            "wrapS": wrapping(cpu_texture.wrap_s),
+            "wrapT": wrapping(cpu_texture.wrap_t),
+        });
+        let sampler = match self.samplers.iter().position(|s| *s == sampler) {
+            Some(index) => index,
+            None => {
+                self.samplers.push(sampler);
+                self.samplers.len() - 1
+            }
+        };
+
+        let texture = json!({ "source": image, "sampler": sampler });
+        Ok(match self.textures.iter().position(|t| *t == texture) {
+            Some(index) => index,
+            None => {
+                self.textures.push(texture);
+                self.textures.len() - 1
+            }
+        })
+    }
+
+    fn image(&mut self, cpu_texture: &'a CPUTexture<u8>) -> ThreeDResult<usize> {
+        let color_type = match cpu_texture.format {
+            Format::R => image::ColorType::L8,
+            Format::RG => image::ColorType::La8,
+            Format::RGB => image::ColorType::Rgb8,
+            Format::RGBA => image::ColorType::Rgba8,
+        };
+        let mut png = Vec::new();
+        image::png::PngEncoder::new(&mut png).encode(
+            &cpu_texture.data,
+            cpu_texture.width,
+            cpu_texture.height,
+            color_type,
+        )?;
+        let view = self.buffer_view(&png, None);
+        self.images
+            .push(json!({ "bufferView": view, "mimeType": "image/png" }));
+        self.image_textures.push(cpu_texture);
+        Ok(self.images.len() - 1)
+    }
+
+    fn accessor_f32(&mut self, data: &[f32], components: usize, use_bounds: bool) -> usize {
+        let mut bytes = Vec::with_capacity(data.len() * 4);
+        for value in data {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        let view = self.buffer_view(&bytes, Some(ARRAY_BUFFER));
+        let index = self.accessor(view, FLOAT, data.len() / components, components, false);
+        if use_bounds && data.len() >= components {
+            let mut min = vec![f32::INFINITY; components];
+            let mut max = vec![f32::NEG_INFINITY; components];
+            for element in data.chunks_exact(components) {
+                for i in 0..components {
+                    min[i] = min[i].min(element[i]);
+                    max[i] = max[i].max(element[i]);
+                }
+            }
+            self.accessors[index]["min"] = json!(min);
+            self.accessors[index]["max"] = json!(max);
+        }
+        index
+    }
+
+    fn accessor(
+        &mut self,
+        buffer_view: usize,
+        component_type: u32,
+        count: usize,
+        components: usize,
+        normalized: bool,
+    ) -> usize {
+        let element_type = match components {
+            1 => "SCALAR",
+            2 => "VEC2",
+            3 => "VEC3",
+            _ => "VEC4",
+        };
+        let mut accessor = json!({
+            "bufferView": buffer_view,
+            "componentType": component_type,
+            "count": count,
+            "type": element_type,
+        });
+        if normalized {
+            accessor["normalized"] = json!(true);
+        }
+        self.accessors.push(accessor);
+        self.accessors.len() - 1
+    }
+
+    fn buffer_view(&mut self, bytes: &[u8], target: Option<u32>) -> usize {
+        self.bin.resize(self.bin.len().div_ceil(4) * 4, 0);
+        let mut buffer_view = json!({
+            "buffer": 0,
+            "byteOffset": self.bin.len(),
+            "byteLength": bytes.len(),
+        });
+        if let Some(target) = target {
+            buffer_view["target"] = json!(target);
+        }
+        self.bin.extend_from_slice(bytes);
+        self.buffer_views.push(buffer_view);
+        self.buffer_views.len() - 1
+    }
+
+    fn use_extension(&mut self, extension: &'static str) {
+        if !self.extensions_used.contains(&extension) {
+            self.extensions_used.push(extension);
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn color_to_slice(color: &Color) -> [f32; 4] {
+    [
+        color.r as f32 / 255.0,
+        color.g as f32 / 255.0,
+        color.b as f32 / 255.0,
+        color.a as f32 / 255.0,
+    ]
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn texture_transform_json(texture_transform: &Mat3) -> serde_json::Value {
+    let scale_x = texture_transform.x.truncate().magnitude();
+    let scale_y = texture_transform.y.truncate().magnitude();
+    json!({
+        "offset": [texture_transform.z.x, texture_transform.z.y],
+        "rotation": (-texture_transform.x.y).atan2(texture_transform.x.x),
+        "scale": [scale_x, scale_y],
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn animated_node(interpolation: &str, target: &str, input: &[f32], output: &[f32]) -> Loaded {
+        let mut bin = Vec::new();
+        for value in input.iter().chain(output.iter()) {
+            bin.extend_from_slice(&value.to_le_bytes());
+        }
+        let (output_type, components) = match target {
+            "rotation" => ("VEC4", 4),
+            "weights" => ("SCALAR", 1),
+            _ => ("VEC3", 3),
+        };
+        let root = json!({
+            "asset": { "version": "2.0" },
+            "scenes": [{ "nodes": [0] }],
+            "nodes": [{}],
+            "buffers": [{ "uri": "animation.bin", "byteLength": bin.len() }],
+            "bufferViews": [
+                { "buffer": 0, "byteOffset": 0, "byteLength": 4 * input.len() },
+                { "buffer": 0, "byteOffset": 4 * input.len(), "byteLength": 4 * output.len() },
+            ],
+            "accessors": [
+                {
+                    "bufferView": 0,
+                    "componentType": 5126,
+                    "count": input.len(),
+                    "type": "SCALAR",
+                    "min": [input.first().cloned().unwrap_or(0.0)],
+                    "max": [input.last().cloned().unwrap_or(0.0)],
+                },
+                {
+                    "bufferView": 1,
+                    "componentType": 5126,
+                    "count": output.len() / components,
+                    "type": output_type,
+                },
+            ],
+            "animations": [{
+                "channels": [{ "sampler": 0, "target": { "node": 0, "path": target } }],
+                "samplers": [{ "input": 0, "output": 1, "interpolation": interpolation }],
+            }],
+        });
+        let mut loaded = Loaded::new();
+        loaded.insert_bytes("animation.gltf", serde_json::to_vec(&root).unwrap());
+        loaded.insert_bytes("animation.bin", bin);
+        loaded
+    }
+
+    fn triangle(modify: impl FnOnce(&mut serde_json::Value)) -> Loaded {
+        let mut bin = Vec::new();
+        for value in [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0].iter() {
+            bin.extend_from_slice(&value.to_le_bytes());
+        }
+        for index in [0u16, 1, 2].iter() {
+            bin.extend_from_slice(&index.to_le_bytes());
+        }
+        let mut root = json!({
+            "asset": { "version": "2.0" },
+            "scenes": [{ "nodes": [0] }],
+            "nodes": [{ "mesh": 0 }],
+            "meshes": [{
+                "primitives": [{ "attributes": { "POSITION": 0 }, "indices": 1 }],
+            }],
+            "buffers": [{ "uri": "triangle.bin", "byteLength": bin.len() }],
+            "bufferViews": [
+                { "buffer": 0, "byteOffset": 0, "byteLength": 36 },
+                { "buffer": 0, "byteOffset": 36, "byteLength": 6 },
+            ],
+            "accessors": [
+                {
+                    "bufferView": 0,
+                    "componentType": 5126,
+                    "count": 3,
+                    "type": "VEC3",
+                    "min": [0.0, 0.0, 0.0],
+                    "max": [1.0, 1.0, 0.0],
+                },
+                {
+                    "bufferView": 1,
+                    "componentType": 5123,
+                    "count": 3,
+                    "type": "SCALAR",
+                },
+            ],
+        });
+        modify(&mut root);
+        let mut loaded = Loaded::new();
+        loaded.insert_bytes("triangle.gltf", serde_json::to_vec(&root).unwrap());
+        loaded.insert_bytes("triangle.bin", bin);
+        loaded
+    }
+
+    fn assert_corrupt(
+        result: ThreeDResult<(CPUScene, Vec<CPUMesh>, Vec<CPUMaterial>)>,
+        expected_path: &str,
+    ) {
+        let error = match result {
+            Ok(_) => panic!("expected an error"),
+            Err(error) => error,
+        };
+        match error.downcast_ref::<IOError>() {
+            Some(IOError::Corrupt(path, _)) => assert_eq!(path, expected_path),
+            _ => panic!("unexpected error {:?}", error),
+        }
+    }
+
+    #[test]
+    pub fn animation_sampling() {
+        let (scene, _, _) = animated_node(
+            "LINEAR",
+            "translation",
+            &[0.0, 1.0],
+            &[0.0, 0.0, 0.0, 2.0, 4.0, 6.0],
+        )
+        .gltf_scene("animation.gltf")
+        .unwrap();
+        let key_frames = scene.animations[0].tracks[0].translation.as_ref().unwrap();
+        assert_eq!(key_frames.interpolate(0.5), Some(vec3(1.0, 2.0, 3.0)));
+
+        let (scene, _, _) = animated_node(
+            "CUBICSPLINE",
+            "scale",
+            &[0.0, 1.0],
+            &[
+                0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 3.0, 3.0, 3.0, 0.0,
+                0.0, 0.0,
+            ],
+        )
+        .gltf_scene("animation.gltf")
+        .unwrap();
+        let key_frames = scene.animations[0].tracks[0].scale.as_ref().unwrap();
+        assert_eq!(key_frames.interpolate(0.5), Some(vec3(2.0, 2.0, 2.0)));
+    }
+
+    #[test]
+    pub fn morph_weight_animation() {
+        let (scene, _, _) = animated_node("LINEAR", "weights", &[0.0, 1.0], &[0.0, 1.0, 1.0, 0.0])
+            .gltf_scene("animation.gltf")
+            .unwrap();
+        assert_eq!(
+            scene.animations[0].morph_weights(0, 0.5),
+            Some(vec![0.5, 0.5])
+        );
+    }
+
+    #[test]
+    pub fn morph_weight_animation_without_weights() {
+        assert_corrupt(
+            animated_node("LINEAR", "weights", &[0.0, 1.0], &[]).gltf_scene("animation.gltf"),
+            "animation.gltf",
+        );
+        assert_corrupt(
+            animated_node("LINEAR", "weights", &[0.0, 1.0], &[0.0, 1.0, 0.5])
+                .gltf_scene("animation.gltf"),
+            "animation.gltf",
+        );
+    }
+
+    #[test]
+    pub fn animation_with_too_few_outputs() {
+        // One output value for each key frame instead of three
+        assert_corrupt(
+            animated_node(
+                "CUBICSPLINE",
+                "translation",
+                &[0.0, 1.0],
+                &[0.0, 0.0, 0.0, 1.0, 1.0, 1.0],
+            )
+            .gltf_scene("animation.gltf"),
+            "animation.gltf",
+        );
+        assert_corrupt(
+            animated_node(
+                "LINEAR",
+                "rotation",
+                &[0.0, 1.0, 2.0],
+                &[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0],
+            )
+            .gltf_scene("animation.gltf"),
+            "animation.gltf",
+        );
+        assert_corrupt(
+            animated_node(
+                "STEP",
+                "scale",
+                &[0.0, 1.0, 2.0],
+                &[1.0, 1.0, 1.0, 2.0, 2.0, 2.0],
+            )
+            .gltf_scene("animation.gltf"),
+            "animation.gltf",
+        );
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn round_trip_scene() -> (Vec<CPUMesh>, Vec<CPUMaterial>) {
+        let mesh = CPUMesh {
+            name: "quad".to_string(),
+            material_name: Some("textured".to_string()),
+            positions: vec![
+                -1.0, -1.0, 0.0, 1.0, -1.0, 0.0, 1.0, 1.0, 0.0, -1.0, 1.0, 0.0,
+            ],
+            indices: Some(Indices::U16(vec![0, 1, 2, 2, 3, 0])),
+            normals: Some(vec![
+                0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0,
+            ]),
+            uvs: Some(vec![0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0, 1.0]),
+            colors: Some(vec![
+                255, 0, 0, 255, 0, 255, 0, 255, 0, 0, 255, 255, 255, 255, 255, 128,
+            ]),
+            ..Default::default()
+        };
+        let material = CPUMaterial {
+            name: "textured".to_string(),
+            albedo: Color::new(255, 128, 0, 255),
+            albedo_texture: Some(CPUTexture {
+                data: vec![
+                    255, 0, 0, 255, 0, 255, 0, 255, 0, 0, 255, 255, 255, 255, 255, 255,
+                ],
+                width: 2,
+                height: 2,
+                format: Format::RGBA,
+                min_filter: Interpolation::Nearest,
+                mag_filter: Interpolation::Nearest,
+                mip_map_filter: None,
+                wrap_s: Wrapping::ClampToEdge,
+                wrap_t: Wrapping::MirroredRepeat,
+            }),
+            metallic: 0.25,
+            roughness: 0.75,
+            emissive: Color::new(0, 255, 0, 255),
+            emissive_strength: 2.0,
+            alpha_mode: AlphaMode::Mask,
+            alpha_cutout: Some(0.25),
+            double_sided: false,
+            texture_transform: Mat3::from_translation(vec2(0.5, 0.25))
+                * Mat3::from_nonuniform_scale(2.0, 4.0),
+            ..Default::default()
+        };
+        (vec![mesh], vec![material])
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn assert_round_trip(
+        cpu_meshes: &[CPUMesh],
+        cpu_materials: &[CPUMaterial],
+        loaded_meshes: &[CPUMesh],
+        loaded_materials: &[CPUMaterial],
+    ) {
+        assert_eq!(loaded_meshes.len(), cpu_meshes.len());
+        for (loaded, mesh) in loaded_meshes.iter().zip(cpu_meshes.iter()) {
+            assert_eq!(loaded.name, mesh.name);
+            assert_eq!(loaded.material_name, mesh.material_name);
+            assert_eq!(loaded.positions, mesh.positions);
+            assert_eq!(
+                loaded.indices.as_ref().map(|i| i.into_u32()),
+                mesh.indices.as_ref().map(|i| i.into_u32())
+            );
+            assert_eq!(loaded.normals, mesh.normals);
+            assert_eq!(loaded.uvs, mesh.uvs);
+            assert_eq!(loaded.colors, mesh.colors);
+        }
+        assert_eq!(loaded_materials.len(), cpu_materials.len());
+        for (loaded, material) in loaded_materials.iter().zip(cpu_materials.iter()) {
+            assert_eq!(loaded.name, material.name);
+            assert_eq!(loaded.albedo, material.albedo);
+            assert_eq!(loaded.metallic, material.metallic);
+            assert_eq!(loaded.roughness, material.roughness);
+            assert_eq!(loaded.emissive, material.emissive);
+            assert_eq!(loaded.emissive_strength, material.emissive_strength);
+            assert_eq!(loaded.alpha_mode, material.alpha_mode);
+            assert_eq!(loaded.alpha_cutout, material.alpha_cutout);
+            assert_eq!(loaded.double_sided, material.double_sided);
+            assert_eq!(loaded.unlit, material.unlit);
+            let loaded_transform: &[f32; 9] = loaded.texture_transform.as_ref();
+            let transform: &[f32; 9] = material.texture_transform.as_ref();
+            for (a, b) in loaded_transform.iter().zip(transform.iter()) {
+                assert!((a - b).abs() < 0.0001);
+            }
+            let loaded_texture = loaded.albedo_texture.as_ref().unwrap();
+            let texture = material.albedo_texture.as_ref().unwrap();
+            assert_eq!(loaded_texture.data, texture.data);
+            assert_eq!(loaded_texture.width, texture.width);
+            assert_eq!(loaded_texture.height, texture.height);
+            assert_eq!(loaded_texture.format, texture.format);
+            assert_eq!(loaded_texture.min_filter, texture.min_filter);
+            assert_eq!(loaded_texture.mag_filter, texture.mag_filter);
+            assert_eq!(loaded_texture.mip_map_filter, texture.mip_map_filter);
+            assert_eq!(loaded_texture.wrap_s, texture.wrap_s);
+            assert_eq!(loaded_texture.wrap_t, texture.wrap_t);
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    pub fn glb_round_trip() {
+        let (cpu_meshes, cpu_materials) = round_trip_scene();
+        let bytes = Saver::serialize_glb(&cpu_meshes, &cpu_materials).unwrap();
+        let mut loaded = Loaded::new();
+        loaded.insert_bytes("scene.glb", bytes);
+        let (meshes, materials) = loaded.gltf("scene.glb").unwrap();
+        assert_round_trip(&cpu_meshes, &cpu_materials, &meshes, &materials);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    pub fn gltf_round_trip() {
+        let (cpu_meshes, cpu_materials) = round_trip_scene();
+        let (json, bin) = Saver::serialize_gltf(&cpu_meshes, &cpu_materials, "scene.bin").unwrap();
+        let mut loaded = Loaded::new();
+        loaded.insert_bytes("assets/scene.gltf", json);
+        loaded.insert_bytes("assets/scene.bin", bin);
+        let (meshes, materials) = loaded.gltf("assets/scene.gltf").unwrap();
+        assert_round_trip(&cpu_meshes, &cpu_materials, &meshes, &materials);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    pub fn shared_textures() {
+        let (mut cpu_meshes, mut cpu_materials) = round_trip_scene();
+        let mut other = round_trip_scene().1.remove(0);
+        other.name = "other".to_string();
+        let texture = || round_trip_scene().1.remove(0).albedo_texture;
+        other.emissive_texture = texture();
+        let mut sampler = texture().unwrap();
+        sampler.wrap_s = Wrapping::Repeat;
+        other.normal_texture = Some(sampler);
+        cpu_materials.push(other);
+        let mut mesh = cpu_meshes[0].clone();
+        mesh.material_name = Some("other".to_string());
+        cpu_meshes.push(mesh);
+
+        // The four textures have the same pixels and only one of them has different sampler parameters
+        let (json, bin) = Saver::serialize_gltf(&cpu_meshes, &cpu_materials, "scene.bin").unwrap();
+        let root: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(root["images"].as_array().unwrap().len(), 1);
+        assert_eq!(root["samplers"].as_array().unwrap().len(), 2);
+        assert_eq!(root["textures"].as_array().unwrap().len(), 2);
+        assert_eq!(
+            root["materials"][0]["pbrMetallicRoughness"]["baseColorTexture"]["index"],
+            root["materials"][1]["emissiveTexture"]["index"]
+        );
+
+        let mut loaded = Loaded::new();
+        loaded.insert_bytes("scene.gltf", json);
+        loaded.insert_bytes("scene.bin", bin);
+        let (meshes, materials) = loaded.gltf("scene.gltf").unwrap();
+        assert_round_trip(&cpu_meshes, &cpu_materials, &meshes, &materials);
+        let normal_texture = materials[1].normal_texture.as_ref().unwrap();
+        assert_eq!(normal_texture.wrap_s, Wrapping::Repeat);
+        assert_eq!(
+            normal_texture.data,
+            cpu_materials[0].albedo_texture.as_ref().unwrap().data
+        );
+    }
+
+    #[test]
+    pub fn malformed_accessors() {
+        let (_, meshes, _) = triangle(|_| {}).gltf_scene("triangle.gltf").unwrap();
+        assert_eq!(meshes[0].positions.len(), 9);
+        let modifications: Vec<fn(&mut serde_json::Value)> = vec![
+            |root| root["accessors"][0]["count"] = json!(0),
+            |root| root["accessors"][0]["count"] = json!(4),
+            |root| root["accessors"][0]["byteOffset"] = json!(4),
+            |root| root["accessors"][1]["componentType"] = json!(5126),
+            |root| root["accessors"][0]["type"] = json!("VEC2"),
+            |root| root["bufferViews"][0]["byteOffset"] = json!(40),
+            |root| root["bufferViews"][1]["byteLength"] = json!(8),
+            |root| root["bufferViews"][0]["byteStride"] = json!(8),
+            |root| {
+                root["accessors"][0]["sparse"] = json!({
+                    "count": 1,
+                    "indices": { "bufferView": 1, "componentType": 5123 },
+                    "values": { "bufferView": 0 },
+                })
+            },
+            |root| root["buffers"][0]["byteLength"] = json!(100),
+        ];
+        for (index, modify) in modifications.into_iter().enumerate() {
+            let path = if index == 9 {
+                "triangle.bin"
+            } else {
+                "triangle.gltf"
+            };
+            assert_corrupt(triangle(modify).gltf_scene("triangle.gltf"), path);
+        }
+    }
+
+    #[test]
+    pub fn malformed_node_hierarchy() {
+        assert_corrupt(
+            triangle(|root| {
+                root["nodes"] = json!([{ "mesh": 0 }, { "children": [2] }, { "children": [1] }])
+            })
+            .gltf_scene("triangle.gltf"),
+            "triangle.gltf",
+        );
+        assert_corrupt(
+            triangle(|root| {
+                root["nodes"] = json!([{ "mesh": 0 }, { "children": [2] }, { "children": [3] }, { "children": [2] }])
+            })
+            .gltf_scene("triangle.gltf"),
+            "triangle.gltf",
+        );
+        assert_corrupt(
+            triangle(|root| {
+                root["nodes"] = json!([{ "children": [1] }, { "mesh": 0 }]);
+                root["scenes"] = json!([{ "nodes": [0, 1] }]);
+            })
+            .gltf_scene("triangle.gltf"),
+            "triangle.gltf",
+        );
+        assert_corrupt(
+            triangle(|root| {
+                root["nodes"] = json!([{ "children": [2] }, { "children": [2] }, { "mesh": 0 }])
+            })
+            .gltf_scene("triangle.gltf"),
+            "triangle.gltf",
+        );
+        // A node with a singular transformation is valid and must not make the flattening fail
+        let (meshes, _) = triangle(|root| root["nodes"][0]["scale"] = json!([0.0, 0.0, 0.0]))
+            .gltf("triangle.gltf")
+            .unwrap();
+        assert_eq!(meshes[0].positions, vec![0.0; 9]);
+    }
+
+    #[test]
+    pub fn scene_roots() {
+        // Only the roots of the default scene are used
+        let (scene, _, _) = triangle(|root| {
+            root["nodes"] = json!([{ "mesh": 0 }, { "mesh": 0 }, { "children": [1] }]);
+            root["scenes"] = json!([{ "nodes": [0] }, { "name": "second", "nodes": [2, 2] }]);
+            root["scene"] = json!(1);
+        })
+        .gltf_scene("triangle.gltf")
+        .unwrap();
+        assert_eq!(scene.name, "second");
+        assert_eq!(scene.roots, vec![2]);
+        assert_eq!(scene.nodes[1].parent, Some(2));
+
+        // Otherwise the roots of the first scene are used
+        let (scene, _, _) = triangle(|root| {
+            root["nodes"] = json!([{ "mesh": 0 }, { "mesh": 0 }]);
+            root["scenes"] = json!([{ "name": "first", "nodes": [1] }, { "nodes": [0] }]);
+        })
+        .gltf_scene("triangle.gltf")
+        .unwrap();
+        assert_eq!(scene.name, "first");
+        assert_eq!(scene.roots, vec![1]);
+    }
+
+    #[test]
+    pub fn deep_node_hierarchy() {
+        let count = 100_000;
+        let (scene, _, _) = triangle(|root| {
+            let mut nodes = (1..count)
+                .map(|child| json!({ "children": [child] }))
+                .collect::<Vec<_>>();
+            nodes.push(json!({ "mesh": 0 }));
+            root["nodes"] = serde_json::Value::Array(nodes);
+        })
+        .gltf_scene("triangle.gltf")
+        .unwrap();
+        assert_eq!(scene.roots, vec![0]);
+        assert_eq!(scene.nodes[count - 1].parent, Some(count - 2));
+    }
+}
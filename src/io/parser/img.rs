@@ -14,21 +14,17 @@ pub fn image_from_bytes(bytes: &[u8]) -> ThreeDResult<crate::core::CPUTexture<u8
     use image::DynamicImage;
     use image::GenericImageView;
     let img = image::load_from_memory(bytes)?;
-    let format = match img {
-        DynamicImage::ImageLuma8(_) => Format::R,
-        DynamicImage::ImageLumaA8(_) => Format::RG,
-        DynamicImage::ImageRgb8(_) => Format::RGB,
-        DynamicImage::ImageRgba8(_) => Format::RGBA,
-        DynamicImage::ImageBgr8(_) => unimplemented!(),
-        DynamicImage::ImageBgra8(_) => unimplemented!(),
-        DynamicImage::ImageLuma16(_) => unimplemented!(),
-        DynamicImage::ImageLumaA16(_) => unimplemented!(),
-        DynamicImage::ImageRgb16(_) => unimplemented!(),
-        DynamicImage::ImageRgba16(_) => unimplemented!(),
+    let (data, format) = match img {
+        DynamicImage::ImageLuma8(_) => (img.to_bytes(), Format::R),
+        DynamicImage::ImageLumaA8(_) => (img.to_bytes(), Format::RG),
+        DynamicImage::ImageRgb8(_) => (img.to_bytes(), Format::RGB),
+        DynamicImage::ImageRgba8(_) => (img.to_bytes(), Format::RGBA),
+        // Other formats, for example with 16 bits per channel, are converted to 8 bit RGBA
+        _ => (img.to_rgba8().into_raw(), Format::RGBA),
     };
 
     Ok(CPUTexture {
-        data: img.to_bytes(),
+        data,
         width: img.width(),
         height: img.height(),
         format,
@@ -112,17 +108,25 @@ impl Loaded {
     /// **Note:** If the image contains high dynamic range (hdr) information, use [hdr_image](Loaded::hdr_image) instead.
     ///
     pub fn image<P: AsRef<Path>>(&mut self, path: P) -> ThreeDResult<CPUTexture<u8>> {
-        image_from_bytes(&self.get_bytes(path)?)
+        Ok(
+            image_from_bytes(self.get_bytes(path.as_ref())?).map_err(|e| {
+                IOError::Corrupt(path.as_ref().to_string_lossy().to_string(), e.to_string())
+            })?,
+        )
     }
 
     ///
     /// Deserialize the loaded image resource with hdr information at the given path into a [CPUTexture] using
     /// the [image](https://crates.io/crates/image/main.rs) crate.
     /// The CPUTexture can then be used to create a [Texture2D] or a [TextureCubeMap] using the `new_from_equirectangular` method.
-    /// Supported formats: HDR.
+    /// Supported formats: HDR. OpenEXR images can be loaded with `exr_image` which requires the `exr-io` feature.
     ///
     pub fn hdr_image(&mut self, path: impl AsRef<Path>) -> ThreeDResult<CPUTexture<f32>> {
-        hdr_image_from_bytes(&self.get_bytes(path)?)
+        Ok(
+            hdr_image_from_bytes(self.get_bytes(path.as_ref())?).map_err(|e| {
+                IOError::Corrupt(path.as_ref().to_string_lossy().to_string(), e.to_string())
+            })?,
+        )
     }
 
     ///
@@ -164,6 +168,62 @@ impl Loaded {
             wrap_r: right.wrap_s,
         })
     }
+
+    ///
+    /// Deserialize a loaded image resource which contains all 6 sides of a cube map into a [CPUTextureCube].
+    /// The layout of the sides is detected from the aspect ratio of the image, see [CubeMapLayout] for the supported layouts.
+    /// If the `compressed-texture-io` feature is enabled, .ktx2 and .dds cube maps are also supported and are decompressed on the CPU.
+    /// The CPUTextureCube can then be used to create a [TextureCubeMap].
+    ///
+    pub fn packed_cube_image(
+        &mut self,
+        path: impl AsRef<Path>,
+    ) -> ThreeDResult<CPUTextureCube<u8>> {
+        #[cfg(feature = "compressed-texture-io")]
+        if super::compressed::is_compressed_image(self.get_bytes(path.as_ref())?) {
+            return self.compressed_cube_image(path)?.decompress();
+        }
+        let image = self.image(path.as_ref())?;
+        packed_cube_image(path.as_ref(), &image)
+    }
+
+    ///
+    /// Deserialize a loaded image resource with hdr information which contains all 6 sides of a cube map into a [CPUTextureCube].
+    /// The layout of the sides is detected from the aspect ratio of the image, see [CubeMapLayout] for the supported layouts.
+    /// Supported formats: HDR and, if the `exr-io` feature is enabled, OpenEXR.
+    /// The CPUTextureCube can then be used to create a [TextureCubeMap].
+    ///
+    pub fn packed_hdr_cube_image(
+        &mut self,
+        path: impl AsRef<Path>,
+    ) -> ThreeDResult<CPUTextureCube<f32>> {
+        #[cfg(feature = "exr-io")]
+        if self
+            .get_bytes(path.as_ref())?
+            .starts_with(&[0x76, 0x2f, 0x31, 0x01])
+        {
+            let image = self.exr_image(path.as_ref())?;
+            return packed_cube_image(path.as_ref(), &image);
+        }
+        let image = self.hdr_image(path.as_ref())?;
+        packed_cube_image(path.as_ref(), &image)
+    }
+}
+
+fn packed_cube_image<T: TextureDataType>(
+    path: &Path,
+    image: &CPUTexture<T>,
+) -> ThreeDResult<CPUTextureCube<T>> {
+    let layout = CubeMapLayout::detect(image.width, image.height).ok_or_else(|| {
+        IOError::Corrupt(
+            path.to_string_lossy().to_string(),
+            format!(
+                "the size {}x{} does not match any cube map layout",
+                image.width, image.height
+            ),
+        )
+    })?;
+    CPUTextureCube::from_layout(image, layout)
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -177,6 +237,16 @@ impl Saver {
         width: u32,
         height: u32,
     ) -> ThreeDResult<()> {
+        if pixels.len() < width as usize * height as usize * 4 {
+            Err(IOError::Corrupt(
+                path.as_ref().to_string_lossy().to_string(),
+                format!(
+                    "expected {} bytes of RGBA pixel data but got {}",
+                    width as usize * height as usize * 4,
+                    pixels.len()
+                ),
+            ))?;
+        }
         let mut pixels_out = vec![0u8; width as usize * height as usize * 4];
         for row in 0..height as usize {
             for col in 0..width as usize {
@@ -196,4 +266,233 @@ impl Saver {
         )?;
         Ok(())
     }
+
+    ///
+    /// Saves the given texture, for example loaded with [Loaded::image] or packed with [CPUTextureCube::to_layout], as an image.
+    /// The image format is determined from the file extension of the path.
+    ///
+    pub fn save_texture(path: impl AsRef<Path>, cpu_texture: &CPUTexture<u8>) -> ThreeDResult<()> {
+        let channels = cpu_texture.format.color_channel_count() as usize;
+        let width = cpu_texture.width as usize;
+        let height = cpu_texture.height as usize;
+        if cpu_texture.data.len() < width * height * channels {
+            Err(IOError::Corrupt(
+                path.as_ref().to_string_lossy().to_string(),
+                format!(
+                    "expected {} bytes of pixel data but got {}",
+                    width * height * channels,
+                    cpu_texture.data.len()
+                ),
+            ))?;
+        }
+        let mut pixels = Vec::with_capacity(width * height * 4);
+        // The pixels given to save_pixels are expected to be ordered from the bottom row to the top row like when read from the screen.
+        for row in (0..height).rev() {
+            for col in 0..width {
+                let i = (row * width + col) * channels;
+                let p = &cpu_texture.data[i..i + channels];
+                pixels.extend_from_slice(&match channels {
+                    1 => [p[0], p[0], p[0], 255],
+                    2 => [p[0], p[0], p[0], p[1]],
+                    3 => [p[0], p[1], p[2], 255],
+                    _ => [p[0], p[1], p[2], p[3]],
+                });
+            }
+        }
+        Self::save_pixels(path, &pixels, cpu_texture.width, cpu_texture.height)
+    }
+
+    ///
+    /// Saves the given RGBA float pixels, for example the result of [Texture2D::read] on a `f16` or `f32` texture, as a high dynamic range image.
+    /// Like [save_pixels](Self::save_pixels), the pixels are expected to be ordered from the bottom row to the top row.
+    /// The image format is determined from the file extension of the path and can be either .hdr (Radiance) or .exr (OpenEXR, requires the `exr-io` feature).
+    /// **Note:** The alpha channel is not saved in the .hdr format.
+    ///
+    pub fn save_hdr_pixels<T: TextureDataType + Into<f32>>(
+        path: impl AsRef<Path>,
+        pixels: &[T],
+        width: u32,
+        height: u32,
+    ) -> ThreeDResult<()> {
+        if pixels.len() < width as usize * height as usize * 4 {
+            Err(IOError::Corrupt(
+                path.as_ref().to_string_lossy().to_string(),
+                format!(
+                    "expected {} values of RGBA pixel data but got {}",
+                    width as usize * height as usize * 4,
+                    pixels.len()
+                ),
+            ))?;
+        }
+        let mut pixels_out = Vec::with_capacity(width as usize * height as usize);
+        for row in (0..height as usize).rev() {
+            for col in 0..width as usize {
+                let i = 4 * (width as usize * row + col);
+                pixels_out.push([
+                    pixels[i].into(),
+                    pixels[i + 1].into(),
+                    pixels[i + 2].into(),
+                    pixels[i + 3].into(),
+                ]);
+            }
+        }
+        save_hdr(path.as_ref(), width, height, &pixels_out)
+    }
+
+    ///
+    /// Saves the given float texture, for example loaded with [Loaded::hdr_image], as a high dynamic range image.
+    /// A texture with only one channel is saved as a grey scale image and missing channels are otherwise set to zero, except alpha which is set to one.
+    /// See [save_hdr_pixels](Self::save_hdr_pixels) for the supported image formats.
+    ///
+    pub fn save_hdr_texture<T: TextureDataType + Into<f32>>(
+        path: impl AsRef<Path>,
+        cpu_texture: &CPUTexture<T>,
+    ) -> ThreeDResult<()> {
+        let channels = cpu_texture.format.color_channel_count() as usize;
+        let pixel_count = cpu_texture.width as usize * cpu_texture.height as usize;
+        if cpu_texture.data.len() < pixel_count * channels {
+            Err(IOError::Corrupt(
+                path.as_ref().to_string_lossy().to_string(),
+                format!(
+                    "expected {} values of pixel data but got {}",
+                    pixel_count * channels,
+                    cpu_texture.data.len()
+                ),
+            ))?;
+        }
+        let pixels = cpu_texture
+            .data
+            .chunks_exact(channels)
+            .take(pixel_count)
+            .map(|pixel| {
+                let value = |i: usize| pixel[i].into();
+                match cpu_texture.format {
+                    Format::R => [value(0), value(0), value(0), 1.0],
+                    Format::RG => [value(0), value(1), 0.0, 1.0],
+                    Format::RGB => [value(0), value(1), value(2), 1.0],
+                    Format::RGBA => [value(0), value(1), value(2), value(3)],
+                }
+            })
+            .collect::<Vec<_>>();
+        save_hdr(
+            path.as_ref(),
+            cpu_texture.width,
+            cpu_texture.height,
+            &pixels,
+        )
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn save_hdr(path: &Path, width: u32, height: u32, pixels: &[[f32; 4]]) -> ThreeDResult<()> {
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "hdr" => {
+            let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+            image::codecs::hdr::HdrEncoder::new(file).encode(
+                &pixels
+                    .iter()
+                    .map(|p| image::Rgb([p[0], p[1], p[2]]))
+                    .collect::<Vec<_>>(),
+                width as usize,
+                height as usize,
+            )?;
+            Ok(())
+        }
+        #[cfg(feature = "exr-io")]
+        "exr" => Saver::save_file(path, &super::exr::serialize_exr(width, height, pixels)?),
+        _ => Err(IOError::UnsupportedFormat(
+            path.to_string_lossy().to_string(),
+        ))?,
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod test {
+    use super::*;
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("three-d-{}-{}", std::process::id(), name))
+    }
+
+    fn load_hdr(path: &Path) -> CPUTexture<f32> {
+        let texture = hdr_image_from_bytes(&std::fs::read(path).unwrap()).unwrap();
+        std::fs::remove_file(path).unwrap();
+        texture
+    }
+
+    fn assert_close(loaded: &[f32], expected: &[f32]) {
+        assert_eq!(loaded.len(), expected.len());
+        // The .hdr format stores an 8 bit mantissa per channel and an exponent shared by the channels of a pixel
+        for (a, b) in loaded.chunks(3).zip(expected.chunks(3)) {
+            let max = b.iter().cloned().fold(0.0f32, f32::max);
+            for c in 0..3 {
+                assert!((a[c] - b[c]).abs() <= max / 64.0, "{:?} != {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    pub fn hdr_texture_round_trip() {
+        let data = vec![
+            0.0f32, 0.5, 1.0, 2.0, 4.0, 8.0, 100.0, 0.25, 0.125, 1.5, 3.0, 0.75, 0.0, 0.0, 0.0,
+            10.0, 20.0, 30.0,
+        ];
+        let path = temp_path("texture_f32.hdr");
+        Saver::save_hdr_texture(
+            &path,
+            &CPUTexture {
+                data: data.clone(),
+                width: 3,
+                height: 2,
+                format: Format::RGB,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let texture = load_hdr(&path);
+        assert_eq!((texture.width, texture.height), (3, 2));
+        assert_eq!(texture.format, Format::RGB);
+        assert_close(&texture.data, &data);
+
+        let path = temp_path("texture_f16.hdr");
+        Saver::save_hdr_texture(
+            &path,
+            &CPUTexture {
+                data: data.iter().map(|&v| f16::from_f32(v)).collect(),
+                width: 2,
+                height: 3,
+                format: Format::RGB,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let texture = load_hdr(&path);
+        assert_eq!((texture.width, texture.height), (2, 3));
+        assert_close(&texture.data, &data);
+    }
+
+    #[test]
+    pub fn hdr_pixels_round_trip() {
+        // The pixels are given from the bottom row to the top row, so the rows are flipped when loaded
+        let pixels = [1.0f32, 2.0, 3.0, 1.0, 0.5, 0.25, 0.125, 1.0]
+            .iter()
+            .map(|&v| f16::from_f32(v))
+            .collect::<Vec<_>>();
+        let path = temp_path("pixels_f16.hdr");
+        Saver::save_hdr_pixels(&path, &pixels, 1, 2).unwrap();
+        let texture = load_hdr(&path);
+        assert_eq!((texture.width, texture.height), (1, 2));
+        assert_close(&texture.data, &[0.5, 0.25, 0.125, 1.0, 2.0, 3.0]);
+
+        let path = temp_path("pixels_f32.hdr");
+        Saver::save_hdr_pixels(&path, &[1.0f32, 2.0, 3.0, 1.0], 1, 1).unwrap();
+        assert_close(&load_hdr(&path).data, &[1.0, 2.0, 3.0]);
+
+        assert!(Saver::save_hdr_pixels(temp_path("short.hdr"), &[1.0f32; 4], 2, 1).is_err());
+        assert!(Saver::save_hdr_pixels(temp_path("unknown.png"), &[1.0f32; 4], 1, 1).is_err());
+    }
 }
@@ -6,160 +6,554 @@ use std::path::Path;
 impl Loaded {
     ///
     /// Deserialize a loaded .obj file resource and .mtl material file resource (if present) into a list of meshes and materials.
-    /// It uses the [wavefront-obj](https://crates.io/crates/wavefront_obj/main.rs) crate.
+    /// It uses the [wavefront-obj](https://crates.io/crates/wavefront_obj/main.rs) crate for the .obj file.
+    ///
+    /// Each object in the .obj file results in one mesh for each material used by the faces of that object.
+    /// Besides the classic .mtl statements, the PBR extensions `Pr`, `Pm`, `map_Pr`, `map_Pm` and `norm` are supported.
+    /// If any of these are present, the material uses the Cook-Torrance lighting model, otherwise the Blinn lighting model.
     ///
     pub fn obj(
         &mut self,
         path: impl AsRef<Path>,
     ) -> ThreeDResult<(Vec<CPUMesh>, Vec<CPUMaterial>)> {
-        let obj_bytes = self.remove_bytes(path.as_ref())?;
-        let obj = wavefront_obj::obj::parse(String::from_utf8(obj_bytes).unwrap())?;
-        let p = path.as_ref().parent().unwrap();
+        let path = path.as_ref();
+        let obj_bytes = self.remove_bytes(path)?;
+        let obj = wavefront_obj::obj::parse(text_from_bytes(path, obj_bytes)?)
+            .map_err(|e| IOError::Corrupt(path.to_string_lossy().to_string(), e.to_string()))?;
+        if obj.objects.is_empty() {
+            Err(IOError::Corrupt(
+                path.to_string_lossy().to_string(),
+                "the file does not contain any objects".to_string(),
+            ))?;
+        }
+        let p = path.parent().unwrap_or(Path::new(""));
 
         // Parse materials
         let mut cpu_materials = Vec::new();
         if let Some(material_library) = obj.material_library {
-            let bytes = self.remove_bytes(p.join(material_library).to_str().unwrap())?;
-            let materials = wavefront_obj::mtl::parse(String::from_utf8(bytes).unwrap())?.materials;
-
-            for material in materials {
-                let color = if material.color_diffuse.r != material.color_diffuse.g
-                    || material.color_diffuse.g != material.color_diffuse.b
-                {
-                    material.color_diffuse
-                } else if material.color_specular.r != material.color_specular.g
-                    || material.color_specular.g != material.color_specular.b
-                {
-                    material.color_specular
-                } else if material.color_ambient.r != material.color_ambient.g
-                    || material.color_ambient.g != material.color_ambient.b
-                {
-                    material.color_ambient
-                } else {
-                    material.color_diffuse
-                };
-
-                let normal_texture = if let Some(ref texture_name) = material.bump_map {
-                    Some(self.image(p.join(texture_name))?)
-                } else {
-                    None
-                };
-                let albedo_texture = if let Some(ref texture_name) = material.diffuse_map {
-                    Some(self.image(p.join(texture_name))?)
-                } else {
-                    None
-                };
-
-                cpu_materials.push(CPUMaterial {
-                    name: material.name,
-                    albedo: Color::from_rgba_slice(&[
-                        color.r as f32,
-                        color.g as f32,
-                        color.b as f32,
-                        material.alpha as f32,
-                    ]),
-                    albedo_texture,
-                    metallic: ((material.color_specular.r
-                        + material.color_specular.g
-                        + material.color_specular.b)
-                        / 3.0) as f32,
-                    roughness: if material.specular_coefficient > 0.1 {
-                        ((1.999 / material.specular_coefficient).sqrt() as f32).min(1.0)
-                    } else {
-                        1.0
-                    },
-                    normal_texture,
-                    lighting_model: LightingModel::Blinn,
-                    ..Default::default()
-                });
+            let mtl_path = p.join(material_library);
+            let bytes = self.remove_bytes(&mtl_path)?;
+            for material in parse_mtl(&mtl_path, &text_from_bytes(&mtl_path, bytes)?)? {
+                cpu_materials.push(parse_material(self, p, material)?);
             }
         }
 
         // Parse meshes
         let mut cpu_meshes = Vec::new();
         for object in obj.objects.iter() {
-            // Objects consisting of several meshes with different materials
+            // Group the parts of the object with the same material
+            let mut groups: Vec<(Option<String>, Vec<&wavefront_obj::obj::Geometry>)> = Vec::new();
             for mesh in object.geometry.iter() {
-                // All meshes with different materials
+                if let Some(group) = groups
+                    .iter_mut()
+                    .find(|(material_name, _)| material_name == &mesh.material_name)
+                {
+                    group.1.push(mesh);
+                } else {
+                    groups.push((mesh.material_name.clone(), vec![mesh]));
+                }
+            }
+
+            for (material_name, meshes) in groups {
+                // All triangles with the same material
                 let mut positions = Vec::new();
                 let mut normals = Vec::new();
                 let mut uvs = Vec::new();
                 let mut indices = Vec::new();
+                let mut has_normals = false;
+                let mut has_uvs = false;
 
-                let mut map: HashMap<usize, usize> = HashMap::new();
-
-                let mut process = |i: wavefront_obj::obj::VTNIndex| {
-                    let mut index = map.get(&i.0).map(|v| *v);
-
-                    let uvw = i.1.map(|tex_index| object.tex_vertices[tex_index]);
-                    let normal = i.2.map(|normal_index| object.normals[normal_index]);
-
-                    if let Some(ind) = index {
-                        if let Some(tex) = uvw {
-                            if ((uvs[ind * 2] - tex.u as f32) as f32).abs() > std::f32::EPSILON
-                                || ((uvs[ind * 2 + 1] - tex.v as f32) as f32).abs()
-                                    > std::f32::EPSILON
-                            {
-                                index = None;
-                            }
-                        }
-                        if let Some(n) = normal {
-                            if ((normals[ind * 3] - n.x as f32) as f32).abs() > std::f32::EPSILON
-                                || ((normals[ind * 3 + 1] - n.y as f32) as f32).abs()
-                                    > std::f32::EPSILON
-                                || ((normals[ind * 3 + 2] - n.z as f32) as f32).abs()
-                                    > std::f32::EPSILON
-                            {
-                                index = None;
-                            }
-                        }
+                let mut map: HashMap<wavefront_obj::obj::VTNIndex, u32> = HashMap::new();
+                let mut process = |i: wavefront_obj::obj::VTNIndex| -> ThreeDResult<()> {
+                    if i.0 >= object.vertices.len()
+                        || i.1.map(|t| t >= object.tex_vertices.len()).unwrap_or(false)
+                        || i.2.map(|n| n >= object.normals.len()).unwrap_or(false)
+                    {
+                        Err(IOError::Corrupt(
+                            path.to_string_lossy().to_string(),
+                            format!("the object {} contains an invalid index", object.name),
+                        ))?;
                     }
-
-                    if index.is_none() {
-                        index = Some(positions.len() / 3);
-                        map.insert(i.0, index.unwrap());
+                    let index = *map.entry(i).or_insert_with(|| {
                         let position = object.vertices[i.0];
                         positions.push(position.x as f32);
                         positions.push(position.y as f32);
                         positions.push(position.z as f32);
 
-                        if let Some(tex) = uvw {
+                        if let Some(tex) = i.1.map(|tex_index| object.tex_vertices[tex_index]) {
                             uvs.push(tex.u as f32);
                             uvs.push(1.0 - tex.v as f32);
+                            has_uvs = true;
+                        } else {
+                            uvs.push(0.0);
+                            uvs.push(0.0);
                         }
-                        if let Some(n) = normal {
+                        if let Some(n) = i.2.map(|normal_index| object.normals[normal_index]) {
                             normals.push(n.x as f32);
                             normals.push(n.y as f32);
                             normals.push(n.z as f32);
+                            has_normals = true;
+                        } else {
+                            normals.push(0.0);
+                            normals.push(0.0);
+                            normals.push(0.0);
                         }
-                    }
-
-                    indices.push(index.unwrap() as u32);
+                        (positions.len() / 3 - 1) as u32
+                    });
+                    indices.push(index);
+                    Ok(())
                 };
-                for shape in mesh.shapes.iter() {
-                    // All triangles with same material
-                    match shape.primitive {
-                        wavefront_obj::obj::Primitive::Triangle(i0, i1, i2) => {
-                            process(i0);
-                            process(i1);
-                            process(i2);
+                for mesh in meshes {
+                    for shape in mesh.shapes.iter() {
+                        match shape.primitive {
+                            wavefront_obj::obj::Primitive::Triangle(i0, i1, i2) => {
+                                process(i0)?;
+                                process(i1)?;
+                                process(i2)?;
+                            }
+                            _ => {}
                         }
-                        _ => {}
                     }
                 }
 
                 cpu_meshes.push(CPUMesh {
                     name: object.name.to_string(),
-                    material_name: mesh.material_name.clone(),
+                    material_name,
                     positions,
                     indices: Some(Indices::U32(indices)),
-                    normals: Some(normals),
-                    uvs: Some(uvs),
-                    colors: None,
-                    tangents: None,
+                    normals: if has_normals { Some(normals) } else { None },
+                    uvs: if has_uvs { Some(uvs) } else { None },
+                    ..Default::default()
                 });
             }
         }
         Ok((cpu_meshes, cpu_materials))
     }
 }
+
+fn text_from_bytes(path: &Path, bytes: Vec<u8>) -> ThreeDResult<String> {
+    Ok(String::from_utf8(bytes)
+        .map_err(|_| IOError::InvalidUtf8(path.to_string_lossy().to_string()))?)
+}
+
+///
+/// The statements of a material in an .mtl file which are used when converting it into a [CPUMaterial].
+///
+#[derive(Default)]
+struct MtlMaterial {
+    name: String,
+    diffuse: Option<[f32; 3]>,
+    specular: Option<[f32; 3]>,
+    emissive: Option<[f32; 3]>,
+    specular_exponent: Option<f32>,
+    dissolve: Option<f32>,
+    transparency: Option<f32>,
+    roughness: Option<f32>,
+    metallic: Option<f32>,
+    diffuse_map: Option<String>,
+    emissive_map: Option<String>,
+    roughness_map: Option<String>,
+    metallic_map: Option<String>,
+    normal_map: Option<String>,
+    bump_map: Option<String>,
+}
+
+fn parse_mtl(path: &Path, text: &str) -> ThreeDResult<Vec<MtlMaterial>> {
+    let mut materials: Vec<MtlMaterial> = Vec::new();
+    for (line_number, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        let (keyword, args) = match line.find(char::is_whitespace) {
+            Some(i) => (&line[..i], line[i..].trim()),
+            None => (line, ""),
+        };
+        if keyword.is_empty() {
+            continue;
+        }
+        if keyword == "newmtl" {
+            materials.push(MtlMaterial {
+                name: args.to_string(),
+                ..Default::default()
+            });
+            continue;
+        }
+        let material = materials.last_mut().ok_or_else(|| {
+            IOError::Mtl(
+                path.to_string_lossy().to_string(),
+                format!(
+                    "line {}: {} specified before newmtl",
+                    line_number + 1,
+                    keyword
+                ),
+            )
+        })?;
+        let number = |args: &str| {
+            args.split_whitespace()
+                .next()
+                .and_then(|v| v.parse::<f32>().ok())
+                .ok_or_else(|| {
+                    IOError::Mtl(
+                        path.to_string_lossy().to_string(),
+                        format!(
+                            "line {}: expected a number after {}",
+                            line_number + 1,
+                            keyword
+                        ),
+                    )
+                })
+        };
+        let color = |args: &str| {
+            let values = args
+                .split_whitespace()
+                .map(|v| v.parse::<f32>())
+                .collect::<Result<Vec<_>, _>>()
+                .ok()
+                .filter(|values| values.len() > 0)
+                .ok_or_else(|| {
+                    IOError::Mtl(
+                        path.to_string_lossy().to_string(),
+                        format!(
+                            "line {}: expected a color after {}",
+                            line_number + 1,
+                            keyword
+                        ),
+                    )
+                })?;
+            // A single value means that all three channels have the same value
+            Ok::<_, IOError>([
+                values[0],
+                *values.get(1).unwrap_or(&values[0]),
+                *values.get(2).unwrap_or(&values[0]),
+            ])
+        };
+        match keyword.to_lowercase().as_str() {
+            "kd" => material.diffuse = Some(color(args)?),
+            "ks" => material.specular = Some(color(args)?),
+            "ke" => material.emissive = Some(color(args)?),
+            "ns" => material.specular_exponent = Some(number(args)?),
+            "d" => material.dissolve = Some(number(args)?),
+            "tr" => material.transparency = Some(number(args)?),
+            "pr" => material.roughness = Some(number(args)?),
+            "pm" => material.metallic = Some(number(args)?),
+            "map_kd" => material.diffuse_map = Some(texture_path(args)),
+            "map_ke" => material.emissive_map = Some(texture_path(args)),
+            "map_pr" => material.roughness_map = Some(texture_path(args)),
+            "map_pm" => material.metallic_map = Some(texture_path(args)),
+            "norm" => material.normal_map = Some(texture_path(args)),
+            "bump" | "map_bump" => material.bump_map = Some(texture_path(args)),
+            _ => {}
+        }
+    }
+    Ok(materials)
+}
+
+///
+/// Returns the path of a texture statement in an .mtl file, ie. the arguments without the texture options.
+///
+fn texture_path(args: &str) -> String {
+    let mut words = args.split_whitespace().peekable();
+    let mut path = Vec::new();
+    while let Some(word) = words.next() {
+        if path.is_empty() && word.starts_with('-') {
+            let argument_count = match word {
+                "-mm" => 2,
+                "-o" | "-s" | "-t" => 3,
+                _ => 1,
+            };
+            for _ in 0..argument_count {
+                // Some options have optional numeric arguments
+                if argument_count > 1
+                    && words
+                        .peek()
+                        .map(|w| w.parse::<f32>().is_err())
+                        .unwrap_or(true)
+                {
+                    break;
+                }
+                words.next();
+            }
+        } else {
+            path.push(word);
+        }
+    }
+    path.join(" ")
+}
+
+fn parse_material(
+    loaded: &mut Loaded,
+    path: &Path,
+    material: MtlMaterial,
+) -> ThreeDResult<CPUMaterial> {
+    let mut load_texture = |name: &Option<String>| -> ThreeDResult<Option<CPUTexture<u8>>> {
+        Ok(if let Some(ref texture_name) = name {
+            Some(loaded.image(path.join(texture_name))?)
+        } else {
+            None
+        })
+    };
+    let albedo_texture = load_texture(&material.diffuse_map)?;
+    let emissive_texture = load_texture(&material.emissive_map)?;
+    let normal_texture = if material.normal_map.is_some() {
+        load_texture(&material.normal_map)?
+    } else {
+        load_texture(&material.bump_map)?
+    };
+    let roughness_texture = load_texture(&material.roughness_map)?;
+    let metallic_texture = load_texture(&material.metallic_map)?;
+    let is_pbr = material.roughness.is_some()
+        || material.metallic.is_some()
+        || roughness_texture.is_some()
+        || metallic_texture.is_some()
+        || material.normal_map.is_some();
+
+    let diffuse = material.diffuse.unwrap_or([1.0, 1.0, 1.0]);
+    let alpha = material
+        .dissolve
+        .or(material.transparency.map(|t| 1.0 - t))
+        .unwrap_or(1.0);
+    let emissive = material.emissive.unwrap_or(if emissive_texture.is_some() {
+        [1.0, 1.0, 1.0]
+    } else {
+        [0.0, 0.0, 0.0]
+    });
+    let roughness = material.roughness.unwrap_or_else(|| {
+        if roughness_texture.is_some() {
+            1.0
+        } else {
+            match material.specular_exponent {
+                Some(exponent) if exponent > 0.1 => (1.999 / exponent).sqrt().min(1.0),
+                _ => 1.0,
+            }
+        }
+    });
+    let metallic = material.metallic.unwrap_or_else(|| {
+        if metallic_texture.is_some() {
+            1.0
+        } else if is_pbr {
+            0.0
+        } else {
+            material
+                .specular
+                .map(|s| (s[0] + s[1] + s[2]) / 3.0)
+                .unwrap_or(0.0)
+        }
+    });
+    let metallic_roughness_texture = if roughness_texture.is_some() || metallic_texture.is_some() {
+        Some(metallic_roughness_texture(
+            roughness_texture.as_ref(),
+            metallic_texture.as_ref(),
+        ))
+    } else {
+        None
+    };
+
+    Ok(CPUMaterial {
+        name: material.name,
+        albedo: Color::from_rgba_slice(&[
+            diffuse[0].max(0.0).min(1.0),
+            diffuse[1].max(0.0).min(1.0),
+            diffuse[2].max(0.0).min(1.0),
+            alpha.max(0.0).min(1.0),
+        ]),
+        albedo_texture,
+        metallic: metallic.max(0.0).min(1.0),
+        roughness: roughness.max(0.0).min(1.0),
+        metallic_roughness_texture,
+        normal_texture,
+        emissive: Color::from_rgb_slice(&[
+            emissive[0].max(0.0).min(1.0),
+            emissive[1].max(0.0).min(1.0),
+            emissive[2].max(0.0).min(1.0),
+        ]),
+        emissive_texture,
+        lighting_model: if is_pbr {
+            LightingModel::Cook(
+                NormalDistributionFunction::TrowbridgeReitzGGX,
+                GeometryFunction::SmithSchlickGGX,
+            )
+        } else {
+            LightingModel::Blinn
+        },
+        ..Default::default()
+    })
+}
+
+///
+/// Combines the separate roughness and metallic textures of an .mtl file into one texture with the roughness values in the green channel
+/// and the metallic values in the blue channel as expected by [CPUMaterial::metallic_roughness_texture].
+/// The first channel of each texture is used and if the textures differ in size, the metallic texture is resampled to the size of the roughness texture.
+///
+fn metallic_roughness_texture(
+    roughness_texture: Option<&CPUTexture<u8>>,
+    metallic_texture: Option<&CPUTexture<u8>>,
+) -> CPUTexture<u8> {
+    let (width, height) = roughness_texture
+        .or(metallic_texture)
+        .map(|t| (t.width, t.height))
+        .unwrap_or((1, 1));
+    let sample = |texture: Option<&CPUTexture<u8>>, x: u32, y: u32| {
+        texture
+            .map(|t| {
+                let tx = x * t.width / width;
+                let ty = y * t.height / height;
+                t.data[((ty * t.width + tx) * t.format.color_channel_count()) as usize]
+            })
+            .unwrap_or(255)
+    };
+    let mut data = Vec::with_capacity((width * height * 3) as usize);
+    for y in 0..height {
+        for x in 0..width {
+            data.push(255);
+            data.push(sample(roughness_texture, x, y));
+            data.push(sample(metallic_texture, x, y));
+        }
+    }
+    CPUTexture {
+        data,
+        width,
+        height,
+        format: Format::RGB,
+        ..Default::default()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Saver {
+    ///
+    /// Saves the given meshes and materials as an .obj file at the given path and an .mtl material file with the same name next to it.
+    /// The positions, normals, uv coordinates and indices of the meshes are saved while other vertex attributes are ignored.
+    /// The textures of the materials are saved as .png files next to the .obj file using [Saver::save_pixels].
+    ///
+    pub fn save_obj(
+        path: impl AsRef<Path>,
+        cpu_meshes: &[CPUMesh],
+        cpu_materials: &[CPUMaterial],
+    ) -> ThreeDResult<()> {
+        use std::fmt::Write;
+        let path = path.as_ref();
+        let dir = path.parent().unwrap_or(Path::new("./"));
+        let filename = path
+            .file_stem()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or("mesh".to_string());
+
+        let mut obj = String::new();
+        if cpu_materials.len() > 0 {
+            let mut mtl = String::new();
+            for cpu_material in cpu_materials {
+                let name = file_safe_name(&cpu_material.name);
+                let albedo = cpu_material.albedo;
+                let emissive = cpu_material.emissive;
+                writeln!(mtl, "newmtl {}", cpu_material.name)?;
+                writeln!(
+                    mtl,
+                    "Ns {}",
+                    (1.999 / cpu_material.roughness.max(0.001).powi(2)).min(1000.0)
+                )?;
+                writeln!(mtl, "Ka 0 0 0")?;
+                writeln!(
+                    mtl,
+                    "Kd {} {} {}",
+                    albedo.r as f32 / 255.0,
+                    albedo.g as f32 / 255.0,
+                    albedo.b as f32 / 255.0
+                )?;
+                writeln!(
+                    mtl,
+                    "Ks {} {} {}",
+                    cpu_material.metallic, cpu_material.metallic, cpu_material.metallic
+                )?;
+                writeln!(
+                    mtl,
+                    "Ke {} {} {}",
+                    emissive.r as f32 / 255.0,
+                    emissive.g as f32 / 255.0,
+                    emissive.b as f32 / 255.0
+                )?;
+                writeln!(mtl, "Ni 1.5")?;
+                writeln!(mtl, "d {}", albedo.a as f32 / 255.0)?;
+                writeln!(mtl, "illum 2")?;
+                if let Some(ref cpu_texture) = cpu_material.albedo_texture {
+                    let texture_name = format!("{}_{}_albedo.png", filename, name);
+                    Self::save_texture(dir.join(&texture_name), cpu_texture)?;
+                    writeln!(mtl, "map_Kd {}", texture_name)?;
+                }
+                if let Some(ref cpu_texture) = cpu_material.emissive_texture {
+                    let texture_name = format!("{}_{}_emissive.png", filename, name);
+                    Self::save_texture(dir.join(&texture_name), cpu_texture)?;
+                    writeln!(mtl, "map_Ke {}", texture_name)?;
+                }
+                if let Some(ref cpu_texture) = cpu_material.normal_texture {
+                    let texture_name = format!("{}_{}_normal.png", filename, name);
+                    Self::save_texture(dir.join(&texture_name), cpu_texture)?;
+                    writeln!(mtl, "bump {}", texture_name)?;
+                }
+                writeln!(mtl)?;
+            }
+            let mtl_name = format!("{}.mtl", filename);
+            Self::save_file(dir.join(&mtl_name), mtl.as_bytes())?;
+            writeln!(obj, "mtllib {}", mtl_name)?;
+        }
+
+        // The indices in an .obj file are global and start at 1
+        let mut position_offset = 1;
+        let mut uv_offset = 1;
+        let mut normal_offset = 1;
+        for cpu_mesh in cpu_meshes {
+            writeln!(obj, "o {}", cpu_mesh.name)?;
+            if let Some(ref material_name) = cpu_mesh.material_name {
+                writeln!(obj, "usemtl {}", material_name)?;
+            }
+            for p in cpu_mesh.positions.chunks_exact(3) {
+                writeln!(obj, "v {} {} {}", p[0], p[1], p[2])?;
+            }
+            if let Some(ref uvs) = cpu_mesh.uvs {
+                for uv in uvs.chunks_exact(2) {
+                    writeln!(obj, "vt {} {}", uv[0], 1.0 - uv[1])?;
+                }
+            }
+            if let Some(ref normals) = cpu_mesh.normals {
+                for n in normals.chunks_exact(3) {
+                    writeln!(obj, "vn {} {} {}", n[0], n[1], n[2])?;
+                }
+            }
+            let vertex_count = cpu_mesh.positions.len() / 3;
+            let indices = cpu_mesh
+                .indices
+                .as_ref()
+                .map(|indices| indices.into_u32())
+                .unwrap_or((0..vertex_count as u32).collect());
+            for triangle in indices.chunks_exact(3) {
+                write!(obj, "f")?;
+                for index in triangle {
+                    let index = *index as usize;
+                    write!(obj, " {}", position_offset + index)?;
+                    match (cpu_mesh.uvs.is_some(), cpu_mesh.normals.is_some()) {
+                        (true, true) => {
+                            write!(obj, "/{}/{}", uv_offset + index, normal_offset + index)?
+                        }
+                        (true, false) => write!(obj, "/{}", uv_offset + index)?,
+                        (false, true) => write!(obj, "//{}", normal_offset + index)?,
+                        (false, false) => {}
+                    }
+                }
+                writeln!(obj)?;
+            }
+            position_offset += vertex_count;
+            if cpu_mesh.uvs.is_some() {
+                uv_offset += vertex_count;
+            }
+            if cpu_mesh.normals.is_some() {
+                normal_offset += vertex_count;
+            }
+        }
+        Self::save_file(path, obj.as_bytes())?;
+        Ok(())
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn file_safe_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect()
+}
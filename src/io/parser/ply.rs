@@ -0,0 +1,674 @@
+use crate::core::*;
+use crate::io::*;
+use std::path::Path;
+
+///
+/// The possible encodings of the data in a .ply file.
+///
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum PlyFormat {
+    /// Human readable text.
+    Ascii,
+    /// Binary data in little endian byte order.
+    BinaryLittleEndian,
+    /// Binary data in big endian byte order.
+    BinaryBigEndian,
+}
+
+impl Loaded {
+    ///
+    /// Deserialize a loaded .ply file resource in either ascii or binary format into a mesh.
+    /// The vertex positions, normals, uv coordinates and colors are read from the vertex element and the faces,
+    /// which are triangulated if they have more than three vertices, are read into the indices of the mesh.
+    ///
+    /// If the file does not contain any faces, the returned mesh is a point cloud, ie. the mesh does not have indices and
+    /// each vertex represents a single point.
+    ///
+    pub fn ply(&mut self, path: impl AsRef<Path>) -> ThreeDResult<CPUMesh> {
+        let name = path
+            .as_ref()
+            .file_stem()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let bytes = self.get_bytes(path.as_ref())?;
+        Ok(parse_ply(name, bytes)
+            .map_err(|e| IOError::Ply(path.as_ref().to_string_lossy().to_string(), e))?)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Saver {
+    ///
+    /// Saves the given mesh as a .ply file in the given format.
+    /// The vertex positions, normals, uv coordinates and colors are saved if present and the triangles are saved as faces if the mesh has indices.
+    /// A mesh without indices is saved as a point cloud, ie. without faces.
+    ///
+    pub fn save_ply(
+        path: impl AsRef<Path>,
+        cpu_mesh: &CPUMesh,
+        format: PlyFormat,
+    ) -> ThreeDResult<()> {
+        Self::save_file(path, &Self::serialize_ply(cpu_mesh, format))
+    }
+
+    ///
+    /// Serializes the given mesh into the contents of a .ply file in the given format, see [save_ply](Self::save_ply).
+    ///
+    pub fn serialize_ply(cpu_mesh: &CPUMesh, format: PlyFormat) -> Vec<u8> {
+        let vertex_count = cpu_mesh.positions.len() / 3;
+        let indices = cpu_mesh.indices.as_ref().map(|indices| indices.into_u32());
+        let mut header = String::from("ply\n");
+        header.push_str(match format {
+            PlyFormat::Ascii => "format ascii 1.0\n",
+            PlyFormat::BinaryLittleEndian => "format binary_little_endian 1.0\n",
+            PlyFormat::BinaryBigEndian => "format binary_big_endian 1.0\n",
+        });
+        header.push_str("comment Created by three-d\n");
+        header.push_str(&format!("element vertex {}\n", vertex_count));
+        header.push_str("property float x\nproperty float y\nproperty float z\n");
+        if cpu_mesh.normals.is_some() {
+            header.push_str("property float nx\nproperty float ny\nproperty float nz\n");
+        }
+        if cpu_mesh.uvs.is_some() {
+            header.push_str("property float s\nproperty float t\n");
+        }
+        if cpu_mesh.colors.is_some() {
+            header.push_str("property uchar red\nproperty uchar green\nproperty uchar blue\nproperty uchar alpha\n");
+        }
+        if let Some(ref indices) = indices {
+            header.push_str(&format!("element face {}\n", indices.len() / 3));
+            header.push_str("property list uchar uint vertex_indices\n");
+        }
+        header.push_str("end_header\n");
+
+        let mut writer = PlyWriter {
+            format,
+            bytes: header.into_bytes(),
+        };
+        for i in 0..vertex_count {
+            writer.write_f32s(&cpu_mesh.positions[i * 3..i * 3 + 3]);
+            if let Some(ref normals) = cpu_mesh.normals {
+                writer.write_f32s(&normals[i * 3..i * 3 + 3]);
+            }
+            if let Some(ref uvs) = cpu_mesh.uvs {
+                writer.write_f32s(&uvs[i * 2..i * 2 + 2]);
+            }
+            if let Some(ref colors) = cpu_mesh.colors {
+                writer.write_u8s(&colors[i * 4..i * 4 + 4]);
+            }
+            writer.end_line();
+        }
+        if let Some(ref indices) = indices {
+            for triangle in indices.chunks_exact(3) {
+                writer.write_u8s(&[3]);
+                writer.write_u32s(triangle);
+                writer.end_line();
+            }
+        }
+        writer.bytes
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum PlyType {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl PlyType {
+    fn parse(name: &str) -> Result<Self, String> {
+        Ok(match name {
+            "char" | "int8" => Self::I8,
+            "uchar" | "uint8" => Self::U8,
+            "short" | "int16" => Self::I16,
+            "ushort" | "uint16" => Self::U16,
+            "int" | "int32" => Self::I32,
+            "uint" | "uint32" => Self::U32,
+            "float" | "float32" => Self::F32,
+            "double" | "float64" => Self::F64,
+            _ => Err(format!("unknown property type {}", name))?,
+        })
+    }
+
+    fn size(&self) -> usize {
+        match self {
+            Self::I8 | Self::U8 => 1,
+            Self::I16 | Self::U16 => 2,
+            Self::I32 | Self::U32 | Self::F32 => 4,
+            Self::F64 => 8,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum PlyProperty {
+    Scalar(String, PlyType),
+    List(String, PlyType, PlyType),
+}
+
+impl PlyProperty {
+    fn name(&self) -> &str {
+        match self {
+            Self::Scalar(name, _) => name,
+            Self::List(name, _, _) => name,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct PlyElement {
+    name: String,
+    count: usize,
+    properties: Vec<PlyProperty>,
+}
+
+fn parse_ply(name: String, bytes: &[u8]) -> Result<CPUMesh, String> {
+    let (header_end, body_start) =
+        find_end_header(bytes).ok_or_else(|| "missing end_header".to_string())?;
+    let header = std::str::from_utf8(&bytes[..header_end])
+        .map_err(|_| "the header is not valid text".to_string())?;
+    let (format, elements) = parse_header(header)?;
+    let mut reader = PlyReader::new(format, &bytes[body_start..])?;
+
+    let mut cpu_mesh = CPUMesh {
+        name,
+        ..Default::default()
+    };
+    for element in elements.iter() {
+        match element.name.as_str() {
+            "vertex" => read_vertices(&mut reader, element, &mut cpu_mesh)?,
+            "face" => read_faces(&mut reader, element, &mut cpu_mesh)?,
+            _ => {
+                for _ in 0..element.count {
+                    for property in element.properties.iter() {
+                        reader.read_property(property)?;
+                    }
+                }
+            }
+        }
+    }
+    let vertex_count = cpu_mesh.positions.len() / 3;
+    if let Some(ref indices) = cpu_mesh.indices {
+        if indices
+            .into_u32()
+            .iter()
+            .any(|i| *i as usize >= vertex_count)
+        {
+            Err("a face refers to a vertex which does not exist".to_string())?;
+        }
+    }
+    Ok(cpu_mesh)
+}
+
+///
+/// Returns the start of the `end_header` line and the start of the body, which is the byte after that line.
+/// Only a line which consists of `end_header` ends the header, so comments and obj_info lines may contain the keyword.
+///
+fn find_end_header(bytes: &[u8]) -> Option<(usize, usize)> {
+    let mut line_start = 0;
+    while line_start < bytes.len() {
+        let line_end = bytes[line_start..]
+            .iter()
+            .position(|b| *b == b'\n')
+            .map(|i| line_start + i)
+            .unwrap_or(bytes.len());
+        let line = std::str::from_utf8(&bytes[line_start..line_end]);
+        if line
+            .map(|line| line.trim() == "end_header")
+            .unwrap_or(false)
+        {
+            return Some((line_start, (line_end + 1).min(bytes.len())));
+        }
+        line_start = line_end + 1;
+    }
+    None
+}
+
+fn parse_header(header: &str) -> Result<(PlyFormat, Vec<PlyElement>), String> {
+    let mut lines = header.lines().map(|line| line.trim());
+    if lines.next() != Some("ply") {
+        Err("missing ply magic number".to_string())?;
+    }
+    let mut format = None;
+    let mut elements: Vec<PlyElement> = Vec::new();
+    for line in lines {
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        match words.as_slice() {
+            ["format", "ascii", ..] => format = Some(PlyFormat::Ascii),
+            ["format", "binary_little_endian", ..] => format = Some(PlyFormat::BinaryLittleEndian),
+            ["format", "binary_big_endian", ..] => format = Some(PlyFormat::BinaryBigEndian),
+            ["element", name, count] => elements.push(PlyElement {
+                name: name.to_string(),
+                count: count
+                    .parse()
+                    .map_err(|_| format!("invalid element count {}", count))?,
+                properties: Vec::new(),
+            }),
+            ["property", "list", count_type, item_type, name] => elements
+                .last_mut()
+                .ok_or_else(|| "property defined before element".to_string())?
+                .properties
+                .push(PlyProperty::List(
+                    name.to_string(),
+                    PlyType::parse(count_type)?,
+                    PlyType::parse(item_type)?,
+                )),
+            ["property", data_type, name] => elements
+                .last_mut()
+                .ok_or_else(|| "property defined before element".to_string())?
+                .properties
+                .push(PlyProperty::Scalar(
+                    name.to_string(),
+                    PlyType::parse(data_type)?,
+                )),
+            _ => {}
+        }
+    }
+    let format = format.ok_or_else(|| "missing format".to_string())?;
+    Ok((format, elements))
+}
+
+fn read_vertices(
+    reader: &mut PlyReader,
+    element: &PlyElement,
+    cpu_mesh: &mut CPUMesh,
+) -> Result<(), String> {
+    let index_of = |names: &[&str]| {
+        element
+            .properties
+            .iter()
+            .position(|property| names.contains(&property.name()))
+    };
+    let position = [index_of(&["x"]), index_of(&["y"]), index_of(&["z"])];
+    let normal = [index_of(&["nx"]), index_of(&["ny"]), index_of(&["nz"])];
+    let uv = [
+        index_of(&["s", "u", "texture_u", "texture_s"]),
+        index_of(&["t", "v", "texture_v", "texture_t"]),
+    ];
+    let color = [
+        index_of(&["red", "r", "diffuse_red"]),
+        index_of(&["green", "g", "diffuse_green"]),
+        index_of(&["blue", "b", "diffuse_blue"]),
+    ];
+    let alpha = index_of(&["alpha", "a"]);
+    let float_color = color[0]
+        .map(|i| match element.properties[i] {
+            PlyProperty::Scalar(_, data_type) => {
+                data_type == PlyType::F32 || data_type == PlyType::F64
+            }
+            _ => false,
+        })
+        .unwrap_or(false);
+    let use_normals = normal.iter().all(|i| i.is_some());
+    let use_uvs = uv.iter().all(|i| i.is_some());
+    let use_colors = color.iter().all(|i| i.is_some());
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut uvs = Vec::new();
+    let mut colors = Vec::new();
+    let mut values = vec![0.0; element.properties.len()];
+    for _ in 0..element.count {
+        for (i, property) in element.properties.iter().enumerate() {
+            values[i] = reader.read_property(property)?;
+        }
+        for i in position.iter() {
+            positions.push(i.map(|i| values[i] as f32).unwrap_or(0.0));
+        }
+        if use_normals {
+            for i in normal.iter().flatten() {
+                normals.push(values[*i] as f32);
+            }
+        }
+        if use_uvs {
+            for i in uv.iter().flatten() {
+                uvs.push(values[*i] as f32);
+            }
+        }
+        if use_colors {
+            let scale = if float_color { 255.0 } else { 1.0 };
+            for i in color.iter().flatten() {
+                colors.push((values[*i] * scale).clamp(0.0, 255.0) as u8);
+            }
+            colors.push(
+                alpha
+                    .map(|i| (values[i] * scale).clamp(0.0, 255.0) as u8)
+                    .unwrap_or(255),
+            );
+        }
+    }
+    cpu_mesh.positions = positions;
+    if use_normals {
+        cpu_mesh.normals = Some(normals);
+    }
+    if use_uvs {
+        cpu_mesh.uvs = Some(uvs);
+    }
+    if use_colors {
+        cpu_mesh.colors = Some(colors);
+    }
+    Ok(())
+}
+
+fn read_faces(
+    reader: &mut PlyReader,
+    element: &PlyElement,
+    cpu_mesh: &mut CPUMesh,
+) -> Result<(), String> {
+    let mut indices = Vec::new();
+    for _ in 0..element.count {
+        for property in element.properties.iter() {
+            match property {
+                PlyProperty::List(name, count_type, item_type)
+                    if name == "vertex_indices" || name == "vertex_index" =>
+                {
+                    let count = reader.read_index(*count_type)?;
+                    let mut face = Vec::new();
+                    for _ in 0..count {
+                        face.push(reader.read_index(*item_type)?);
+                    }
+                    // Triangulate the polygon as a triangle fan
+                    for i in 2..face.len() {
+                        indices.push(face[0]);
+                        indices.push(face[i - 1]);
+                        indices.push(face[i]);
+                    }
+                }
+                _ => {
+                    reader.read_property(property)?;
+                }
+            }
+        }
+    }
+    if !indices.is_empty() {
+        cpu_mesh.indices = Some(Indices::U32(indices));
+    }
+    Ok(())
+}
+
+enum PlyReader<'a> {
+    Ascii(std::str::SplitWhitespace<'a>),
+    Binary(&'a [u8], bool),
+}
+
+impl<'a> PlyReader<'a> {
+    fn new(format: PlyFormat, body: &'a [u8]) -> Result<Self, String> {
+        Ok(match format {
+            PlyFormat::Ascii => Self::Ascii(
+                std::str::from_utf8(body)
+                    .map_err(|_| "the ascii data is not valid text".to_string())?
+                    .split_whitespace(),
+            ),
+            PlyFormat::BinaryLittleEndian => Self::Binary(body, true),
+            PlyFormat::BinaryBigEndian => Self::Binary(body, false),
+        })
+    }
+
+    ///
+    /// Reads the given property and returns the value if it is a scalar or the number of items if it is a list.
+    ///
+    fn read_property(&mut self, property: &PlyProperty) -> Result<f64, String> {
+        match property {
+            PlyProperty::Scalar(_, data_type) => self.read(*data_type),
+            PlyProperty::List(_, count_type, item_type) => {
+                let count = self.read_index(*count_type)?;
+                for _ in 0..count {
+                    self.read(*item_type)?;
+                }
+                Ok(count as f64)
+            }
+        }
+    }
+
+    ///
+    /// Reads a value which must be a non-negative integer, ie. a list count or a vertex index.
+    ///
+    fn read_index(&mut self, data_type: PlyType) -> Result<u32, String> {
+        let value = self.read(data_type)?;
+        if !(value >= 0.0 && value <= u32::MAX as f64 && value.fract() == 0.0) {
+            Err(format!("invalid index {}", value))?;
+        }
+        Ok(value as u32)
+    }
+
+    fn read(&mut self, data_type: PlyType) -> Result<f64, String> {
+        match self {
+            Self::Ascii(words) => {
+                let word = words
+                    .next()
+                    .ok_or_else(|| "unexpected end of data".to_string())?;
+                Ok(word
+                    .parse::<f64>()
+                    .map_err(|_| format!("invalid number {}", word))?)
+            }
+            Self::Binary(bytes, little_endian) => {
+                let size = data_type.size();
+                if bytes.len() < size {
+                    Err("unexpected end of data".to_string())?;
+                }
+                let mut b = [0u8; 8];
+                b[..size].copy_from_slice(&bytes[..size]);
+                if !*little_endian {
+                    b[..size].reverse();
+                }
+                *bytes = &bytes[size..];
+                Ok(match data_type {
+                    PlyType::I8 => b[0] as i8 as f64,
+                    PlyType::U8 => b[0] as f64,
+                    PlyType::I16 => i16::from_le_bytes([b[0], b[1]]) as f64,
+                    PlyType::U16 => u16::from_le_bytes([b[0], b[1]]) as f64,
+                    PlyType::I32 => i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64,
+                    PlyType::U32 => u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64,
+                    PlyType::F32 => f32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64,
+                    PlyType::F64 => f64::from_le_bytes(b),
+                })
+            }
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+struct PlyWriter {
+    format: PlyFormat,
+    bytes: Vec<u8>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl PlyWriter {
+    fn write_f32s(&mut self, values: &[f32]) {
+        for value in values {
+            match self.format {
+                PlyFormat::Ascii => self.write_text(&value.to_string()),
+                PlyFormat::BinaryLittleEndian => self.bytes.extend_from_slice(&value.to_le_bytes()),
+                PlyFormat::BinaryBigEndian => self.bytes.extend_from_slice(&value.to_be_bytes()),
+            }
+        }
+    }
+
+    fn write_u32s(&mut self, values: &[u32]) {
+        for value in values {
+            match self.format {
+                PlyFormat::Ascii => self.write_text(&value.to_string()),
+                PlyFormat::BinaryLittleEndian => self.bytes.extend_from_slice(&value.to_le_bytes()),
+                PlyFormat::BinaryBigEndian => self.bytes.extend_from_slice(&value.to_be_bytes()),
+            }
+        }
+    }
+
+    fn write_u8s(&mut self, values: &[u8]) {
+        for value in values {
+            match self.format {
+                PlyFormat::Ascii => self.write_text(&value.to_string()),
+                _ => self.bytes.push(*value),
+            }
+        }
+    }
+
+    fn write_text(&mut self, text: &str) {
+        if self.bytes.last().map(|b| *b != b'\n').unwrap_or(false) {
+            self.bytes.push(b' ');
+        }
+        self.bytes.extend_from_slice(text.as_bytes());
+    }
+
+    fn end_line(&mut self) {
+        if self.format == PlyFormat::Ascii {
+            self.bytes.push(b'\n');
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn triangle_mesh() -> CPUMesh {
+        CPUMesh {
+            name: "mesh".to_string(),
+            positions: vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 0.5],
+            indices: Some(Indices::U32(vec![0, 1, 2, 2, 3, 0])),
+            normals: Some(vec![
+                0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0, -1.0,
+            ]),
+            uvs: Some(vec![0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.25, 0.75]),
+            colors: Some(vec![
+                255, 0, 0, 255, 0, 255, 0, 255, 0, 0, 255, 128, 10, 20, 30, 40,
+            ]),
+            ..Default::default()
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn round_trip(cpu_mesh: &CPUMesh, format: PlyFormat) -> CPUMesh {
+        let mut loaded = Loaded::new();
+        loaded.insert_bytes("mesh.ply", Saver::serialize_ply(cpu_mesh, format));
+        loaded.ply("mesh.ply").unwrap()
+    }
+
+    fn assert_same_mesh(loaded: &CPUMesh, cpu_mesh: &CPUMesh) {
+        assert_eq!(loaded.name, cpu_mesh.name);
+        assert_eq!(loaded.positions, cpu_mesh.positions);
+        assert_eq!(
+            loaded.indices.as_ref().map(|i| i.into_u32()),
+            cpu_mesh.indices.as_ref().map(|i| i.into_u32())
+        );
+        assert_eq!(loaded.normals, cpu_mesh.normals);
+        assert_eq!(loaded.uvs, cpu_mesh.uvs);
+        assert_eq!(loaded.colors, cpu_mesh.colors);
+    }
+
+    fn parse_error(ply: &str) -> String {
+        let mut loaded = Loaded::new();
+        loaded.insert_bytes("mesh.ply", ply.as_bytes().to_vec());
+        match loaded.ply("mesh.ply") {
+            Err(e) => match e.downcast_ref::<IOError>() {
+                Some(IOError::Ply(path, message)) => {
+                    assert_eq!(path, "mesh.ply");
+                    message.clone()
+                }
+                _ => panic!("unexpected error {}", e),
+            },
+            Ok(_) => panic!("expected the .ply file to be rejected"),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    pub fn ascii_round_trip() {
+        let cpu_mesh = triangle_mesh();
+        assert_same_mesh(&round_trip(&cpu_mesh, PlyFormat::Ascii), &cpu_mesh);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    pub fn binary_little_endian_round_trip() {
+        let cpu_mesh = triangle_mesh();
+        assert_same_mesh(
+            &round_trip(&cpu_mesh, PlyFormat::BinaryLittleEndian),
+            &cpu_mesh,
+        );
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    pub fn binary_big_endian_round_trip() {
+        let cpu_mesh = triangle_mesh();
+        assert_same_mesh(
+            &round_trip(&cpu_mesh, PlyFormat::BinaryBigEndian),
+            &cpu_mesh,
+        );
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    pub fn point_cloud_round_trip() {
+        let cpu_mesh = CPUMesh {
+            name: "mesh".to_string(),
+            positions: vec![0.0, 1.0, 2.0, -3.0, 4.5, 6.0],
+            colors: Some(vec![1, 2, 3, 4, 5, 6, 7, 8]),
+            ..Default::default()
+        };
+        for format in [
+            PlyFormat::Ascii,
+            PlyFormat::BinaryLittleEndian,
+            PlyFormat::BinaryBigEndian,
+        ]
+        .iter()
+        {
+            let loaded = round_trip(&cpu_mesh, *format);
+            assert!(loaded.indices.is_none());
+            assert_same_mesh(&loaded, &cpu_mesh);
+        }
+    }
+
+    #[test]
+    pub fn read_ascii_polygons() {
+        let ply =
+            "ply\nformat ascii 1.0\ncomment an end_header inside a comment\nobj_info end_header\n\
+                   element vertex 4\nproperty float x\nproperty float y\nproperty float z\n\
+                   element face 1\nproperty list uchar int vertex_indices\nend_header\n\
+                   0 0 0\n1 0 0\n1 1 0\n0 1 0\n4 0 1 2 3\n";
+        let mut loaded = Loaded::new();
+        loaded.insert_bytes("quad.ply", ply.as_bytes().to_vec());
+        let cpu_mesh = loaded.ply("quad.ply").unwrap();
+        assert_eq!(cpu_mesh.positions.len(), 12);
+        assert_eq!(
+            cpu_mesh.indices.map(|i| i.into_u32()),
+            Some(vec![0, 1, 2, 0, 2, 3])
+        );
+    }
+
+    #[test]
+    pub fn read_invalid_indices() {
+        let header = "ply\nformat ascii 1.0\nelement vertex 3\nproperty float x\nproperty float y\nproperty float z\n\
+                      element face 1\nproperty list uchar int vertex_indices\nend_header\n\
+                      0 0 0\n1 0 0\n1 1 0\n";
+        for face in [
+            "3 0 1 -1",
+            "3 0 1 1.5",
+            "3 0 1 nan",
+            "3 0 1 inf",
+            "3 0 1 3",
+            "3 0 1 4294967296",
+            "-3 0 1 2",
+            "3 0 1",
+        ]
+        .iter()
+        {
+            parse_error(&format!("{}{}\n", header, face));
+        }
+    }
+
+    #[test]
+    pub fn read_missing_end_header() {
+        assert_eq!(
+            parse_error("ply\nformat ascii 1.0\ncomment end_header\nelement vertex 0\n"),
+            "missing end_header"
+        );
+    }
+}
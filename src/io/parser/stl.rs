@@ -0,0 +1,169 @@
+use crate::core::*;
+use crate::io::*;
+use std::path::Path;
+
+impl Loaded {
+    ///
+    /// Deserialize a loaded .stl file resource in either ascii or binary format into a mesh using the [stl_io](https://crates.io/crates/stl_io) crate.
+    /// Vertices with the exact same position are welded together, so the returned mesh is indexed and
+    /// [CPUMesh::compute_normals] will produce smooth normals if called.
+    /// Use [CPUMesh::compute_normals_with_crease_angle] or [CPUMesh::compute_flat_normals] instead to keep hard edges, for example of a CAD model.
+    /// The normals stored in the file are per face and are therefore ignored.
+    ///
+    pub fn stl(&mut self, path: impl AsRef<Path>) -> ThreeDResult<CPUMesh> {
+        let name = path
+            .as_ref()
+            .file_stem()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let bytes = self.get_bytes(path.as_ref())?;
+        let stl = stl_io::read_stl(&mut std::io::Cursor::new(bytes)).map_err(|e| {
+            IOError::Stl(path.as_ref().to_string_lossy().to_string(), e.to_string())
+        })?;
+
+        let mut positions = Vec::with_capacity(stl.vertices.len() * 3);
+        for vertex in stl.vertices.iter() {
+            positions.extend_from_slice(&vertex.0);
+        }
+        let mut indices = Vec::with_capacity(stl.faces.len() * 3);
+        for face in stl.faces.iter() {
+            indices.extend(face.vertices.iter().map(|i| *i as u32));
+        }
+        Ok(CPUMesh {
+            name,
+            positions,
+            indices: Some(Indices::U32(indices)),
+            ..Default::default()
+        })
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Saver {
+    ///
+    /// Saves the given mesh as a binary .stl file.
+    /// Only the triangles are saved, all other vertex attributes are ignored.
+    ///
+    pub fn save_stl(path: impl AsRef<Path>, cpu_mesh: &CPUMesh) -> ThreeDResult<()> {
+        Self::save_file(path, &Self::serialize_stl(cpu_mesh)?)
+    }
+
+    ///
+    /// Serializes the given mesh into the contents of a binary .stl file, see [save_stl](Self::save_stl).
+    ///
+    pub fn serialize_stl(cpu_mesh: &CPUMesh) -> ThreeDResult<Vec<u8>> {
+        let mut triangles = Vec::new();
+        cpu_mesh.for_each_triangle(|i0, i1, i2| {
+            let p0 = cpu_mesh.position(i0);
+            let p1 = cpu_mesh.position(i1);
+            let p2 = cpu_mesh.position(i2);
+            let normal = (p1 - p0).cross(p2 - p0);
+            let normal = if normal.magnitude2() > 0.0 {
+                normal.normalize()
+            } else {
+                normal
+            };
+            triangles.push(stl_io::Triangle {
+                normal: stl_io::Normal::new([normal.x, normal.y, normal.z]),
+                vertices: [
+                    stl_io::Vertex::new([p0.x, p0.y, p0.z]),
+                    stl_io::Vertex::new([p1.x, p1.y, p1.z]),
+                    stl_io::Vertex::new([p2.x, p2.y, p2.z]),
+                ],
+            });
+        });
+        let mut bytes = Vec::new();
+        stl_io::write_stl(&mut bytes, triangles.iter())?;
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn triangles(mesh: &CPUMesh) -> Vec<[Vec3; 3]> {
+        let mut triangles = Vec::new();
+        mesh.for_each_triangle(|i0, i1, i2| {
+            triangles.push([mesh.position(i0), mesh.position(i1), mesh.position(i2)])
+        });
+        triangles
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    pub fn binary_round_trip() {
+        let cube = CPUMesh::cube();
+        let bytes = Saver::serialize_stl(&cube).unwrap();
+        assert_eq!(bytes.len(), 84 + 12 * 50);
+        let mut loaded = Loaded::new();
+        loaded.insert_bytes("models/cube.stl", bytes);
+        let mesh = loaded.stl("models/cube.stl").unwrap();
+        assert_eq!(mesh.name, "cube");
+        assert_eq!(triangles(&mesh), triangles(&cube));
+        // The vertices at the same position are welded, so only the eight corners are left
+        assert_eq!(mesh.positions.len() / 3, 8);
+        assert!(mesh.normals.is_none());
+        mesh.validate().unwrap();
+    }
+
+    #[test]
+    pub fn ascii() {
+        let ascii = "solid tetrahedron
+  facet normal 0 0 -1
+    outer loop
+      vertex 0 0 0
+      vertex 0 1 0
+      vertex 1 0 0
+    endloop
+  endfacet
+  facet normal 0 -1 0
+    outer loop
+      vertex 0 0 0
+      vertex 1 0 0
+      vertex 0 0 1
+    endloop
+  endfacet
+  facet normal -1 0 0
+    outer loop
+      vertex 0 0 0
+      vertex 0 0 1
+      vertex 0 1 0
+    endloop
+  endfacet
+  facet normal 0.577 0.577 0.577
+    outer loop
+      vertex 1 0 0
+      vertex 0 1 0
+      vertex 0 0 1
+    endloop
+  endfacet
+endsolid tetrahedron
+";
+        let mut loaded = Loaded::new();
+        loaded.insert_bytes("tetrahedron.stl", ascii.as_bytes().to_vec());
+        let mesh = loaded.stl("tetrahedron.stl").unwrap();
+        assert_eq!(mesh.name, "tetrahedron");
+        assert_eq!(mesh.positions.len() / 3, 4);
+        assert_eq!(triangles(&mesh).len(), 4);
+        assert_eq!(
+            triangles(&mesh)[3],
+            [
+                vec3(1.0, 0.0, 0.0),
+                vec3(0.0, 1.0, 0.0),
+                vec3(0.0, 0.0, 1.0)
+            ]
+        );
+        mesh.validate().unwrap();
+    }
+
+    #[test]
+    pub fn invalid() {
+        let mut loaded = Loaded::new();
+        loaded.insert_bytes(
+            "invalid.stl",
+            b"solid broken\n  facet normal 0 0 1\n".to_vec(),
+        );
+        assert!(loaded.stl("invalid.stl").is_err());
+    }
+}
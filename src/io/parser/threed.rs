@@ -1,35 +1,95 @@
 use crate::core::*;
 use crate::io::*;
+use bincode::Options;
 use std::path::Path;
 
+///
+/// The magic number at the start of a .3d file in the versioned format, see [Saver::serialize_3d_scene].
+/// Files saved with [Saver::save_3d_file] in one of the legacy formats do not start with this magic number.
+///
+const MAGIC_NUMBER: &[u8; 8] = b"three-d\0";
+
+///
+/// The current version of the .3d format. Versions 1 to 3 are the legacy formats without a header.
+/// This version must be increased whenever the serialized types change.
+/// Files with any other version than this are rejected when loaded.
+///
+const VERSION: u32 = 4;
+
 impl Loaded {
+    ///
+    /// Deserialize a loaded .3d file resource (a custom binary format for `three-d`) into a scene hierarchy and a list of meshes and materials,
+    /// see [Saver::save_3d_scene].
+    /// Everything is restored exactly as it was saved, including the textures of the materials which are embedded in the file.
+    /// Files saved in one of the legacy formats using [Saver::save_3d_file] are also supported, in that case the scene is empty
+    /// and the texture of each material is loaded from the file next to the .3d file.
+    ///
+    pub fn three_d_scene(
+        &mut self,
+        path: impl AsRef<Path>,
+    ) -> ThreeDResult<(CPUScene, Vec<CPUMesh>, Vec<CPUMaterial>)> {
+        let bytes = self.get_bytes(path.as_ref())?;
+        if !bytes.starts_with(MAGIC_NUMBER) {
+            let (cpu_meshes, cpu_materials) = self.legacy_three_d(path)?;
+            return Ok((CPUScene::default(), cpu_meshes, cpu_materials));
+        }
+        let corrupt = |message: String| {
+            IOError::Corrupt(path.as_ref().to_string_lossy().to_string(), message)
+        };
+        let version = bytes
+            .get(8..12)
+            .map(|v| u32::from_le_bytes([v[0], v[1], v[2], v[3]]))
+            .ok_or_else(|| corrupt("unexpected end of file".to_owned()))?;
+        let payload = &bytes[12..];
+        let asset = match version {
+            VERSION => bincode::DefaultOptions::new()
+                .with_limit(payload.len() as u64)
+                .with_fixint_encoding()
+                .allow_trailing_bytes()
+                .deserialize::<ThreeDScene>(payload)
+                .map_err(|e| corrupt(e.to_string()))?,
+            _ => Err(corrupt(format!(
+                "version {} of the .3d format is not supported, the newest supported version is {}",
+                version, VERSION
+            )))?,
+        };
+        Ok((asset.scene, asset.meshes, asset.materials))
+    }
+
     ///
     /// Deserialize a loaded .3d file resource (a custom binary format for `three-d`) into a list of meshes and materials.
     ///
-    #[deprecated]
+    #[deprecated(note = "use three_d_scene instead")]
     pub fn three_d<P: AsRef<Path>>(
         &mut self,
         path: P,
     ) -> ThreeDResult<(Vec<CPUMesh>, Vec<CPUMaterial>)> {
+        self.legacy_three_d(path)
+    }
+
+    fn legacy_three_d(
+        &mut self,
+        path: impl AsRef<Path>,
+    ) -> ThreeDResult<(Vec<CPUMesh>, Vec<CPUMaterial>)> {
+        let corrupt = |message: String| {
+            IOError::Corrupt(path.as_ref().to_string_lossy().to_string(), message)
+        };
         let bytes = self.get_bytes(path.as_ref())?;
         let mut decoded = bincode::deserialize::<ThreeDMesh>(bytes)
             .or_else(|_| Self::deserialize_version2(bytes))
-            .or_else(|_| Self::deserialize_version1(bytes))?;
+            .or_else(|_| Self::deserialize_version1(bytes))
+            .map_err(|e| corrupt(e.to_string()))?;
 
-        if decoded.meshes.len() == 0 {
-            decoded = Self::deserialize_version1(&bytes)?;
+        if decoded.meshes.is_empty() {
+            decoded = Self::deserialize_version1(bytes).map_err(|e| corrupt(e.to_string()))?;
         }
 
         if decoded.magic_number != 61 {
-            Err(bincode::Error::new(bincode::ErrorKind::Custom(
-                "Corrupt file!".to_string(),
-            )))?;
+            Err(corrupt("invalid magic number".to_string()))?;
         }
 
-        if decoded.meshes.len() == 0 {
-            Err(bincode::Error::new(bincode::ErrorKind::Custom(
-                "No mesh data in file!".to_string(),
-            )))?;
+        if decoded.meshes.is_empty() {
+            Err(corrupt("no mesh data in the file".to_string()))?;
         }
 
         let mut cpu_meshes = Vec::new();
@@ -41,8 +101,7 @@ impl Loaded {
                 indices: mesh.indices.map(|i| Indices::U32(i)),
                 normals: mesh.normals,
                 uvs: mesh.uvs,
-                colors: None,
-                tangents: None,
+                ..Default::default()
             });
         }
 
@@ -97,13 +156,13 @@ impl Loaded {
                 magic_number: m.magic_number,
                 version: 3,
                 meshes: vec![ThreeDMeshSubMesh {
-                    indices: if m.indices.len() > 0 {
+                    indices: if !m.indices.is_empty() {
                         Some(m.indices)
                     } else {
                         None
                     },
                     positions: m.positions,
-                    normals: if m.normals.len() > 0 {
+                    normals: if !m.normals.is_empty() {
                         Some(m.normals)
                     } else {
                         None
@@ -118,26 +177,69 @@ impl Loaded {
 
 #[cfg(not(target_arch = "wasm32"))]
 impl Saver {
+    ///
+    /// Saves the given scene, meshes and materials as a .3d file (a custom binary format for `three-d`) which can be loaded again using [Loaded::three_d_scene].
+    /// All of the data is saved, including vertex colors, tangents, skinning data, morph targets, animations and the textures of the materials,
+    /// which makes it suitable for caching preprocessed assets.
+    /// Use an empty [CPUScene] if the meshes are not part of a scene hierarchy.
+    ///
+    pub fn save_3d_scene(
+        path: impl AsRef<Path>,
+        cpu_scene: &CPUScene,
+        cpu_meshes: &[CPUMesh],
+        cpu_materials: &[CPUMaterial],
+    ) -> ThreeDResult<()> {
+        Self::save_file(
+            path,
+            &Self::serialize_3d_scene(cpu_scene, cpu_meshes, cpu_materials)?,
+        )
+    }
+
+    ///
+    /// Serializes the given scene, meshes and materials into the contents of a .3d file, see [save_3d_scene](Self::save_3d_scene).
+    /// The file starts with a magic number and a version number followed by the data serialized using [bincode](https://crates.io/crates/bincode).
+    /// The bytes can be deserialized again by inserting them into a [Loaded] using [Loaded::insert_bytes] and calling [Loaded::three_d_scene].
+    ///
+    pub fn serialize_3d_scene(
+        cpu_scene: &CPUScene,
+        cpu_meshes: &[CPUMesh],
+        cpu_materials: &[CPUMaterial],
+    ) -> ThreeDResult<Vec<u8>> {
+        let mut bytes = MAGIC_NUMBER.to_vec();
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        bincode::serialize_into(
+            &mut bytes,
+            &ThreeDSceneRef {
+                scene: cpu_scene,
+                meshes: cpu_meshes,
+                materials: cpu_materials,
+            },
+        )?;
+        Ok(bytes)
+    }
+
     ///
     /// Saves the given meshes and materials as a .3d file.
     ///
-    #[deprecated]
+    #[deprecated(note = "use save_3d_scene instead")]
     pub fn save_3d_file<P: AsRef<Path>>(
         path: P,
         cpu_meshes: Vec<CPUMesh>,
         cpu_materials: Vec<CPUMaterial>,
     ) -> ThreeDResult<()> {
-        let dir = path.as_ref().parent().unwrap();
-        let filename = path.as_ref().file_stem().unwrap().to_str().unwrap();
+        let dir = path.as_ref().parent().unwrap_or(Path::new("./"));
+        let filename = path
+            .as_ref()
+            .file_stem()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or("mesh".to_string());
         for cpu_material in cpu_materials.iter() {
             if let Some(ref cpu_texture) = cpu_material.albedo_texture {
-                let number_of_channels =
-                    cpu_texture.data.len() as u32 / (cpu_texture.width * cpu_texture.height);
-                let format = match number_of_channels {
-                    1 => image::ColorType::L8,
-                    3 => image::ColorType::Rgb8,
-                    4 => image::ColorType::Rgba8,
-                    _ => unimplemented!(),
+                let format = match cpu_texture.format {
+                    Format::R => image::ColorType::L8,
+                    Format::RG => image::ColorType::La8,
+                    Format::RGB => image::ColorType::Rgb8,
+                    Format::RGBA => image::ColorType::Rgba8,
                 };
                 let tex_path = dir.join(format!("{}_{}.png", filename, cpu_material.name));
                 image::save_buffer(
@@ -149,7 +251,7 @@ impl Saver {
                 )?;
             }
         }
-        let bytes = Self::serialize(filename, cpu_meshes, cpu_materials)?;
+        let bytes = Self::serialize(&filename, cpu_meshes, cpu_materials)?;
         Self::save_file(dir.join(format!("{}.3d", filename)), &bytes)?;
         Ok(())
     }
@@ -201,6 +303,27 @@ impl Saver {
     }
 }
 
+///
+/// The payload of a .3d file in the current version of the format.
+///
+#[derive(serde::Deserialize)]
+struct ThreeDScene {
+    scene: CPUScene,
+    meshes: Vec<CPUMesh>,
+    materials: Vec<CPUMaterial>,
+}
+
+///
+/// The same as [ThreeDScene] but borrowing the data to avoid copying it when serializing.
+///
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(serde::Serialize)]
+struct ThreeDSceneRef<'a> {
+    scene: &'a CPUScene,
+    meshes: &'a [CPUMesh],
+    materials: &'a [CPUMaterial],
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
 struct ThreeDMesh {
     pub magic_number: u8,
@@ -254,3 +377,206 @@ struct ThreeDMeshV1 {
     pub positions: Vec<f32>,
     pub normals: Vec<f32>,
 }
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod test {
+    use super::*;
+
+    fn texture(data: Vec<u8>, format: Format) -> CPUTexture<u8> {
+        CPUTexture {
+            data,
+            width: 2,
+            height: 1,
+            format,
+            min_filter: Interpolation::Nearest,
+            mag_filter: Interpolation::Linear,
+            mip_map_filter: Some(Interpolation::Linear),
+            wrap_s: Wrapping::Repeat,
+            wrap_t: Wrapping::MirroredRepeat,
+        }
+    }
+
+    #[test]
+    pub fn three_d_scene_round_trip() {
+        let mesh = CPUMesh {
+            name: "skinned".to_string(),
+            material_name: Some("textured".to_string()),
+            positions: vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0],
+            indices: Some(Indices::U16(vec![0, 1, 2])),
+            normals: Some(vec![0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0]),
+            tangents: Some(vec![
+                1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, -1.0, 1.0, 0.0, 0.0, 1.0,
+            ]),
+            uvs: Some(vec![0.0, 0.0, 1.0, 0.0, 0.0, 1.0]),
+            colors: Some(vec![255, 0, 0, 255, 0, 255, 0, 128, 0, 0, 255, 0]),
+            joints: Some(vec![0, 1, 0, 0, 1, 0, 0, 0, 0, 1, 0, 0]),
+            weights: Some(vec![
+                0.5, 0.5, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.25, 0.75, 0.0, 0.0,
+            ]),
+            morph_targets: vec![
+                MorphTarget {
+                    positions: vec![0.0, 0.5, 0.0, 0.0, 0.5, 0.0, 0.0, 0.5, 0.0],
+                    normals: Some(vec![0.0, 0.1, 0.0, 0.0, 0.1, 0.0, 0.0, 0.1, 0.0]),
+                    tangents: None,
+                },
+                MorphTarget {
+                    positions: vec![0.25, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+                    normals: None,
+                    tangents: Some(vec![0.0, 0.2, 0.0, 0.0, 0.2, 0.0, 0.0, 0.2, 0.0]),
+                },
+            ],
+        };
+        let material = CPUMaterial {
+            name: "textured".to_string(),
+            albedo: Color::new(255, 128, 0, 255),
+            albedo_texture: Some(texture(vec![255, 0, 0, 255, 0, 255, 0, 128], Format::RGBA)),
+            normal_texture: Some(texture(vec![128, 128, 255, 128, 128, 255], Format::RGB)),
+            emissive_texture: Some(texture(vec![10, 20], Format::R)),
+            metallic: 0.25,
+            roughness: 0.75,
+            ..Default::default()
+        };
+        let scene = CPUScene {
+            name: "scene".to_string(),
+            nodes: vec![
+                CPUNode {
+                    name: "root".to_string(),
+                    children: vec![1, 2],
+                    meshes: vec![0],
+                    materials: vec![Some(0)],
+                    skin: Some(0),
+                    morph_weights: vec![0.5, 0.25],
+                    ..Default::default()
+                },
+                CPUNode {
+                    name: "joint0".to_string(),
+                    parent: Some(0),
+                    ..Default::default()
+                },
+                CPUNode {
+                    name: "joint1".to_string(),
+                    transformation: Mat4::from_translation(vec3(1.0, 0.0, 0.0)),
+                    parent: Some(0),
+                    ..Default::default()
+                },
+            ],
+            roots: vec![0],
+            animations: vec![CPUAnimation {
+                name: "wave".to_string(),
+                tracks: vec![CPUAnimationTrack {
+                    node: 0,
+                    translation: None,
+                    rotation: None,
+                    scale: None,
+                    morph_weights: Some(KeyFrames {
+                        interpolation: KeyFrameInterpolation::Linear,
+                        times: vec![0.0, 1.0],
+                        values: vec![vec![0.0, 0.0], vec![1.0, 0.5]],
+                    }),
+                }],
+            }],
+            skins: vec![CPUSkin {
+                name: "skin".to_string(),
+                joints: vec![1, 2],
+                inverse_bind_matrices: vec![
+                    Mat4::identity(),
+                    Mat4::from_translation(vec3(-1.0, 0.0, 0.0)),
+                ],
+            }],
+            ..Default::default()
+        };
+
+        let bytes =
+            Saver::serialize_3d_scene(&scene, std::slice::from_ref(&mesh), &[material]).unwrap();
+        let mut loaded = Loaded::new();
+        loaded.insert_bytes("scene.3d", bytes);
+        let (loaded_scene, loaded_meshes, loaded_materials) =
+            loaded.three_d_scene("scene.3d").unwrap();
+
+        assert_eq!(loaded_meshes.len(), 1);
+        let loaded_mesh = &loaded_meshes[0];
+        assert_eq!(loaded_mesh.name, mesh.name);
+        assert_eq!(loaded_mesh.material_name, mesh.material_name);
+        assert_eq!(loaded_mesh.positions, mesh.positions);
+        assert_eq!(
+            loaded_mesh.indices.as_ref().map(|i| i.into_u32()),
+            mesh.indices.as_ref().map(|i| i.into_u32())
+        );
+        assert_eq!(loaded_mesh.normals, mesh.normals);
+        assert_eq!(loaded_mesh.tangents, mesh.tangents);
+        assert_eq!(loaded_mesh.uvs, mesh.uvs);
+        assert_eq!(loaded_mesh.colors, mesh.colors);
+        assert_eq!(loaded_mesh.joints, mesh.joints);
+        assert_eq!(loaded_mesh.weights, mesh.weights);
+        assert_eq!(loaded_mesh.morph_targets.len(), mesh.morph_targets.len());
+        for (loaded, target) in loaded_mesh.morph_targets.iter().zip(&mesh.morph_targets) {
+            assert_eq!(loaded.positions, target.positions);
+            assert_eq!(loaded.normals, target.normals);
+            assert_eq!(loaded.tangents, target.tangents);
+        }
+
+        assert_eq!(loaded_materials.len(), 1);
+        let loaded_material = &loaded_materials[0];
+        assert_eq!(loaded_material.name, "textured");
+        assert_eq!(loaded_material.albedo, Color::new(255, 128, 0, 255));
+        assert_eq!(loaded_material.metallic, 0.25);
+        assert_eq!(loaded_material.roughness, 0.75);
+        for (loaded, expected) in [
+            (
+                &loaded_material.albedo_texture,
+                texture(vec![255, 0, 0, 255, 0, 255, 0, 128], Format::RGBA),
+            ),
+            (
+                &loaded_material.normal_texture,
+                texture(vec![128, 128, 255, 128, 128, 255], Format::RGB),
+            ),
+            (
+                &loaded_material.emissive_texture,
+                texture(vec![10, 20], Format::R),
+            ),
+        ]
+        .iter()
+        {
+            let loaded = loaded.as_ref().unwrap();
+            assert_eq!(loaded.data, expected.data);
+            assert_eq!(loaded.width, expected.width);
+            assert_eq!(loaded.height, expected.height);
+            assert_eq!(loaded.format, expected.format);
+            assert_eq!(loaded.min_filter, expected.min_filter);
+            assert_eq!(loaded.mag_filter, expected.mag_filter);
+            assert_eq!(loaded.mip_map_filter, expected.mip_map_filter);
+            assert_eq!(loaded.wrap_s, expected.wrap_s);
+            assert_eq!(loaded.wrap_t, expected.wrap_t);
+        }
+        assert!(loaded_material.occlusion_texture.is_none());
+
+        assert_eq!(loaded_scene.name, scene.name);
+        assert_eq!(loaded_scene.roots, scene.roots);
+        assert_eq!(loaded_scene.nodes.len(), scene.nodes.len());
+        for (loaded, node) in loaded_scene.nodes.iter().zip(&scene.nodes) {
+            assert_eq!(loaded.name, node.name);
+            assert_eq!(loaded.transformation, node.transformation);
+            assert_eq!(loaded.parent, node.parent);
+            assert_eq!(loaded.children, node.children);
+            assert_eq!(loaded.meshes, node.meshes);
+            assert_eq!(loaded.materials, node.materials);
+            assert_eq!(loaded.skin, node.skin);
+            assert_eq!(loaded.morph_weights, node.morph_weights);
+        }
+        assert_eq!(loaded_scene.skins.len(), 1);
+        assert_eq!(loaded_scene.skins[0].name, "skin");
+        assert_eq!(loaded_scene.skins[0].joints, scene.skins[0].joints);
+        assert_eq!(
+            loaded_scene.skins[0].inverse_bind_matrices,
+            scene.skins[0].inverse_bind_matrices
+        );
+        assert_eq!(loaded_scene.animations.len(), 1);
+        let track = &loaded_scene.animations[0].tracks[0];
+        assert_eq!(track.node, 0);
+        let morph_weights = track.morph_weights.as_ref().unwrap();
+        assert_eq!(morph_weights.interpolation, KeyFrameInterpolation::Linear);
+        assert_eq!(morph_weights.times, vec![0.0, 1.0]);
+        assert_eq!(morph_weights.values, vec![vec![0.0, 0.0], vec![1.0, 0.5]]);
+    }
+}
@@ -0,0 +1,372 @@
+use std::collections::HashMap;
+use std::path::{Component, Path};
+
+///
+/// A source of resources which the [Loader](crate::Loader) and [Loaded](crate::Loaded) use before the file system and urls.
+/// Implement this trait to load resources from for example an asset pack or an application specific fetcher.
+/// A closure with the signature of [ResourceResolver::resolve] also implements this trait.
+///
+/// The resolvers are also used to get the resources which are referenced by other resources,
+/// for example the textures and buffers of a .gltf file or the materials of an .obj file,
+/// so multi-file assets can be loaded from the same source without extracting them.
+///
+pub trait ResourceResolver: Send + Sync {
+    ///
+    /// Returns the bytes of the resource at the given path or `None` if the resource is not available from this resolver.
+    ///
+    fn resolve(&self, path: &Path) -> Option<std::io::Result<Vec<u8>>>;
+}
+
+impl<F: Fn(&Path) -> Option<std::io::Result<Vec<u8>>> + Send + Sync> ResourceResolver for F {
+    fn resolve(&self, path: &Path) -> Option<std::io::Result<Vec<u8>>> {
+        self(path)
+    }
+}
+
+///
+/// A [ResourceResolver] which contains a virtual file system in memory.
+/// The paths are compared after removing `.` and `..` components and any root, so `/textures/../a.png` and `a.png` refer to the same resource.
+///
+#[derive(Default, Debug)]
+pub struct MemoryResolver {
+    files: HashMap<String, Vec<u8>>,
+}
+
+impl MemoryResolver {
+    ///
+    /// Constructs a new empty virtual file system.
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// Inserts the given bytes as the resource at the given path, replacing any existing resource at that path.
+    ///
+    pub fn insert(&mut self, path: impl AsRef<Path>, bytes: Vec<u8>) {
+        self.files.insert(normalize(path.as_ref()), bytes);
+    }
+
+    ///
+    /// Returns the paths of all of the resources in this virtual file system.
+    ///
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.files.keys().map(|path| path.as_str())
+    }
+}
+
+impl ResourceResolver for MemoryResolver {
+    fn resolve(&self, path: &Path) -> Option<std::io::Result<Vec<u8>>> {
+        self.files
+            .get(&normalize(path))
+            .map(|bytes| Ok(bytes.clone()))
+    }
+}
+
+///
+/// A [ResourceResolver] which reads the resources from a .zip archive.
+/// The paths are relative to the root of the archive, see [MemoryResolver] for how the paths are compared.
+///
+#[cfg(feature = "archive-io")]
+#[cfg_attr(docsrs, doc(cfg(feature = "archive-io")))]
+pub struct ZipResolver {
+    archive: std::sync::Mutex<zip::ZipArchive<std::io::Cursor<Vec<u8>>>>,
+}
+
+#[cfg(feature = "archive-io")]
+impl ZipResolver {
+    ///
+    /// Constructs a new resolver from the bytes of a .zip archive.
+    ///
+    pub fn new(bytes: Vec<u8>) -> crate::ThreeDResult<Self> {
+        Ok(Self {
+            archive: std::sync::Mutex::new(zip::ZipArchive::new(std::io::Cursor::new(bytes))?),
+        })
+    }
+}
+
+#[cfg(feature = "archive-io")]
+impl ResourceResolver for ZipResolver {
+    fn resolve(&self, path: &Path) -> Option<std::io::Result<Vec<u8>>> {
+        use std::io::Read;
+        let mut archive = self.archive.lock().ok()?;
+        let mut file = archive.by_name(&normalize(path)).ok()?;
+        let mut bytes = Vec::new();
+        Some(file.read_to_end(&mut bytes).map(|_| bytes))
+    }
+}
+
+///
+/// A [ResourceResolver] which reads the resources from a .tar or a gzip compressed .tar.gz archive.
+/// All of the files in the archive are decompressed into memory when constructed.
+/// The paths are relative to the root of the archive, see [MemoryResolver] for how the paths are compared.
+///
+#[cfg(feature = "archive-io")]
+#[cfg_attr(docsrs, doc(cfg(feature = "archive-io")))]
+pub struct TarResolver {
+    files: MemoryResolver,
+}
+
+#[cfg(feature = "archive-io")]
+impl TarResolver {
+    ///
+    /// Constructs a new resolver from the bytes of a .tar or .tar.gz archive.
+    ///
+    pub fn new(bytes: &[u8]) -> crate::ThreeDResult<Self> {
+        use std::io::Read;
+        let reader: Box<dyn Read + '_> = if bytes.starts_with(&[0x1f, 0x8b]) {
+            Box::new(flate2::read::GzDecoder::new(bytes))
+        } else {
+            Box::new(bytes)
+        };
+        let mut files = MemoryResolver::new();
+        let mut archive = tar::Archive::new(reader);
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            if entry.header().entry_type().is_file() {
+                let path = entry.path()?.to_path_buf();
+                let mut bytes = Vec::new();
+                entry.read_to_end(&mut bytes)?;
+                files.insert(path, bytes);
+            }
+        }
+        Ok(Self { files })
+    }
+}
+
+#[cfg(feature = "archive-io")]
+impl ResourceResolver for TarResolver {
+    fn resolve(&self, path: &Path) -> Option<std::io::Result<Vec<u8>>> {
+        self.files.resolve(path)
+    }
+}
+
+fn normalize(path: &Path) -> String {
+    let mut components: Vec<String> = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(name) => components.push(name.to_string_lossy().to_string()),
+            Component::ParentDir => {
+                components.pop();
+            }
+            _ => {}
+        }
+    }
+    components.join("/")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::io::*;
+    use std::sync::Arc;
+
+    fn files() -> Vec<(&'static str, Vec<u8>)> {
+        vec![
+            ("a.txt", b"a".to_vec()),
+            ("textures/b.png", vec![1, 2, 3]),
+            ("textures/nested/c.bin", vec![0; 1000]),
+        ]
+    }
+
+    fn assert_files(resolver: &dyn ResourceResolver) {
+        for (path, bytes) in files() {
+            assert_eq!(resolver.resolve(Path::new(path)).unwrap().unwrap(), bytes);
+        }
+        assert_eq!(
+            resolver
+                .resolve(Path::new("/textures/nested/../b.png"))
+                .unwrap()
+                .unwrap(),
+            vec![1, 2, 3]
+        );
+        assert_eq!(
+            resolver
+                .resolve(Path::new("./textures/nested/c.bin"))
+                .unwrap()
+                .unwrap()
+                .len(),
+            1000
+        );
+        assert!(resolver.resolve(Path::new("b.png")).is_none());
+        assert!(resolver.resolve(Path::new("textures")).is_none());
+    }
+
+    #[cfg(feature = "archive-io")]
+    fn zip() -> Vec<u8> {
+        use std::io::Write;
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        writer
+            .add_directory("textures/", zip::write::FileOptions::default())
+            .unwrap();
+        for (path, bytes) in files() {
+            writer
+                .start_file(path, zip::write::FileOptions::default())
+                .unwrap();
+            writer.write_all(&bytes).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[cfg(feature = "archive-io")]
+    fn tar() -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (path, bytes) in files() {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(bytes.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, path, &bytes[..]).unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    #[cfg(feature = "archive-io")]
+    fn gzip(bytes: &[u8]) -> Vec<u8> {
+        use std::io::Write;
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(bytes).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    pub fn memory_resolver() {
+        let mut resolver = MemoryResolver::new();
+        for (path, bytes) in files() {
+            resolver.insert(path, bytes);
+        }
+        assert_files(&resolver);
+        let mut paths = resolver.paths().collect::<Vec<_>>();
+        paths.sort_unstable();
+        assert_eq!(
+            paths,
+            vec!["a.txt", "textures/b.png", "textures/nested/c.bin"]
+        );
+
+        resolver.insert("/textures/../a.txt", b"replaced".to_vec());
+        assert_eq!(
+            resolver.resolve(Path::new("a.txt")).unwrap().unwrap(),
+            b"replaced".to_vec()
+        );
+        assert_eq!(resolver.paths().count(), 3);
+    }
+
+    #[test]
+    pub fn normalize_paths() {
+        assert_eq!(normalize(Path::new("a/b/c.png")), "a/b/c.png");
+        assert_eq!(normalize(Path::new("/a/./b/../c.png")), "a/c.png");
+        assert_eq!(normalize(Path::new("../../c.png")), "c.png");
+        assert_eq!(normalize(Path::new("")), "");
+    }
+
+    #[cfg(feature = "archive-io")]
+    #[test]
+    pub fn zip_round_trip() {
+        assert_files(&ZipResolver::new(zip()).unwrap());
+    }
+
+    #[cfg(feature = "archive-io")]
+    #[test]
+    pub fn tar_round_trip() {
+        assert_files(&TarResolver::new(&tar()).unwrap());
+        assert_files(&TarResolver::new(&gzip(&tar())).unwrap());
+    }
+
+    #[cfg(feature = "archive-io")]
+    #[test]
+    pub fn malformed_archives() {
+        assert!(ZipResolver::new(Vec::new()).is_err());
+        assert!(ZipResolver::new(b"not a zip archive".to_vec()).is_err());
+        let zip = zip();
+        assert!(ZipResolver::new(zip[..zip.len() / 2].to_vec()).is_err());
+
+        let tar = tar();
+        let mut corrupt_header = tar.clone();
+        corrupt_header[0] ^= 0xFF;
+        assert!(TarResolver::new(&corrupt_header).is_err());
+        assert!(TarResolver::new(&tar[..600]).is_err());
+        let gz = gzip(&tar);
+        assert!(TarResolver::new(&gz[..gz.len() / 2]).is_err());
+        assert!(TarResolver::new(&[0x1f, 0x8b, 0, 0]).is_err());
+    }
+
+    #[test]
+    pub fn loaded_uses_resolvers() {
+        let mut resolver = MemoryResolver::new();
+        resolver.insert("textures/b.png", vec![1, 2, 3]);
+        let mut loaded = Loaded::new();
+        loaded.insert_bytes("https://example.com/assets/b.png", vec![4, 5, 6]);
+        loaded.insert_bytes("b.png", vec![7, 8, 9]);
+        loaded.add_resolver(Arc::new(resolver));
+
+        // An exact match is used first, then the resolvers and then a loaded resource which contains the path
+        assert_eq!(loaded.get_bytes("b.png").unwrap(), &[7, 8, 9]);
+        assert_eq!(
+            loaded.get_bytes("models/../textures/b.png").unwrap(),
+            &[1, 2, 3]
+        );
+        assert_eq!(loaded.get_bytes("assets/b.png").unwrap(), &[4, 5, 6]);
+        assert!(loaded.get_bytes("c.png").is_err());
+    }
+
+    #[cfg(all(
+        feature = "archive-io",
+        feature = "gltf-io",
+        not(target_arch = "wasm32")
+    ))]
+    #[test]
+    pub fn gltf_with_relative_texture_path() {
+        use crate::core::*;
+        use std::io::Write;
+        let mesh = CPUMesh {
+            material_name: Some("textured".to_string()),
+            positions: vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0],
+            uvs: Some(vec![0.0, 0.0, 1.0, 0.0, 0.0, 1.0]),
+            ..Default::default()
+        };
+        let data = vec![255, 0, 0, 255, 0, 255, 0, 255];
+        let material = CPUMaterial {
+            name: "textured".to_string(),
+            albedo_texture: Some(CPUTexture {
+                data: data.clone(),
+                width: 2,
+                height: 1,
+                format: Format::RGBA,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let (json, bin) = Saver::serialize_gltf(&[mesh], &[material], "scene.bin").unwrap();
+
+        // Refer to the texture by a path relative to the .gltf file instead of embedding it
+        let mut root: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        root["images"][0] = serde_json::json!({ "uri": "../textures/albedo.png" });
+        let mut png = Vec::new();
+        image::codecs::png::PngEncoder::new(&mut png)
+            .encode(&data, 2, 1, image::ColorType::Rgba8)
+            .unwrap();
+
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        for (path, bytes) in [
+            ("models/scene.gltf", serde_json::to_vec(&root).unwrap()),
+            ("models/scene.bin", bin),
+            ("textures/albedo.png", png),
+        ]
+        .iter()
+        {
+            writer
+                .start_file(*path, zip::write::FileOptions::default())
+                .unwrap();
+            writer.write_all(bytes).unwrap();
+        }
+        let zip = writer.finish().unwrap().into_inner();
+
+        let mut loaded = Loaded::new();
+        loaded.add_resolver(Arc::new(ZipResolver::new(zip).unwrap()));
+        let (meshes, materials) = loaded.gltf("models/scene.gltf").unwrap();
+        assert_eq!(meshes.len(), 1);
+        let texture = materials[0].albedo_texture.as_ref().unwrap();
+        assert_eq!((texture.width, texture.height), (2, 1));
+        assert_eq!(texture.data, data);
+    }
+}
@@ -4,8 +4,9 @@
 //!
 
 pub use crate::core::{
-    math::*, render_states::*, render_target::*, texture::*, Camera, Context, GeometryFunction,
-    LightingModel, NormalDistributionFunction, Viewport,
+    math::*, render_states::*, render_target::*, texture::*, CPUCamera, CPULight, CPULightType,
+    CPUProjection, Camera, Context, GeometryFunction, LightingModel, NormalDistributionFunction,
+    Viewport,
 };
 
 pub mod material;
@@ -37,6 +37,28 @@ pub struct Attenuation {
     pub quadratic: f32,
 }
 
+impl Attenuation {
+    ///
+    /// Returns an attenuation which approximately fades the light intensity to zero at the given distance
+    /// or an inverse square attenuation if the range is infinite, ie. `None`.
+    ///
+    pub fn from_range(range: Option<f32>) -> Self {
+        if let Some(range) = range {
+            Self {
+                constant: 1.0,
+                linear: 4.5 / range,
+                quadratic: 75.0 / (range * range),
+            }
+        } else {
+            Self {
+                constant: 1.0,
+                linear: 0.0,
+                quadratic: 1.0,
+            }
+        }
+    }
+}
+
 impl Default for Attenuation {
     fn default() -> Self {
         Self {
@@ -32,6 +32,22 @@ impl DirectionalLight {
         })
     }
 
+    ///
+    /// Constructs a new directional light from a [CPULight], for example loaded from a glTF file.
+    /// The direction is taken from the transformation of the [CPULight] while the type of the [CPULight] is not taken into account.
+    ///
+    pub fn from_cpu_light(
+        context: &Context,
+        cpu_light: &CPULight,
+    ) -> ThreeDResult<DirectionalLight> {
+        Self::new(
+            context,
+            cpu_light.intensity,
+            cpu_light.color,
+            &cpu_light.direction(),
+        )
+    }
+
     pub fn set_color(&mut self, color: Color) {
         self.color = color;
     }
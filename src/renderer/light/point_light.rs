@@ -27,6 +27,21 @@ impl PointLight {
         })
     }
 
+    ///
+    /// Constructs a new point light from a [CPULight], for example loaded from a glTF file.
+    /// The position is taken from the transformation of the [CPULight] and the attenuation from the range, see [Attenuation::from_range],
+    /// while the type of the [CPULight] is not taken into account.
+    ///
+    pub fn from_cpu_light(context: &Context, cpu_light: &CPULight) -> ThreeDResult<PointLight> {
+        Self::new(
+            context,
+            cpu_light.intensity,
+            cpu_light.color,
+            &cpu_light.position(),
+            Attenuation::from_range(cpu_light.range),
+        )
+    }
+
     pub fn set_color(&mut self, color: Color) {
         self.color = color;
     }
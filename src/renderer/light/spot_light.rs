@@ -41,6 +41,29 @@ impl SpotLight {
         })
     }
 
+    ///
+    /// Constructs a new spot light from a [CPULight], for example loaded from a glTF file.
+    /// The position and direction are taken from the transformation of the [CPULight], the attenuation from the range, see [Attenuation::from_range],
+    /// and the cutoff from the outer cone angle if the [CPULight] is a [CPULightType::Spot] and otherwise it defaults to 45 degrees.
+    ///
+    pub fn from_cpu_light(context: &Context, cpu_light: &CPULight) -> ThreeDResult<SpotLight> {
+        let cutoff = match cpu_light.light_type {
+            CPULightType::Spot {
+                outer_cone_angle, ..
+            } => outer_cone_angle,
+            _ => degrees(45.0).into(),
+        };
+        Self::new(
+            context,
+            cpu_light.intensity,
+            cpu_light.color,
+            &cpu_light.position(),
+            &cpu_light.direction(),
+            cutoff,
+            Attenuation::from_range(cpu_light.range),
+        )
+    }
+
     pub fn set_color(&mut self, color: Color) {
         self.color = color;
     }
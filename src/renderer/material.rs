@@ -6,7 +6,7 @@
 use crate::core::*;
 use crate::renderer::*;
 
-pub use crate::core::{CPUMaterial, Color};
+pub use crate::core::{AlphaMode, CPUMaterial, Color};
 
 mod color_material;
 #[doc(inline)]
@@ -33,8 +33,20 @@ pub struct DeferredPhysicalMaterial {
     pub normal_texture: Option<Rc<Texture2D<u8>>>,
     /// Render states
     pub render_states: RenderStates,
-    /// Alpha cutout value for transparency in deferred rendering pipeline.
+    /// Color of light shining from an object.
+    pub emissive: Color,
+    /// Texture with color of light shining from an object.
+    pub emissive_texture: Option<Rc<Texture2D<u8>>>,
+    /// A scalar multiplier applied to the [Self::emissive] color and [Self::emissive_texture].
+    pub emissive_strength: f32,
+    /// Specifies how the alpha value of the albedo color and albedo texture is interpreted.
+    /// Since the deferred pipeline does not support transparency, the material is rendered opaque unless fragments are discarded using the [Self::alpha_cutout].
+    pub alpha_mode: AlphaMode,
+    /// Fragments with an alpha value below this value are discarded if the alpha mode is [AlphaMode::Auto] or [AlphaMode::Mask].
+    /// If the alpha mode is [AlphaMode::Mask] and no value is specified, 0.5 is used.
     pub alpha_cutout: Option<f32>,
+    /// Whether or not the material is unlit, ie. the albedo color is rendered as it is without being affected by lights.
+    pub unlit: bool,
 }
 
 impl DeferredPhysicalMaterial {
@@ -42,6 +54,7 @@ impl DeferredPhysicalMaterial {
     /// Constructs a new deferred physical material from a [CPUMaterial].
     /// If the input contains an [CPUMaterial::occlusion_metallic_roughness_texture], this texture is used for both
     /// [DeferredPhysicalMaterial::metallic_roughness_texture] and [DeferredPhysicalMaterial::occlusion_texture] while any [CPUMaterial::metallic_roughness_texture] or [CPUMaterial::occlusion_texture] are ignored.
+    /// The [CPUMaterial::texture_transform] is not part of the material and must be applied to the model, see [Model::set_texture_transform].
     ///
     pub fn new(context: &Context, cpu_material: &CPUMaterial) -> ThreeDResult<Self> {
         let albedo_texture = if let Some(ref cpu_texture) = cpu_material.albedo_texture {
@@ -73,6 +86,11 @@ impl DeferredPhysicalMaterial {
         } else {
             None
         };
+        let emissive_texture = if let Some(ref cpu_texture) = cpu_material.emissive_texture {
+            Some(Rc::new(Texture2D::new(context, cpu_texture)?))
+        } else {
+            None
+        };
         Ok(Self {
             name: cpu_material.name.clone(),
             albedo: cpu_material.albedo,
@@ -84,8 +102,20 @@ impl DeferredPhysicalMaterial {
             normal_scale: cpu_material.normal_scale,
             occlusion_texture,
             occlusion_strength: cpu_material.occlusion_strength,
-            render_states: RenderStates::default(),
+            render_states: RenderStates {
+                cull: if cpu_material.double_sided {
+                    Cull::None
+                } else {
+                    Cull::Back
+                },
+                ..Default::default()
+            },
+            emissive: cpu_material.emissive,
+            emissive_texture,
+            emissive_strength: cpu_material.emissive_strength,
+            alpha_mode: cpu_material.alpha_mode,
             alpha_cutout: cpu_material.alpha_cutout,
+            unlit: cpu_material.unlit,
         })
     }
 
@@ -105,7 +135,12 @@ impl DeferredPhysicalMaterial {
             occlusion_texture: physical_material.occlusion_texture.clone(),
             occlusion_strength: physical_material.occlusion_strength,
             render_states: physical_material.opaque_render_states,
-            alpha_cutout: None,
+            emissive: physical_material.emissive,
+            emissive_texture: physical_material.emissive_texture.clone(),
+            emissive_strength: physical_material.emissive_strength,
+            alpha_mode: physical_material.alpha_mode,
+            alpha_cutout: physical_material.alpha_cutout,
+            unlit: physical_material.unlit,
         }
     }
 }
@@ -113,35 +148,43 @@ impl DeferredPhysicalMaterial {
 impl Material for DeferredPhysicalMaterial {
     fn fragment_shader_source(&self, use_vertex_colors: bool, _lights: &[&dyn Light]) -> String {
         let mut output = include_str!("../../core/shared.frag").to_string();
-        if self.albedo_texture.is_some()
-            || self.metallic_roughness_texture.is_some()
-            || self.normal_texture.is_some()
-            || self.occlusion_texture.is_some()
-            || self.alpha_cutout.is_some()
+        if self.unlit {
+            output.push_str("#define UNLIT\n");
+        } else {
+            output.push_str("in vec3 pos;\nin vec3 nor;\n");
+        }
+        let use_lighting_textures = !self.unlit
+            && (self.metallic_roughness_texture.is_some()
+                || self.normal_texture.is_some()
+                || self.occlusion_texture.is_some());
+        if self.albedo_texture.is_some() || self.emissive_texture.is_some() || use_lighting_textures
         {
             output.push_str("in vec2 uvs;\n");
             if self.albedo_texture.is_some() {
                 output.push_str("#define USE_ALBEDO_TEXTURE;\n");
             }
-            if self.metallic_roughness_texture.is_some() {
-                output.push_str("#define USE_METALLIC_ROUGHNESS_TEXTURE;\n");
-            }
-            if self.occlusion_texture.is_some() {
-                output.push_str("#define USE_OCCLUSION_TEXTURE;\n");
-            }
-            if self.normal_texture.is_some() {
-                output.push_str("#define USE_NORMAL_TEXTURE;\nin vec3 tang;\nin vec3 bitang;\n");
+            if use_lighting_textures {
+                if self.metallic_roughness_texture.is_some() {
+                    output.push_str("#define USE_METALLIC_ROUGHNESS_TEXTURE;\n");
+                }
+                if self.occlusion_texture.is_some() {
+                    output.push_str("#define USE_OCCLUSION_TEXTURE;\n");
+                }
+                if self.normal_texture.is_some() {
+                    output
+                        .push_str("#define USE_NORMAL_TEXTURE;\nin vec3 tang;\nin vec3 bitang;\n");
+                }
             }
-            if self.alpha_cutout.is_some() {
-                output.push_str(
-                    format!(
-                        "#define ALPHACUT;\nfloat acut = {};",
-                        self.alpha_cutout.unwrap()
-                    )
-                    .as_str(),
-                );
+            if self.emissive_texture.is_some() {
+                output.push_str("#define USE_EMISSIVE_TEXTURE;\n");
             }
         }
+        if let Some(alpha_cutout) = self.alpha_mode.cutout(self.alpha_cutout) {
+            output.push_str(&format!(
+                "#define ALPHACUT;\nfloat acut = {:?};\n",
+                alpha_cutout
+            ));
+        }
         if use_vertex_colors {
             output.push_str("#define USE_VERTEX_COLORS\nin vec4 col;\n");
         }
@@ -155,22 +198,31 @@ impl Material for DeferredPhysicalMaterial {
         _camera: &Camera,
         _lights: &[&dyn Light],
     ) -> ThreeDResult<()> {
-        program.use_uniform_float("metallic", &self.metallic)?;
-        program.use_uniform_float("roughness", &self.roughness)?;
         program.use_uniform_vec4("albedo", &self.albedo.to_vec4())?;
+        program.use_uniform_vec3(
+            "emissive",
+            &(self.emissive.to_vec3() * self.emissive_strength),
+        )?;
         if let Some(ref texture) = self.albedo_texture {
             program.use_texture("albedoTexture", texture.as_ref())?;
         }
-        if let Some(ref texture) = self.metallic_roughness_texture {
-            program.use_texture("metallicRoughnessTexture", texture.as_ref())?;
+        if let Some(ref texture) = self.emissive_texture {
+            program.use_texture("emissiveTexture", texture.as_ref())?;
         }
-        if let Some(ref texture) = self.occlusion_texture {
-            program.use_uniform_float("occlusionStrength", &self.occlusion_strength)?;
-            program.use_texture("occlusionTexture", texture.as_ref())?;
-        }
-        if let Some(ref texture) = self.normal_texture {
-            program.use_uniform_float("normalScale", &self.normal_scale)?;
-            program.use_texture("normalTexture", texture.as_ref())?;
+        if !self.unlit {
+            program.use_uniform_float("metallic", &self.metallic)?;
+            program.use_uniform_float("roughness", &self.roughness)?;
+            if let Some(ref texture) = self.metallic_roughness_texture {
+                program.use_texture("metallicRoughnessTexture", texture.as_ref())?;
+            }
+            if let Some(ref texture) = self.occlusion_texture {
+                program.use_uniform_float("occlusionStrength", &self.occlusion_strength)?;
+                program.use_texture("occlusionTexture", texture.as_ref())?;
+            }
+            if let Some(ref texture) = self.normal_texture {
+                program.use_uniform_float("normalScale", &self.normal_scale)?;
+                program.use_texture("normalTexture", texture.as_ref())?;
+            }
         }
         Ok(())
     }
@@ -198,7 +250,12 @@ impl Default for DeferredPhysicalMaterial {
             occlusion_texture: None,
             occlusion_strength: 1.0,
             render_states: RenderStates::default(),
+            emissive: Color::BLACK,
+            emissive_texture: None,
+            emissive_strength: 1.0,
+            alpha_mode: AlphaMode::Auto,
             alpha_cutout: None,
+            unlit: false,
         }
     }
 }
@@ -38,6 +38,15 @@ pub struct PhysicalMaterial {
     pub emissive: Color,
     /// Texture with color of light shining from an object.
     pub emissive_texture: Option<Rc<Texture2D<u8>>>,
+    /// A scalar multiplier applied to the [Self::emissive] color and [Self::emissive_texture].
+    pub emissive_strength: f32,
+    /// Specifies how the alpha value of the albedo color and albedo texture is interpreted.
+    pub alpha_mode: AlphaMode,
+    /// Fragments with an alpha value below this value are discarded if the alpha mode is [AlphaMode::Auto] or [AlphaMode::Mask].
+    /// If the alpha mode is [AlphaMode::Mask] and no value is specified, 0.5 is used.
+    pub alpha_cutout: Option<f32>,
+    /// Whether or not the material is unlit, ie. the albedo color is rendered as it is without being affected by lights.
+    pub unlit: bool,
     /// The lighting model used when rendering this material
     pub lighting_model: LightingModel,
 }
@@ -47,6 +56,7 @@ impl PhysicalMaterial {
     /// Constructs a new physical material from a [CPUMaterial].
     /// If the input contains an [CPUMaterial::occlusion_metallic_roughness_texture], this texture is used for both
     /// [PhysicalMaterial::metallic_roughness_texture] and [PhysicalMaterial::occlusion_texture] while any [CPUMaterial::metallic_roughness_texture] or [CPUMaterial::occlusion_texture] are ignored.
+    /// The [CPUMaterial::texture_transform] is not part of the material and must be applied to the model, see [Model::set_texture_transform].
     ///
     pub fn new(context: &Context, cpu_material: &CPUMaterial) -> ThreeDResult<Self> {
         let albedo_texture = if let Some(ref cpu_texture) = cpu_material.albedo_texture {
@@ -83,6 +93,11 @@ impl PhysicalMaterial {
         } else {
             None
         };
+        let cull = if cpu_material.double_sided {
+            Cull::None
+        } else {
+            Cull::Back
+        };
         Ok(Self {
             name: cpu_material.name.clone(),
             albedo: cpu_material.albedo,
@@ -94,14 +109,22 @@ impl PhysicalMaterial {
             normal_scale: cpu_material.normal_scale,
             occlusion_texture,
             occlusion_strength: cpu_material.occlusion_strength,
-            opaque_render_states: RenderStates::default(),
+            opaque_render_states: RenderStates {
+                cull,
+                ..Default::default()
+            },
             transparent_render_states: RenderStates {
                 write_mask: WriteMask::COLOR,
                 blend: Blend::TRANSPARENCY,
+                cull,
                 ..Default::default()
             },
             emissive: cpu_material.emissive,
             emissive_texture,
+            emissive_strength: cpu_material.emissive_strength,
+            alpha_mode: cpu_material.alpha_mode,
+            alpha_cutout: cpu_material.alpha_cutout,
+            unlit: cpu_material.unlit,
             lighting_model: cpu_material.lighting_model,
         })
     }
@@ -109,30 +132,48 @@ impl PhysicalMaterial {
 
 impl Material for PhysicalMaterial {
     fn fragment_shader_source(&self, use_vertex_colors: bool, lights: &[&dyn Light]) -> String {
-        let mut output = lights_fragment_shader_source(lights, self.lighting_model);
-        if self.albedo_texture.is_some()
-            || self.metallic_roughness_texture.is_some()
-            || self.normal_texture.is_some()
-            || self.occlusion_texture.is_some()
-            || self.emissive_texture.is_some()
+        let mut output = if self.unlit {
+            format!("#define UNLIT\n{}", include_str!("../../core/shared.frag"))
+        } else {
+            let mut output = lights_fragment_shader_source(lights, self.lighting_model);
+            output.push_str("in vec3 pos;\nin vec3 nor;\n");
+            output
+        };
+        let use_lighting_textures = !self.unlit
+            && (self.metallic_roughness_texture.is_some()
+                || self.normal_texture.is_some()
+                || self.occlusion_texture.is_some());
+        if self.albedo_texture.is_some() || self.emissive_texture.is_some() || use_lighting_textures
         {
             output.push_str("in vec2 uvs;\n");
             if self.albedo_texture.is_some() {
                 output.push_str("#define USE_ALBEDO_TEXTURE;\n");
             }
-            if self.metallic_roughness_texture.is_some() {
-                output.push_str("#define USE_METALLIC_ROUGHNESS_TEXTURE;\n");
-            }
-            if self.occlusion_texture.is_some() {
-                output.push_str("#define USE_OCCLUSION_TEXTURE;\n");
-            }
-            if self.normal_texture.is_some() {
-                output.push_str("#define USE_NORMAL_TEXTURE;\nin vec3 tang;\nin vec3 bitang;\n");
+            if use_lighting_textures {
+                if self.metallic_roughness_texture.is_some() {
+                    output.push_str("#define USE_METALLIC_ROUGHNESS_TEXTURE;\n");
+                }
+                if self.occlusion_texture.is_some() {
+                    output.push_str("#define USE_OCCLUSION_TEXTURE;\n");
+                }
+                if self.normal_texture.is_some() {
+                    output
+                        .push_str("#define USE_NORMAL_TEXTURE;\nin vec3 tang;\nin vec3 bitang;\n");
+                }
             }
             if self.emissive_texture.is_some() {
                 output.push_str("#define USE_EMISSIVE_TEXTURE;\n");
             }
         }
+        if let Some(alpha_cutout) = self.alpha_mode.cutout(self.alpha_cutout) {
+            output.push_str(&format!(
+                "#define ALPHACUT;\nfloat acut = {:?};\n",
+                alpha_cutout
+            ));
+        }
+        if self.alpha_mode == AlphaMode::Opaque || self.alpha_mode == AlphaMode::Mask {
+            output.push_str("#define ALPHA_OPAQUE\n");
+        }
         if use_vertex_colors {
             output.push_str("#define USE_VERTEX_COLORS\nin vec4 col;\n");
         }
@@ -145,29 +186,34 @@ impl Material for PhysicalMaterial {
         camera: &Camera,
         lights: &[&dyn Light],
     ) -> ThreeDResult<()> {
-        program.use_uniform_vec3("eyePosition", camera.position())?;
-        for (i, light) in lights.iter().enumerate() {
-            light.use_uniforms(program, i as u32)?;
-        }
-        program.use_uniform_float("metallic", &self.metallic)?;
-        program.use_uniform_float("roughness", &self.roughness)?;
         program.use_uniform("albedo", self.albedo.to_vec4())?;
         if program.requires_uniform("emissive") {
-            program.use_uniform_vec3("emissive", &self.emissive.to_vec3())?;
+            program.use_uniform_vec3(
+                "emissive",
+                &(self.emissive.to_vec3() * self.emissive_strength),
+            )?;
         }
         if let Some(ref texture) = self.albedo_texture {
             program.use_texture("albedoTexture", texture.as_ref())?;
         }
-        if let Some(ref texture) = self.metallic_roughness_texture {
-            program.use_texture("metallicRoughnessTexture", texture.as_ref())?;
-        }
-        if let Some(ref texture) = self.occlusion_texture {
-            program.use_uniform_float("occlusionStrength", &self.occlusion_strength)?;
-            program.use_texture("occlusionTexture", texture.as_ref())?;
-        }
-        if let Some(ref texture) = self.normal_texture {
-            program.use_uniform_float("normalScale", &self.normal_scale)?;
-            program.use_texture("normalTexture", texture.as_ref())?;
+        if !self.unlit {
+            program.use_uniform_vec3("eyePosition", camera.position())?;
+            for (i, light) in lights.iter().enumerate() {
+                light.use_uniforms(program, i as u32)?;
+            }
+            program.use_uniform_float("metallic", &self.metallic)?;
+            program.use_uniform_float("roughness", &self.roughness)?;
+            if let Some(ref texture) = self.metallic_roughness_texture {
+                program.use_texture("metallicRoughnessTexture", texture.as_ref())?;
+            }
+            if let Some(ref texture) = self.occlusion_texture {
+                program.use_uniform_float("occlusionStrength", &self.occlusion_strength)?;
+                program.use_texture("occlusionTexture", texture.as_ref())?;
+            }
+            if let Some(ref texture) = self.normal_texture {
+                program.use_uniform_float("normalScale", &self.normal_scale)?;
+                program.use_texture("normalTexture", texture.as_ref())?;
+            }
         }
         if program.requires_uniform("emissiveTexture") {
             if let Some(ref texture) = self.emissive_texture {
@@ -185,12 +231,19 @@ impl Material for PhysicalMaterial {
         }
     }
     fn is_transparent(&self) -> bool {
-        self.albedo.a != 255
-            || self
-                .albedo_texture
-                .as_ref()
-                .map(|t| t.is_transparent())
-                .unwrap_or(false)
+        match self.alpha_mode {
+            AlphaMode::Auto => {
+                self.alpha_cutout.is_none()
+                    && (self.albedo.a != 255
+                        || self
+                            .albedo_texture
+                            .as_ref()
+                            .map(|t| t.is_transparent())
+                            .unwrap_or(false))
+            }
+            AlphaMode::Blend => true,
+            AlphaMode::Opaque | AlphaMode::Mask => false,
+        }
     }
 }
 
@@ -215,6 +268,10 @@ impl Default for PhysicalMaterial {
             },
             emissive: Color::BLACK,
             emissive_texture: None,
+            emissive_strength: 1.0,
+            alpha_mode: AlphaMode::Auto,
+            alpha_cutout: None,
+            unlit: false,
             lighting_model: LightingModel::Blinn,
         }
     }
@@ -32,8 +32,15 @@ void main()
     float roughness_factor = n.w;
     float occlusion = n.z;
 
-    outColor.rgb = calculate_lighting(surface_color.rgb, position, normal, metallic_factor, roughness_factor, occlusion);
-    outColor.rgb = reinhard_tone_mapping(outColor.rgb);
+    vec4 e = texture(gbuffer, vec3(uv, 2));
+    vec3 emissive = e.rgb / max(1.0 - e.rgb, vec3(0.001));
+    if (e.a > 0.5) {
+        // Unlit
+        outColor.rgb = min(emissive + surface_color.rgb, vec3(1.0));
+    } else {
+        outColor.rgb = emissive + calculate_lighting(surface_color.rgb, position, normal, metallic_factor, roughness_factor, occlusion);
+        outColor.rgb = reinhard_tone_mapping(outColor.rgb);
+    }
     outColor.rgb = srgb_from_rgb(outColor.rgb);
     outColor.a = surface_color.a;
 }
\ No newline at end of file
@@ -26,26 +26,37 @@ uniform sampler2D normalTexture;
 uniform float normalScale;
 #endif
 
-in vec3 pos;
-in vec3 nor;
-
 layout (location = 0) out vec4 outColor;
 layout (location = 1) out vec4 outNormal;
+layout (location = 2) out vec4 outEmissive;
 
 void main()
 {
     vec4 surface_color = albedo;
 #ifdef USE_ALBEDO_TEXTURE
     vec4 c = texture(albedoTexture, uvs);
-    #ifdef ALPHACUT
-        if (c.a < acut) discard;
-    #endif
     surface_color *= vec4(rgb_from_srgb(c.rgb), c.a);
 #endif
 #ifdef USE_VERTEX_COLORS
     surface_color *= col;
 #endif
+#ifdef ALPHACUT
+    if (surface_color.a < acut) discard;
+#endif
+
+    vec3 total_emissive = emissive;
+#ifdef USE_EMISSIVE_TEXTURE
+    vec4 e = texture(emissiveTexture, uvs);
+    total_emissive *= rgb_from_srgb(e.rgb);
+#endif
+    // The emissive color can be larger than one, so it is mapped to the range [0..1) and mapped back in the lighting pass
+    outEmissive.rgb = total_emissive / (1.0 + total_emissive);
 
+#ifdef UNLIT
+    outColor = vec4(surface_color.rgb, 0.0);
+    outNormal = vec4(0.5, 0.5, 1.0, 1.0);
+    outEmissive.a = 1.0;
+#else
     float metallic_factor = metallic;
     float roughness_factor = roughness;
 #ifdef USE_METALLIC_ROUGHNESS_TEXTURE
@@ -69,4 +80,6 @@ void main()
 
     outColor = vec4(surface_color.rgb, metallic_factor);
     outNormal = vec4(0.5 * normal.xy + 0.5, occlusion, roughness_factor);
+    outEmissive.a = 0.0;
+#endif
 }
\ No newline at end of file
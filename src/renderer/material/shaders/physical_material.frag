@@ -26,9 +26,6 @@ uniform sampler2D normalTexture;
 uniform float normalScale;
 #endif
 
-in vec3 pos;
-in vec3 nor;
-
 layout (location = 0) out vec4 outColor;
 
 void main()
@@ -36,15 +33,19 @@ void main()
     vec4 surface_color = albedo;
 #ifdef USE_ALBEDO_TEXTURE
     vec4 c = texture(albedoTexture, uvs);
-    #ifdef ALPHACUT
-        if (c.a < acut) discard;
-    #endif
     surface_color *= vec4(rgb_from_srgb(c.rgb), c.a);
 #endif
 #ifdef USE_VERTEX_COLORS
     surface_color *= col;
 #endif
+#ifdef ALPHACUT
+    if (surface_color.a < acut) discard;
+#endif
+#ifdef ALPHA_OPAQUE
+    surface_color.a = 1.0;
+#endif
 
+#ifndef UNLIT
     float metallic_factor = metallic;
     float roughness_factor = roughness;
 #ifdef USE_METALLIC_ROUGHNESS_TEXTURE
@@ -64,6 +65,7 @@ void main()
     vec3 bitangent = normalize(gl_FrontFacing ? bitang : -bitang);
     mat3 tbn = mat3(tangent, bitangent, normal);
     normal = tbn * ((2.0 * texture(normalTexture, uvs).xyz - 1.0) * vec3(normalScale, normalScale, 1.0));
+#endif
 #endif
 
     vec3 total_emissive = emissive;
@@ -72,8 +74,12 @@ void main()
     total_emissive *= rgb_from_srgb(e.rgb);
 #endif
 
+#ifdef UNLIT
+    outColor.rgb = min(total_emissive + surface_color.rgb, vec3(1.0));
+#else
     outColor.rgb = total_emissive + calculate_lighting(surface_color.rgb, pos, normal, metallic_factor, roughness_factor, occlusion);
     outColor.rgb = reinhard_tone_mapping(outColor.rgb);
+#endif
     outColor.rgb = srgb_from_rgb(outColor.rgb);
     outColor.a = surface_color.a;
 }
\ No newline at end of file
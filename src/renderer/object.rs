@@ -2,7 +2,10 @@
 //! A collection of objects that can be rendered, for example a mesh.
 //!
 
-pub use crate::core::{AxisAlignedBoundingBox, CPUMesh, Indices};
+pub use crate::core::{
+    AxisAlignedBoundingBox, CPUAnimation, CPUAnimationTrack, CPUMesh, CPUNode, CPUScene, CPUSkin,
+    Indices, KeyFrameInterpolation, KeyFrames, MorphTarget,
+};
 
 mod model;
 #[doc(inline)]
@@ -12,6 +15,10 @@ mod instanced_model;
 #[doc(inline)]
 pub use instanced_model::*;
 
+mod skinned_model;
+#[doc(inline)]
+pub use skinned_model::*;
+
 mod line;
 #[doc(inline)]
 pub use line::*;
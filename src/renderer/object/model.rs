@@ -13,6 +13,7 @@ pub struct Model<M: Material> {
     aabb_local: AxisAlignedBoundingBox,
     transformation: Mat4,
     texture_transform: Mat3,
+    morph_weights: Vec<f32>,
     /// The material applied to the model
     pub material: M,
 }
@@ -38,6 +39,7 @@ impl<M: Material> Model<M> {
         let mesh = Rc::new(Mesh::new(context, cpu_mesh)?);
         let aabb = cpu_mesh.compute_aabb();
         Ok(Self {
+            morph_weights: vec![0.0; mesh.morph_target_count],
             mesh,
             aabb,
             aabb_local: aabb.clone(),
@@ -48,14 +50,44 @@ impl<M: Material> Model<M> {
         })
     }
 
+    ///
+    /// Returns the transformation applied to the uv coordinates before sampling the textures of the material.
+    ///
     pub fn texture_transform(&mut self) -> &Mat3 {
         &self.texture_transform
     }
 
+    ///
+    /// Sets the transformation applied to the uv coordinates before sampling the textures of the material,
+    /// for example [CPUMaterial::texture_transform].
+    ///
     pub fn set_texture_transform(&mut self, texture_transform: Mat3) {
         self.texture_transform = texture_transform;
     }
 
+    ///
+    /// Returns the weight of each morph target, see [CPUMesh::morph_targets].
+    ///
+    pub fn morph_weights(&self) -> &[f32] {
+        &self.morph_weights
+    }
+
+    ///
+    /// Sets the weight of each morph target, see [CPUMesh::morph_targets].
+    /// The displacements of each morph target are multiplied by the weight and added to the mesh when rendering.
+    /// The number of weights must be equal to the number of morph targets in the mesh.
+    ///
+    pub fn set_morph_weights(&mut self, morph_weights: &[f32]) -> ThreeDResult<()> {
+        if morph_weights.len() != self.morph_weights.len() {
+            Err(CoreError::InvalidMorphWeightCount(
+                morph_weights.len(),
+                self.morph_weights.len(),
+            ))?;
+        }
+        self.morph_weights = morph_weights.to_vec();
+        Ok(())
+    }
+
     pub(in crate::renderer) fn set_transformation_2d(&mut self, transformation: Mat3) {
         self.set_transformation(Mat4::new(
             transformation.x.x,
@@ -77,44 +109,60 @@ impl<M: Material> Model<M> {
         ));
     }
 
-    fn draw(
-        &self,
+    ///
+    /// Binds the vertex buffers, morph targets and the uniforms which are common to all models to the program and draws the mesh.
+    /// Also used by [SkinnedModel] after binding the joints.
+    ///
+    pub(super) fn draw_mesh(
         program: &Program,
         render_states: RenderStates,
-        camera_buffer: &UniformBuffer,
-        viewport: Viewport,
+        camera: &Camera,
+        mesh: &Mesh,
         transformation: &Mat4,
         texture_transform: &Mat3,
+        morph_weights: &[f32],
     ) -> ThreeDResult<()> {
-        program.use_uniform_block("Camera", camera_buffer);
+        program.use_uniform_block("Camera", camera.uniform_buffer());
         program.use_uniform_mat4("modelMatrix", transformation)?;
+        if let Some(ref texture) = mesh.morph_position_texture {
+            program.use_uniform_array("morphWeights", morph_weights)?;
+            program.use_texture("morphPositions", texture)?;
+        }
+        if program.requires_uniform("morphNormals") {
+            program.use_texture("morphNormals", mesh.morph_normal_texture.as_ref().unwrap())?;
+        }
+        if program.requires_uniform("morphTangents") {
+            program.use_texture(
+                "morphTangents",
+                mesh.morph_tangent_texture.as_ref().unwrap(),
+            )?;
+        }
 
         if program.requires_attribute("position") {
-            program.use_attribute_vec3("position", &self.mesh.position_buffer)?;
+            program.use_attribute_vec3("position", &mesh.position_buffer)?;
         }
         if program.requires_attribute("uv_coordinates") {
-            program.use_uniform_mat3("textureTransform", &texture_transform)?;
-            let uv_buffer = self
-                .mesh
+            program.use_uniform_mat3("textureTransform", texture_transform)?;
+            let uv_buffer = mesh
                 .uv_buffer
                 .as_ref()
                 .ok_or(CoreError::MissingMeshBuffer("uv coordinates".to_string()))?;
             program.use_attribute_vec2("uv_coordinates", uv_buffer)?;
         }
         if program.requires_attribute("normal") {
-            let normal_buffer = self
-                .mesh
+            let normal_buffer = mesh
                 .normal_buffer
                 .as_ref()
                 .ok_or(CoreError::MissingMeshBuffer("normal".to_string()))?;
             program.use_attribute_vec3("normal", normal_buffer)?;
-            program.use_uniform_mat4(
-                "normalMatrix",
-                &transformation.invert().unwrap().transpose(),
-            )?;
+            if program.requires_uniform("normalMatrix") {
+                program.use_uniform_mat4(
+                    "normalMatrix",
+                    &transformation.invert().unwrap().transpose(),
+                )?;
+            }
             if program.requires_attribute("tangent") {
-                let tangent_buffer = self
-                    .mesh
+                let tangent_buffer = mesh
                     .tangent_buffer
                     .as_ref()
                     .ok_or(CoreError::MissingMeshBuffer("tangent".to_string()))?;
@@ -122,25 +170,41 @@ impl<M: Material> Model<M> {
             }
         }
         if program.requires_attribute("color") {
-            let color_buffer = self
-                .mesh
+            let color_buffer = mesh
                 .color_buffer
                 .as_ref()
                 .ok_or(CoreError::MissingMeshBuffer("color".to_string()))?;
             program.use_attribute_vec4("color", color_buffer)?;
         }
-        if let Some(ref index_buffer) = self.mesh.index_buffer {
-            program.draw_elements(render_states, viewport, index_buffer);
+        if let Some(ref index_buffer) = mesh.index_buffer {
+            program.draw_elements(render_states, camera.viewport(), index_buffer);
         } else {
             program.draw_arrays(
                 render_states,
-                viewport,
-                self.mesh.position_buffer.count() as u32 / 3,
+                camera.viewport(),
+                mesh.position_buffer.count() as u32 / 3,
             );
         }
         Ok(())
     }
 
+    pub(super) fn morph_target_defines(mesh: &Mesh) -> String {
+        let mut defines = String::new();
+        if mesh.morph_target_count > 0 {
+            defines.push_str(&format!(
+                "#define USE_MORPH_TARGETS\n#define MORPH_TARGET_COUNT {}\n",
+                mesh.morph_target_count
+            ));
+            if mesh.morph_normal_texture.is_some() {
+                defines.push_str("#define USE_MORPH_NORMALS\n");
+            }
+            if mesh.morph_tangent_texture.is_some() {
+                defines.push_str("#define USE_MORPH_TANGENTS\n");
+            }
+        }
+        defines
+    }
+
     pub(super) fn vertex_shader_source(fragment_shader_source: &str) -> ThreeDResult<String> {
         let use_positions = fragment_shader_source.find("in vec3 pos;").is_some();
         let use_normals = fragment_shader_source.find("in vec3 nor;").is_some();
@@ -208,17 +272,22 @@ impl<M: Material> Shadable for Model<M> {
         let fragment_shader_source =
             material.fragment_shader_source(self.mesh.color_buffer.is_some(), lights);
         self.context.program(
-            &Self::vertex_shader_source(&fragment_shader_source)?,
+            &format!(
+                "{}{}",
+                Self::morph_target_defines(&self.mesh),
+                Self::vertex_shader_source(&fragment_shader_source)?
+            ),
             &fragment_shader_source,
             |program| {
                 material.use_uniforms(program, camera, lights)?;
-                self.draw(
+                Self::draw_mesh(
                     program,
                     material.render_states(),
-                    camera.uniform_buffer(),
-                    camera.viewport(),
+                    camera,
+                    &self.mesh,
                     &self.transformation,
                     &self.texture_transform,
+                    &self.morph_weights,
                 )
             },
         )
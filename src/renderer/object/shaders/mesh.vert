@@ -16,6 +16,34 @@ in vec4 row2;
 in vec4 row3;
 #endif
 
+#ifdef USE_SKINNING
+in vec4 joints;
+in vec4 weights;
+uniform mat4 jointTransformations[JOINT_COUNT];
+#endif
+
+#ifdef USE_MORPH_TARGETS
+uniform float morphWeights[MORPH_TARGET_COUNT];
+uniform sampler2D morphPositions;
+#ifdef USE_MORPH_NORMALS
+uniform sampler2D morphNormals;
+#endif
+#ifdef USE_MORPH_TANGENTS
+uniform sampler2D morphTangents;
+#endif
+
+vec3 morph(sampler2D displacements, vec3 value)
+{
+    ivec2 size = textureSize(displacements, 0);
+    int rows = size.y / MORPH_TARGET_COUNT;
+    ivec2 texel = ivec2(gl_VertexID % size.x, gl_VertexID / size.x);
+    for (int i = 0; i < MORPH_TARGET_COUNT; i++) {
+        value += morphWeights[i] * texelFetch(displacements, texel + ivec2(0, i * rows), 0).xyz;
+    }
+    return value;
+}
+#endif
+
 #ifdef USE_POSITIONS
 out vec3 pos;
 #endif
@@ -60,17 +88,29 @@ void main()
     transform[2] = vec4(row1.z, row2.z, row3.z, 0.0);
     transform[3] = vec4(row1.w, row2.w, row3.w, 1.0);
     local2World *= transform;
+#endif
+
+#ifdef USE_SKINNING
+    mat4 skinTransform = weights.x * jointTransformations[int(joints.x)]
+        + weights.y * jointTransformations[int(joints.y)]
+        + weights.z * jointTransformations[int(joints.z)]
+        + weights.w * jointTransformations[int(joints.w)];
+    local2World *= skinTransform;
+#endif
 
 #ifdef USE_NORMALS
+#if defined(INSTANCED) || defined(USE_SKINNING)
     normalMat = mat3(transpose(inverse(local2World)));
-#endif
 #else
-#ifdef USE_NORMALS
     normalMat = mat3(normalMatrix);
 #endif
 #endif
 
-    vec4 worldPosition = local2World * vec4(position, 1.);
+    vec3 localPosition = position;
+#ifdef USE_MORPH_TARGETS
+    localPosition = morph(morphPositions, localPosition);
+#endif
+    vec4 worldPosition = local2World * vec4(localPosition, 1.);
     gl_Position = camera.viewProjection * worldPosition;
 
 #ifdef USE_POSITIONS
@@ -78,10 +118,18 @@ void main()
 #endif
 
 #ifdef USE_NORMALS 
-    nor = normalize(normalMat * normal);
+    vec3 localNormal = normal;
+#ifdef USE_MORPH_NORMALS
+    localNormal = morph(morphNormals, localNormal);
+#endif
+    nor = normalize(normalMat * localNormal);
 
 #ifdef USE_TANGENTS 
-    tang = normalize(normalMat * tangent.xyz);
+    vec3 localTangent = tangent.xyz;
+#ifdef USE_MORPH_TANGENTS
+    localTangent = morph(morphTangents, localTangent);
+#endif
+    tang = normalize(normalMat * localTangent);
     bitang = normalize(cross(nor, tang) * tangent.w);
 #endif
 
@@ -0,0 +1,228 @@
+use crate::core::*;
+use crate::renderer::*;
+use std::rc::Rc;
+
+///
+/// Similar to [Model], except that the vertices are deformed by a number of joints, also called skinning.
+/// The mesh must contain [CPUMesh::joints] and [CPUMesh::weights] which specify up to four joints influencing each vertex.
+/// Use [set_joint_transformations](Self::set_joint_transformations) to animate the joints,
+/// for example using the transformations computed by [CPUSkin::joint_transformations].
+/// The mesh can also contain [CPUMesh::morph_targets] which are applied before the skinning.
+///
+#[derive(Clone)]
+pub struct SkinnedModel<M: Material> {
+    context: Context,
+    mesh: Rc<Mesh>,
+    aabb_local: AxisAlignedBoundingBox,
+    aabb: AxisAlignedBoundingBox,
+    transformation: Mat4,
+    texture_transform: Mat3,
+    morph_weights: Vec<f32>,
+    joint_transformations: Vec<Mat4>,
+    /// The material applied to the skinned model
+    pub material: M,
+}
+
+impl SkinnedModel<ColorMaterial> {
+    ///
+    /// Creates a new skinned 3D model with a triangle mesh as geometry, the given number of joints and a default [ColorMaterial].
+    ///
+    pub fn new(context: &Context, cpu_mesh: &CPUMesh, joint_count: usize) -> ThreeDResult<Self> {
+        Self::new_with_material(context, cpu_mesh, joint_count, ColorMaterial::default())
+    }
+}
+
+impl<M: Material> SkinnedModel<M> {
+    ///
+    /// Creates a new skinned 3D model with a triangle mesh as geometry, the given number of joints and the given material.
+    /// The joint transformations are initialised to the identity, ie. the mesh is rendered in the bind pose.
+    /// The number of joints must be at least one.
+    /// The joint transformations are sent to the vertex shader as uniforms, so the number of joints is also limited by the number of uniform vectors
+    /// available in the vertex shader of the graphics context, which is about 60 joints when only the minimum required by WebGL 2 is available.
+    ///
+    pub fn new_with_material(
+        context: &Context,
+        cpu_mesh: &CPUMesh,
+        joint_count: usize,
+        material: M,
+    ) -> ThreeDResult<Self> {
+        if joint_count == 0 {
+            Err(CoreError::NoJoints)?;
+        }
+        // Each joint transformation takes up four uniform vectors and the other uniforms in the vertex shader at most 16 and one for each morph target
+        let max_joint_count = context
+            .max_vertex_uniform_vectors()
+            .saturating_sub(16 + cpu_mesh.morph_targets.len())
+            / 4;
+        if joint_count > max_joint_count {
+            Err(CoreError::TooManyJoints(joint_count, max_joint_count))?;
+        }
+        if cpu_mesh.joints.is_none() {
+            Err(CoreError::MissingMeshBuffer("joint".to_string()))?;
+        }
+        if cpu_mesh.weights.is_none() {
+            Err(CoreError::MissingMeshBuffer("weight".to_string()))?;
+        }
+        let mesh = Rc::new(Mesh::new(context, cpu_mesh)?);
+        let aabb = cpu_mesh.compute_aabb();
+        Ok(Self {
+            context: context.clone(),
+            morph_weights: vec![0.0; mesh.morph_target_count],
+            mesh,
+            aabb,
+            aabb_local: aabb,
+            transformation: Mat4::identity(),
+            texture_transform: Mat3::identity(),
+            joint_transformations: vec![Mat4::identity(); joint_count],
+            material,
+        })
+    }
+
+    ///
+    /// Returns the transformation applied to the uv coordinates before sampling the textures of the material.
+    ///
+    pub fn texture_transform(&mut self) -> &Mat3 {
+        &self.texture_transform
+    }
+
+    ///
+    /// Sets the transformation applied to the uv coordinates before sampling the textures of the material,
+    /// for example [CPUMaterial::texture_transform].
+    ///
+    pub fn set_texture_transform(&mut self, texture_transform: Mat3) {
+        self.texture_transform = texture_transform;
+    }
+
+    ///
+    /// Returns the weight of each morph target, see [CPUMesh::morph_targets].
+    ///
+    pub fn morph_weights(&self) -> &[f32] {
+        &self.morph_weights
+    }
+
+    ///
+    /// Sets the weight of each morph target, see [Model::set_morph_weights].
+    ///
+    pub fn set_morph_weights(&mut self, morph_weights: &[f32]) -> ThreeDResult<()> {
+        if morph_weights.len() != self.morph_weights.len() {
+            Err(CoreError::InvalidMorphWeightCount(
+                morph_weights.len(),
+                self.morph_weights.len(),
+            ))?;
+        }
+        self.morph_weights = morph_weights.to_vec();
+        Ok(())
+    }
+
+    ///
+    /// Returns the transformation of each joint.
+    ///
+    pub fn joint_transformations(&self) -> &[Mat4] {
+        &self.joint_transformations
+    }
+
+    ///
+    /// Sets the transformation of each joint, ie. the transformation from the mesh in the bind pose to the deformed mesh in the local space of the model.
+    /// The number of transformations must be equal to the number of joints specified when constructing this model.
+    ///
+    pub fn set_joint_transformations(
+        &mut self,
+        joint_transformations: &[Mat4],
+    ) -> ThreeDResult<()> {
+        if joint_transformations.len() != self.joint_transformations.len() {
+            Err(CoreError::InvalidJointCount(
+                joint_transformations.len(),
+                self.joint_transformations.len(),
+            ))?;
+        }
+        self.joint_transformations = joint_transformations.to_vec();
+        self.update_aabb();
+        Ok(())
+    }
+
+    fn update_aabb(&mut self) {
+        let mut aabb = AxisAlignedBoundingBox::EMPTY;
+        for joint_transformation in self.joint_transformations.iter() {
+            let mut aabb2 = self.aabb_local;
+            aabb2.transform(&(self.transformation * joint_transformation));
+            aabb.expand_with_aabb(&aabb2);
+        }
+        self.aabb = aabb;
+    }
+
+    fn draw(
+        &self,
+        program: &Program,
+        render_states: RenderStates,
+        camera: &Camera,
+    ) -> ThreeDResult<()> {
+        program.use_uniform_array("jointTransformations", &self.joint_transformations)?;
+        program.use_attribute_vec4("joints", self.mesh.joint_buffer.as_ref().unwrap())?;
+        program.use_attribute_vec4("weights", self.mesh.weight_buffer.as_ref().unwrap())?;
+        Model::<M>::draw_mesh(
+            program,
+            render_states,
+            camera,
+            &self.mesh,
+            &self.transformation,
+            &self.texture_transform,
+            &self.morph_weights,
+        )
+    }
+
+    fn vertex_shader_source(&self, fragment_shader_source: &str) -> ThreeDResult<String> {
+        Ok(format!(
+            "#define USE_SKINNING\n#define JOINT_COUNT {}\n{}{}",
+            self.joint_transformations.len(),
+            Model::<M>::morph_target_defines(&self.mesh),
+            Model::<M>::vertex_shader_source(fragment_shader_source)?
+        ))
+    }
+}
+
+impl<M: Material> Geometry for SkinnedModel<M> {
+    fn aabb(&self) -> AxisAlignedBoundingBox {
+        self.aabb
+    }
+
+    fn transformation(&self) -> Mat4 {
+        self.transformation
+    }
+}
+
+impl<M: Material> GeometryMut for SkinnedModel<M> {
+    fn set_transformation(&mut self, transformation: Mat4) {
+        self.transformation = transformation;
+        self.update_aabb();
+    }
+}
+
+impl<M: Material> Shadable for SkinnedModel<M> {
+    fn render_with_material(
+        &self,
+        material: &dyn Material,
+        camera: &Camera,
+        lights: &[&dyn Light],
+    ) -> ThreeDResult<()> {
+        let fragment_shader_source =
+            material.fragment_shader_source(self.mesh.color_buffer.is_some(), lights);
+        self.context.program(
+            &self.vertex_shader_source(&fragment_shader_source)?,
+            &fragment_shader_source,
+            |program| {
+                material.use_uniforms(program, camera, lights)?;
+                self.draw(program, material.render_states(), camera)
+            },
+        )
+    }
+}
+
+impl<M: Material> Object for SkinnedModel<M> {
+    fn render(&self, camera: &Camera, lights: &[&dyn Light]) -> ThreeDResult<()> {
+        self.render_with_material(&self.material, camera, lights)
+    }
+
+    fn is_transparent(&self) -> bool {
+        self.material.is_transparent()
+    }
+}
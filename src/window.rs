@@ -20,6 +20,12 @@ mod glutin_window;
 #[cfg(all(feature = "glutin-window", not(target_arch = "wasm32")))]
 pub use glutin_window::*;
 
+#[cfg(all(feature = "glutin-window", not(target_arch = "wasm32")))]
+mod capture;
+#[doc(inline)]
+#[cfg(all(feature = "glutin-window", not(target_arch = "wasm32")))]
+pub use capture::*;
+
 #[cfg(all(feature = "glutin-window", not(target_arch = "wasm32")))]
 #[cfg_attr(
     docsrs,
@@ -186,6 +192,7 @@ pub struct FrameInput {
     pub events: Vec<Event>,
 
     /// Milliseconds since last frame.
+    /// When capturing frames on desktop with a fixed time step, this is always the simulated time step.
     pub elapsed_time: f64,
 
     /// Milliseconds accumulated time since start.
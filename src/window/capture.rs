@@ -0,0 +1,210 @@
+use crate::core::*;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+
+///
+/// Where the frames recorded by a [FrameCapture] are written.
+///
+pub enum CaptureOutput {
+    ///
+    /// Saves each captured frame as an image in the given directory, which is created if it does not exist.
+    /// The images are named using the prefix followed by the number of the captured frame, starting from zero and padded to six digits,
+    /// and the extension which also determines the image format, for example `frame_000000.png`, `frame_000001.png` and so on.
+    ///
+    #[cfg(feature = "image-io")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "image-io")))]
+    ImageSequence {
+        /// The directory where the images are saved.
+        directory: PathBuf,
+        /// The prefix of the file name of each image.
+        prefix: String,
+        /// The file extension of each image.
+        extension: String,
+    },
+    ///
+    /// Writes the pixels of each captured frame to the standard input of an external process, for example a video encoder.
+    /// The closure is called with the width and height in physical pixels of the first captured frame and returns the command used to spawn the process.
+    /// The pixels are written as raw 8 bit RGBA values starting with the top row, so for example ffmpeg can be spawned with the arguments
+    /// `-f rawvideo -pixel_format rgba -video_size {width}x{height} -framerate 60 -i - video.mp4`.
+    /// All of the frames have the size of the first captured frame, so if the window is resized, the top left part of the window is captured
+    /// and the part of the frame outside the window is black.
+    ///
+    Process(Box<dyn FnMut(u32, u32) -> Command>),
+}
+
+///
+/// Settings for recording the frames rendered in the render loop of a [Window](crate::Window), see [Window::set_frame_capture](crate::Window::set_frame_capture).
+/// Construct it using [FrameCapture::image_sequence] or [FrameCapture::process] and adjust the settings afterwards if needed.
+///
+pub struct FrameCapture {
+    /// Where the captured frames are written.
+    pub output: CaptureOutput,
+    /// The simulated time in milliseconds between two frames or `None` to use the real time.
+    /// If specified, this is used as the [FrameInput::elapsed_time](crate::FrameInput::elapsed_time) of every frame,
+    /// so the animations are deterministic and independent of how long it takes to render and capture each frame.
+    pub time_step: Option<f64>,
+    /// Only every n-th frame is captured, counting from the first frame after the start time.
+    pub frame_interval: u32,
+    /// The [FrameInput::accumulated_time](crate::FrameInput::accumulated_time) in milliseconds where the capture starts.
+    pub start_time: f64,
+    /// The [FrameInput::accumulated_time](crate::FrameInput::accumulated_time) in milliseconds where the capture ends or `None` to capture until the window is closed.
+    pub end_time: Option<f64>,
+    /// Whether to close the window when the end time is reached.
+    pub exit_when_done: bool,
+}
+
+impl FrameCapture {
+    ///
+    /// Captures every frame as a PNG image in the given directory with a simulated frame rate of 60 frames per second,
+    /// see [CaptureOutput::ImageSequence].
+    ///
+    #[cfg(feature = "image-io")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "image-io")))]
+    pub fn image_sequence(directory: impl Into<PathBuf>) -> Self {
+        Self::new(CaptureOutput::ImageSequence {
+            directory: directory.into(),
+            prefix: "frame_".to_string(),
+            extension: "png".to_string(),
+        })
+    }
+
+    ///
+    /// Captures every frame by writing it to the standard input of the process spawned from the command returned by the given closure
+    /// with a simulated frame rate of 60 frames per second, see [CaptureOutput::Process].
+    ///
+    pub fn process(command: impl FnMut(u32, u32) -> Command + 'static) -> Self {
+        Self::new(CaptureOutput::Process(Box::new(command)))
+    }
+
+    fn new(output: CaptureOutput) -> Self {
+        Self {
+            output,
+            time_step: Some(1000.0 / 60.0),
+            frame_interval: 1,
+            start_time: 0.0,
+            end_time: None,
+            exit_when_done: false,
+        }
+    }
+}
+
+///
+/// The state of an ongoing [FrameCapture].
+///
+pub(super) struct FrameCapturer {
+    capture: FrameCapture,
+    frame_count: u32,
+    captured_frame_count: u32,
+    process: Option<(Child, Viewport)>,
+    done: bool,
+}
+
+impl FrameCapturer {
+    pub fn new(capture: FrameCapture) -> Self {
+        Self {
+            capture,
+            frame_count: 0,
+            captured_frame_count: 0,
+            process: None,
+            done: false,
+        }
+    }
+
+    ///
+    /// Returns the elapsed time to use for the next frame given the measured elapsed time.
+    ///
+    pub fn elapsed_time(&self, measured_elapsed_time: f64) -> f64 {
+        self.capture.time_step.unwrap_or(measured_elapsed_time)
+    }
+
+    ///
+    /// Captures the frame which has just been rendered to the screen if it is within the time range and is not skipped by the frame interval.
+    /// Returns whether the window should be closed because the capture is done.
+    ///
+    pub fn capture(
+        &mut self,
+        context: &Context,
+        viewport: Viewport,
+        accumulated_time: f64,
+    ) -> ThreeDResult<bool> {
+        if self.done || accumulated_time < self.capture.start_time {
+            return Ok(false);
+        }
+        if let Some(end_time) = self.capture.end_time {
+            if accumulated_time > end_time {
+                self.finish()?;
+                return Ok(self.capture.exit_when_done);
+            }
+        }
+        let frame_index = self.frame_count;
+        self.frame_count += 1;
+        if !frame_index.is_multiple_of(self.capture.frame_interval.max(1)) {
+            return Ok(false);
+        }
+
+        match self.capture.output {
+            #[cfg(feature = "image-io")]
+            CaptureOutput::ImageSequence {
+                ref directory,
+                ref prefix,
+                ref extension,
+            } => {
+                std::fs::create_dir_all(directory)?;
+                let path = directory.join(format!(
+                    "{}{:06}.{}",
+                    prefix, self.captured_frame_count, extension
+                ));
+                let pixels = crate::Screen::read_color(context, viewport)?;
+                crate::Saver::save_pixels(path, &pixels, viewport.width, viewport.height)?;
+            }
+            CaptureOutput::Process(ref mut command) => {
+                if self.process.is_none() {
+                    let child = command(viewport.width, viewport.height)
+                        .stdin(Stdio::piped())
+                        .spawn()?;
+                    self.process = Some((child, viewport));
+                }
+                let (child, frame_viewport) = self.process.as_mut().unwrap();
+                // Only the top left part of the window which fits within the frame is read if the window has been resized
+                let width = frame_viewport.width.min(viewport.width);
+                let height = frame_viewport.height.min(viewport.height);
+                let pixels = crate::Screen::read_color(
+                    context,
+                    Viewport {
+                        x: 0,
+                        y: (viewport.height - height) as i32,
+                        width,
+                        height,
+                    },
+                )?;
+                let padding = vec![0u8; (frame_viewport.width - width) as usize * 4];
+                let stdin = child.stdin.as_mut().unwrap();
+                // The pixels are read from the bottom row to the top row
+                for row in pixels.chunks_exact(width as usize * 4).rev() {
+                    stdin.write_all(row)?;
+                    stdin.write_all(&padding)?;
+                }
+                let empty_row = vec![0u8; frame_viewport.width as usize * 4];
+                for _ in height..frame_viewport.height {
+                    stdin.write_all(&empty_row)?;
+                }
+            }
+        }
+        self.captured_frame_count += 1;
+        Ok(false)
+    }
+
+    ///
+    /// Ends the capture and waits for the external process, if any, to finish.
+    ///
+    pub fn finish(&mut self) -> ThreeDResult<()> {
+        self.done = true;
+        if let Some((mut child, _)) = self.process.take() {
+            // Closing the standard input signals the end of the stream
+            drop(child.stdin.take());
+            child.wait()?;
+        }
+        Ok(())
+    }
+}
@@ -28,6 +28,7 @@ pub struct Window {
     windowed_context: ContextWrapper<PossiblyCurrent, window::Window>,
     event_loop: EventLoop<()>,
     gl: crate::Context,
+    frame_capture: Option<FrameCapture>,
 }
 
 impl Window {
@@ -50,6 +51,7 @@ impl Window {
             windowed_context,
             event_loop,
             gl: crate::core::Context::from_gl_context(context),
+            frame_capture: None,
         })
     }
 
@@ -88,6 +90,14 @@ impl Window {
             .build_windowed(window_builder, event_loop)?)
     }
 
+    ///
+    /// Records the frames rendered in the render loop as specified by the given settings, for example into an image sequence or a video.
+    /// Must be called before [render_loop](Self::render_loop).
+    ///
+    pub fn set_frame_capture(&mut self, frame_capture: FrameCapture) {
+        self.frame_capture = Some(frame_capture);
+    }
+
     ///
     /// Start the main render loop which calls the `callback` closure each frame.
     ///
@@ -104,9 +114,15 @@ impl Window {
         let mut first_frame = true;
         let mut mouse_pressed = None;
         let context = self.gl.clone();
+        let mut frame_capturer = self.frame_capture.map(FrameCapturer::new);
         self.event_loop.run(move |event, _, control_flow| {
             match event {
                 Event::LoopDestroyed => {
+                    if let Some(ref mut frame_capturer) = frame_capturer {
+                        if let Err(e) = frame_capturer.finish() {
+                            log::error!("Failed to finish the frame capture: {}", e);
+                        }
+                    }
                     return;
                 }
                 Event::MainEventsCleared => {
@@ -116,8 +132,11 @@ impl Window {
                     let now = std::time::Instant::now();
                     let duration = now.duration_since(last_time);
                     last_time = now;
-                    let elapsed_time =
+                    let mut elapsed_time =
                         duration.as_secs() as f64 * 1000.0 + duration.subsec_nanos() as f64 * 1e-6;
+                    if let Some(ref frame_capturer) = frame_capturer {
+                        elapsed_time = frame_capturer.elapsed_time(elapsed_time);
+                    }
                     accumulated_time += elapsed_time;
 
                     let (physical_width, physical_height): (u32, u32) =
@@ -141,7 +160,27 @@ impl Window {
                     first_frame = false;
                     events.clear();
                     let frame_output = callback(frame_input);
-                    if frame_output.exit {
+                    let mut capture_done = false;
+                    if let Some(ref mut capturer) = frame_capturer {
+                        match capturer.capture(
+                            &context,
+                            Viewport::new_at_origo(physical_width, physical_height),
+                            accumulated_time,
+                        ) {
+                            Ok(done) => capture_done = done,
+                            Err(e) => {
+                                log::error!(
+                                    "Failed to capture a frame, stopping the capture: {}",
+                                    e
+                                );
+                                if let Err(e) = capturer.finish() {
+                                    log::error!("Failed to finish the frame capture: {}", e);
+                                }
+                                frame_capturer = None;
+                            }
+                        }
+                    }
+                    if frame_output.exit || capture_done {
                         *control_flow = ControlFlow::Exit;
                     } else {
                         if frame_output.swap_buffers {